@@ -1,50 +1,1381 @@
-use crate::app::event_state::EventState;
+use crate::app::event_state::{EventState, Mode, Tab};
 use crate::app::input::CursorPosition;
-use crate::app::state::{StatefulList, TabsState};
+use crate::app::picker::{NamespacePicker, PickAction};
+use crate::app::state::{Areas, StatefulList, TabsState};
+use crate::app::status::Status;
+use crate::app::undo::{Change, Item, UndoStack};
+use crate::cli::export;
+use crate::core::clipboard;
 use crate::core::config::Config;
+use crate::core::fanout::{self, FanOut};
+use crate::core::hooks::{self, HookContext, PreHooks};
+use crate::core::params;
+use crate::core::preview::Preview;
+use crate::core::process::{Limits, Process};
+use crate::core::risk::Risk;
+use crate::core::runner::{self, RunOptions};
+use crate::core::schedule::ScheduledCommand;
+use crate::core::settings::{expand_home, Settings};
+use crate::core::watch::{WatchedCommand, Watcher};
+use crate::core::workflow::{HistoryEntry, Step, StepResult, Workflow};
+use crate::db::command_file::{CommandFile, FileCommand};
 use crate::db::db::Db;
+use crate::db::namespace::{Favorite, Namespace, Origin, TrashEntry};
+use crate::db::project::Project;
+use crate::db::sync;
+use crossterm::event::KeyCode;
 
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::error::Error;
+use std::fs;
+use std::io::{ErrorKind, Write};
 use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// Runs listed in the History tab.
+const HISTORY_LIMIT: usize = 500;
+/// Markdown file written in the current directory by the export of marked commands.
+const EXPORT_FILE: &str = "commands.md";
 
 pub struct App {
     pub config: Config,
+    pub settings: Settings,
+    pub profile: Option<String>,
     pub tabs: Rc<RefCell<TabsState>>,
     pub db: Db,
+    pub project: Option<Project>,
     pub event_state: EventState,
     pub inputs: HashMap<String, Vec<char>>,
-    pub namespaces: Rc<RefCell<StatefulList<String>>>,
+    pub namespaces: Rc<RefCell<StatefulList<Namespace>>>,
     pub commands: Rc<RefCell<StatefulList<String>>>,
     pub tags: Rc<RefCell<StatefulList<String>>>,
+    /// Latest runs, read again when the History tab is shown.
+    pub history: Rc<RefCell<StatefulList<HistoryEntry>>>,
+    /// Starred commands, read again when the Favorites tab is shown.
+    pub favorites: Rc<RefCell<StatefulList<Favorite>>>,
+    /// Deleted commands and namespaces, read again when the Trash tab is shown.
+    pub trash: Rc<RefCell<StatefulList<TrashEntry>>>,
+    /// Stored risk levels by tag and those of the workflows, other commands are detected.
+    pub risks: HashMap<String, Risk>,
+    /// Run options by tag, commands without any run unlimited.
+    pub run_options: HashMap<String, RunOptions>,
+    /// Rows of the Schedule tab, read when it is opened.
+    pub schedules: Vec<ScheduledCommand>,
+    /// Rows of the Settings tab, resolved when it is opened.
+    pub settings_summary: Vec<(String, String)>,
     pub cursor_position: Option<CursorPosition>,
-    pub error: Option<String>,
+    pub output: Option<Process>,
+    /// Lines scrolled back from the end of the output, `None` follows it.
+    pub output_scroll: Option<u16>,
+    /// Lines scrolled down in the details of a command, by tag.
+    pub details_scroll: Option<(String, u16)>,
+    /// Command run again in the output pane when files change.
+    pub watch: Option<WatchedCommand>,
+    pub jobs: Rc<RefCell<StatefulList<Process>>>,
+    /// Lines scrolled back in the output of the selected job.
+    pub job_scroll: Option<u16>,
+    pub fanout: Option<FanOut>,
+    /// Workflow confirmed in the interface, run once the terminal is restored.
+    pub workflow: Option<String>,
+    pub sync_conflicts: Vec<String>,
+    /// Marked commands confirmed in the interface, run once the terminal is restored.
+    pub sequence: Option<Workflow>,
+    /// Namespaces offered while moving, duplicating or merging.
+    pub picker: Option<NamespacePicker>,
+    /// Changes of the library made in this session, to undo and redo them.
+    pub changes: UndoStack,
+    /// Message of the status bar, errors of the interface are shown there.
+    pub status: Status,
+    /// Keys of the current mode shown over the interface.
+    pub help: bool,
+    /// Where the panels were last drawn, to map the mouse events.
+    pub areas: Areas,
+    /// Time and cell of the last click, a second one there soon after is a double-click.
+    pub last_click: Option<(Instant, u16, u16)>,
+    /// First key of a two-key command, like `g` of `gg`.
+    pub pending_key: Option<KeyCode>,
     pub quit: bool,
 }
 
 impl App {
-    pub fn new() -> Result<Self, Box<dyn Error>> {
+    pub fn new(profile: Option<String>) -> Result<Self, Box<dyn Error>> {
         let config = Config::new();
+        let settings = Settings::load()?;
 
-        let db = Db::new()?;
+        let db = Db::new(&settings, profile.as_deref())?;
         db.init_db()?;
+        db.purge_expired_trash(&settings)?;
+
+        let profile = match profile {
+            Some(name) => Some(name),
+            None if std::env::var("CM_DB").is_ok() => None,
+            None => settings.profile.clone(),
+        };
+
+        let project = Project::discover(&std::env::current_dir()?)?;
+
+        let sync_conflicts = match &settings.sync {
+            Some(sync_settings) => sync::get_conflicts(&expand_home(&sync_settings.dir))?,
+            None => vec![],
+        };
 
-        let namespaces = db.get_namespaces()?;
-        let (commands, tags) = db.get_commands_and_tags(None)?;
+        App::with_db(config, settings, profile, db, project, sync_conflicts)
+    }
+
+    /// An interface over an empty library in memory, without a project or a sync directory.
+    #[cfg(test)]
+    pub fn in_memory() -> Result<Self, Box<dyn Error>> {
+        App::with_db(
+            Config::new(),
+            Settings::default(),
+            None,
+            Db::in_memory()?,
+            None,
+            vec![],
+        )
+    }
 
-        Ok(App {
+    fn with_db(
+        config: Config,
+        settings: Settings,
+        profile: Option<String>,
+        db: Db,
+        project: Option<Project>,
+        sync_conflicts: Vec<String>,
+    ) -> Result<Self, Box<dyn Error>> {
+        let mut app = App {
             tabs: Rc::new(RefCell::new(TabsState::new(&config))),
             config,
+            settings,
+            profile,
             db,
+            project,
             event_state: EventState::default(),
             inputs: HashMap::new(),
-            namespaces: Rc::new(RefCell::new(StatefulList::with_items(namespaces))),
-            commands: Rc::new(RefCell::new(StatefulList::with_items(commands))),
-            tags: Rc::new(RefCell::new(StatefulList::with_items(tags))),
+            namespaces: Rc::new(RefCell::new(StatefulList::with_items(vec![]))),
+            commands: Rc::new(RefCell::new(StatefulList::with_items(vec![]))),
+            tags: Rc::new(RefCell::new(StatefulList::with_items(vec![]))),
+            history: Rc::new(RefCell::new(StatefulList::with_items(vec![]))),
+            favorites: Rc::new(RefCell::new(StatefulList::with_items(vec![]))),
+            trash: Rc::new(RefCell::new(StatefulList::with_items(vec![]))),
+            risks: HashMap::new(),
+            run_options: HashMap::new(),
+            schedules: vec![],
+            settings_summary: vec![],
             cursor_position: None,
-            error: None,
+            output: None,
+            output_scroll: None,
+            details_scroll: None,
+            watch: None,
+            jobs: Rc::new(RefCell::new(StatefulList::with_items(vec![]))),
+            job_scroll: None,
+            fanout: None,
+            workflow: None,
+            sync_conflicts,
+            sequence: None,
+            picker: None,
+            changes: UndoStack::default(),
+            status: Status::default(),
+            help: false,
+            areas: Areas::default(),
+            last_click: None,
+            pending_key: None,
             quit: false,
+        };
+
+        app.load_lists()?;
+
+        Ok(app)
+    }
+
+    fn load_lists(&mut self) -> Result<(), Box<dyn Error>> {
+        // Favorites are pinned at the top of the commands
+        App::replace_items(&self.favorites, self.db.get_favorites()?);
+
+        let namespaces = self.get_namespaces()?;
+        let (commands, tags) = self.get_commands_and_tags(namespaces.first())?;
+
+        self.namespaces = Rc::new(RefCell::new(StatefulList::with_items(namespaces)));
+        self.commands = Rc::new(RefCell::new(StatefulList::with_items(commands)));
+        self.tags = Rc::new(RefCell::new(StatefulList::with_items(tags)));
+
+        self.load_command_settings()
+    }
+
+    /// Reads the risk levels and run options set by tag, those of the project file win.
+    /// Workflows get the level of their riskiest step, so drawing them reads no database.
+    fn load_command_settings(&mut self) -> Result<(), Box<dyn Error>> {
+        self.risks = self.db.get_risks()?;
+        self.run_options = self.db.get_run_options()?;
+        if let Some(project) = &self.project {
+            for command in &project.commands {
+                if let Some(risk) = command.risk {
+                    self.risks.insert(command.tag.clone(), risk);
+                }
+                if command.options != RunOptions::default() {
+                    self.run_options
+                        .insert(command.tag.clone(), command.options.clone());
+                }
+            }
+        }
+
+        for workflow in self.db.get_workflows()? {
+            let risk = workflow.get_risk(&self.risks);
+            self.risks.insert(workflow.tag, risk);
+        }
+
+        Ok(())
+    }
+
+    /// Polls the running commands, the end of a long one is notified, recorded in the history
+    /// and post-hooks are run. A watched command is run again once files have changed.
+    pub fn tick(&mut self) {
+        if self
+            .watch
+            .as_mut()
+            .is_some_and(|watch| watch.watcher.poll().is_some())
+        {
+            self.rerun_watched();
+        }
+
+        let settings = &self.settings;
+        let db = &self.db;
+        let mut results = Vec::new();
+
+        if let Some(process) = self.output.as_mut() {
+            if process.poll() {
+                results.push(App::finish(
+                    settings,
+                    db,
+                    &process.tag,
+                    process.get_hooks(),
+                    process.get_exit_code(),
+                    process.get_duration(),
+                ));
+            }
+        }
+
+        for job in self.jobs.as_ref().borrow_mut().items.iter_mut() {
+            if job.poll() {
+                results.push(App::finish(
+                    settings,
+                    db,
+                    &job.tag,
+                    job.get_hooks(),
+                    job.get_exit_code(),
+                    job.get_duration(),
+                ));
+            }
+        }
+
+        if let Some(fanout) = self.fanout.as_mut() {
+            if fanout.tick() {
+                results.push(App::finish(
+                    settings,
+                    db,
+                    &fanout.tag,
+                    fanout.get_hooks(),
+                    fanout.get_exit_code(),
+                    fanout.get_duration(),
+                ));
+            }
+        }
+
+        if results.is_empty() {
+            return;
+        }
+
+        for result in results {
+            if let Err(e) = result {
+                self.status.error(e);
+            }
+        }
+
+        if self.event_state.get_tab() == &Tab::History {
+            self.load_tab(Tab::History);
+        }
+    }
+
+    fn finish(
+        settings: &Settings,
+        db: &Db,
+        tag: &str,
+        context: Option<&HookContext>,
+        exit_code: Option<i32>,
+        duration: Duration,
+    ) -> Result<(), Box<dyn Error>> {
+        runner::notify(&settings.notify, tag, exit_code, duration);
+
+        let context = match context {
+            Some(context) => context,
+            None => return Ok(()),
+        };
+
+        hooks::run_post(&settings.hooks, context, exit_code, duration, false);
+
+        let result = StepResult {
+            tag: tag.to_string(),
+            exit_code,
+            duration,
+        };
+        db.add_history(&result, &context.command, None)
+    }
+
+    /// Hooks of a command started in the interface, its pre-hooks run on the thread of the
+    /// command.
+    fn get_hooks(&self, command: &str, tag: &str) -> (HookContext, PreHooks) {
+        let namespace = self.get_current_namespace_name();
+        let context = HookContext::new(tag, command, namespace.as_deref());
+        let pre_hooks = PreHooks::new(&self.settings.hooks, &context);
+
+        (context, pre_hooks)
+    }
+
+    /// Runs a command in the directories of `spec`, unless the previous fan-out is still running.
+    /// Returns false when nothing was started.
+    pub fn start_fanout(
+        &mut self,
+        command: &str,
+        tag: &str,
+        spec: &str,
+    ) -> Result<bool, Box<dyn Error>> {
+        if self
+            .fanout
+            .as_ref()
+            .is_some_and(|fanout| fanout.is_running())
+        {
+            return Ok(false);
+        }
+
+        let dirs = fanout::resolve_directories(spec, &self.settings.dir_sets)?;
+        if dirs.is_empty() {
+            return Ok(false);
+        }
+
+        let command = params::substitute(command, &BTreeMap::new())?;
+        let (context, pre_hooks) = self.get_hooks(&command, tag);
+        self.fanout = Some(
+            FanOut::new(
+                &command,
+                tag,
+                dirs,
+                self.settings.fanout.max_jobs,
+                self.get_limits(tag),
+            )
+            .with_hooks(context, pre_hooks),
+        );
+
+        Ok(true)
+    }
+
+    /// Copies the selected command to the clipboard, with its placeholders replaced from the
+    /// environment or their default when `substitute`.
+    pub fn copy_command(&mut self, substitute: bool) -> Result<(), Box<dyn Error>> {
+        let command = self.commands.as_ref().borrow().current_item().clone();
+        let tag = self.tags.as_ref().borrow().current_item().clone();
+
+        let command = match substitute {
+            true => params::substitute(&command, &BTreeMap::new())?,
+            false => command,
+        };
+        let method = clipboard::copy(&command)?;
+        self.status.info(format!("{} copied ({})", tag, method));
+
+        Ok(())
+    }
+
+    /// Starts a command as a background job, jobs are killed when cm exits.
+    pub fn start_job(&mut self, command: &str, tag: &str) -> Result<(), Box<dyn Error>> {
+        let command = params::substitute(command, &BTreeMap::new())?;
+        let (context, pre_hooks) = self.get_hooks(&command, tag);
+        let dir = self.get_run_options(tag).get_dir();
+        let job = Process::spawn_in(
+            &command,
+            tag,
+            dir.as_deref(),
+            Some(pre_hooks),
+            self.get_limits(tag),
+        )?
+        .with_hooks(context);
+        self.jobs.as_ref().borrow_mut().items.push(job);
+
+        Ok(())
+    }
+
+    /// Runs a command in the output pane, again whenever files of its working directory change.
+    pub fn start_watch(&mut self, command: &str, tag: &str) -> Result<(), Box<dyn Error>> {
+        let dir = self
+            .get_run_options(tag)
+            .get_dir()
+            .map_or_else(std::env::current_dir, Ok)?;
+        let debounce = Duration::from_millis(self.settings.watch.debounce_ms);
+        let watcher = Watcher::new(&[], &dir, debounce)?;
+
+        self.watch = Some(WatchedCommand {
+            tag: tag.to_string(),
+            command: command.to_string(),
+            watcher,
+        });
+        self.rerun_watched();
+
+        Ok(())
+    }
+
+    /// Replaces the output by a new run of the watched command, a running one is killed.
+    fn rerun_watched(&mut self) {
+        let (command, tag) = match &self.watch {
+            Some(watch) => (watch.command.clone(), watch.tag.clone()),
+            None => return,
+        };
+
+        self.output = None;
+        if let Err(e) = self.run_in_output(&command, &tag) {
+            self.status.error(e);
+        }
+    }
+
+    /// Runs a command in the output pane, unless the previous one is still running.
+    pub fn run_in_output(&mut self, command: &str, tag: &str) -> Result<(), Box<dyn Error>> {
+        if self
+            .output
+            .as_ref()
+            .is_some_and(|process| process.is_running())
+        {
+            return Ok(());
+        }
+
+        let command = params::substitute(command, &BTreeMap::new())?;
+        let (context, pre_hooks) = self.get_hooks(&command, tag);
+        let dir = self.get_run_options(tag).get_dir();
+        self.output = Some(
+            Process::spawn_in(
+                &command,
+                tag,
+                dir.as_deref(),
+                Some(pre_hooks),
+                self.get_limits(tag),
+            )?
+            .with_hooks(context),
+        );
+        self.output_scroll = None;
+
+        Ok(())
+    }
+
+    /// Shows a tab, the runs or favorites it lists are read again.
+    pub fn set_tab(&mut self, tab: Tab) {
+        self.tabs.as_ref().borrow_mut().index = tab.get_index();
+        self.event_state.set_tab(tab);
+        self.load_tab(tab);
+    }
+
+    pub fn next_tab(&mut self) {
+        let index = (self.event_state.get_tab().get_index() + 1) % Tab::ALL.len();
+        self.set_tab(Tab::from_index(index));
+    }
+
+    pub fn previous_tab(&mut self) {
+        let index = (self.event_state.get_tab().get_index() + Tab::ALL.len() - 1) % Tab::ALL.len();
+        self.set_tab(Tab::from_index(index));
+    }
+
+    fn load_tab(&mut self, tab: Tab) {
+        let result = match tab {
+            Tab::History => self.db.get_history(HISTORY_LIMIT).map(|history| {
+                App::replace_items(&self.history, history);
+            }),
+            Tab::Favorites => self.db.get_favorites().map(|favorites| {
+                App::replace_items(&self.favorites, favorites);
+            }),
+            Tab::Trash => self.db.get_trash().map(|trash| {
+                App::replace_items(&self.trash, trash);
+            }),
+            Tab::Schedule => ScheduledCommand::load(&self.db).map(|schedules| {
+                self.schedules = schedules;
+            }),
+            Tab::Settings => {
+                self.settings_summary = self.get_settings_summary();
+                Ok(())
+            }
+            _ => Ok(()),
+        };
+
+        if let Err(e) = result {
+            self.status.error(e);
+        }
+    }
+
+    /// Keeps the selection on the same row, or on the last one when the list got shorter.
+    fn replace_items<T>(list: &Rc<RefCell<StatefulList<T>>>, items: Vec<T>) {
+        let mut list = list.as_ref().borrow_mut();
+
+        let selected = match items.len() {
+            0 => None,
+            len => Some(list.state.selected().map_or(0, |index| index.min(len - 1))),
+        };
+        list.set_items(items);
+        list.state.select(selected);
+    }
+
+    /// Stars the marked commands of the personal library, or removes their star when they all
+    /// have one. They move in or out of the pinned ones and the selected command stays selected.
+    pub fn toggle_favorite(&mut self) -> Result<(), Box<dyn Error>> {
+        let tag = self.tags.as_ref().borrow().current_item().clone();
+        let marked = self
+            .get_marked_commands()
+            .into_iter()
+            .map(|(_, tag)| tag)
+            .collect::<Vec<_>>();
+
+        let favorite = marked.iter().all(|tag| self.is_favorite(tag));
+        let mut reverts = Vec::new();
+        for tag in &marked {
+            // Only the stars actually changed are given back on undo
+            if self.is_favorite(tag) == favorite {
+                self.db.set_favorite(tag, !favorite)?;
+                reverts.push(Change::Star {
+                    tag: tag.clone(),
+                    favorite,
+                });
+            }
+        }
+        self.changes.record(reverts);
+        App::replace_items(&self.favorites, self.db.get_favorites()?);
+
+        let namespace = self.namespaces.as_ref().borrow().current_item().clone();
+        let (commands, tags) = self.get_commands_and_tags(Some(&namespace))?;
+        let index = tags.iter().position(|item| item == &tag);
+
+        for (list, items) in [(&self.commands, commands), (&self.tags, tags)] {
+            let mut list = list.as_ref().borrow_mut();
+            list.set_items(items);
+            list.state.select(index);
+        }
+
+        if favorite {
+            self.status
+                .info(format!("{} removed from favorites", marked.join(", ")));
+        } else {
+            self.status
+                .info(format!("{} added to favorites", marked.join(", ")));
+        }
+
+        Ok(())
+    }
+
+    /// Marked commands of the current namespace as `(command, tag)`, the selected one when
+    /// none is marked.
+    pub fn get_marked_commands(&self) -> Vec<(String, String)> {
+        let commands = self.commands.as_ref().borrow();
+        let tags = self.tags.as_ref().borrow();
+
+        tags.get_marked()
+            .into_iter()
+            .filter_map(|index| {
+                Some((
+                    commands.items.get(index)?.clone(),
+                    tags.items.get(index)?.clone(),
+                ))
+            })
+            .collect()
+    }
+
+    /// Marked namespaces of the personal library, the selected one when none is marked.
+    pub fn get_marked_namespaces(&self) -> Vec<String> {
+        let namespaces = self.namespaces.as_ref().borrow();
+
+        namespaces
+            .get_marked()
+            .into_iter()
+            .filter_map(|index| namespaces.items.get(index))
+            .filter(|namespace| namespace.origin == Origin::Personal)
+            .map(|namespace| namespace.name.clone())
+            .collect()
+    }
+
+    fn is_in_commands(&self) -> bool {
+        self.commands.as_ref().borrow().state.selected().is_some()
+    }
+
+    /// Question and affected items of the confirmation of a bulk action.
+    pub fn get_bulk_summary(&self) -> (String, Vec<String>) {
+        let commands = self.get_marked_commands();
+
+        match self.event_state.get_mode() {
+            Mode::Sequence => (
+                format!(
+                    "Run {} command(s) one after the other, until one fails ? (press Esc to cancel)",
+                    commands.len()
+                ),
+                commands
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (command, tag))| format!("{}. {}: {}", i + 1, tag, command))
+                    .collect(),
+            ),
+            _ if self.is_in_commands() => (
+                format!("Delete {} command(s) ? (press Esc to cancel)", commands.len()),
+                commands
+                    .into_iter()
+                    .map(|(command, tag)| format!("{}: {}", tag, command))
+                    .collect(),
+            ),
+            _ => (
+                "Delete these namespaces and their commands ? (press Esc to cancel)".to_string(),
+                self.get_marked_namespaces(),
+            ),
+        }
+    }
+
+    /// Moves the marked commands, or the marked namespaces with their commands, to the trash.
+    pub fn delete_marked(&mut self) -> Result<(), Box<dyn Error>> {
+        let namespace = self
+            .namespaces
+            .as_ref()
+            .borrow()
+            .current_item()
+            .name
+            .clone();
+
+        let items = if self.is_in_commands() {
+            self.get_marked_commands()
+                .into_iter()
+                .map(|(_, tag)| Item::Command(tag))
+                .collect::<Vec<_>>()
+        } else {
+            self.get_marked_namespaces()
+                .into_iter()
+                .map(Item::Namespace)
+                .collect()
+        };
+
+        let names = items.iter().map(Item::to_string).collect::<Vec<_>>();
+        self.perform(items.into_iter().map(Change::Trash).collect())?;
+        self.status
+            .info(format!("{} moved to the trash", names.join(", ")));
+
+        self.reload_library(&namespace)
+    }
+
+    /// Writes the marked commands, or the commands of the marked namespaces, as a Markdown
+    /// cheatsheet in the current directory, unless the file already exists.
+    pub fn export_marked(&mut self) -> Result<(), Box<dyn Error>> {
+        let mut files = self.db.export_files()?;
+
+        if self.is_in_commands() {
+            let namespace = self.namespaces.as_ref().borrow().current_item().clone();
+            // Project commands come with their settings, others are read from the library
+            let stored = match &self.project {
+                Some(project) if namespace.origin == Origin::Project => project.commands.clone(),
+                _ => files
+                    .into_iter()
+                    .find(|file| file.name.as_ref() == Some(&namespace.name))
+                    .map(|file| file.commands)
+                    .unwrap_or_default(),
+            };
+            let commands = self
+                .get_marked_commands()
+                .into_iter()
+                .map(|(command, tag)| {
+                    stored
+                        .iter()
+                        .find(|stored| stored.tag == tag)
+                        .cloned()
+                        .unwrap_or(FileCommand {
+                            tag,
+                            command,
+                            ..FileCommand::default()
+                        })
+                })
+                .collect();
+
+            files = vec![CommandFile {
+                name: Some(namespace.name),
+                commands,
+            }];
+        } else {
+            let marked = self.get_marked_namespaces();
+            files.retain(|file| file.name.as_ref().is_some_and(|name| marked.contains(name)));
+        }
+
+        let count = files.iter().map(|file| file.commands.len()).sum::<usize>();
+        let content = export::render_markdown(&self.config.name_config.app_title, &files);
+
+        // An existing file is never replaced, it may not come from cm
+        let mut file = match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(EXPORT_FILE)
+        {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                self.status.warn(format!(
+                    "{} already exists, nothing was exported",
+                    EXPORT_FILE
+                ));
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        };
+        file.write_all(content.as_bytes())?;
+
+        self.status
+            .info(format!("{} command(s) exported to {}", count, EXPORT_FILE));
+        Ok(())
+    }
+
+    /// Adds `prefix` to the tags of the marked commands, e.g. `legacy:` to set them apart.
+    pub fn prefix_marked_tags(&mut self, prefix: &str) -> Result<(), Box<dyn Error>> {
+        let changes = self
+            .get_marked_commands()
+            .into_iter()
+            .map(|(_, tag)| Change::RenameTag {
+                name: format!("{}{}", prefix, tag),
+                tag,
+            })
+            .collect::<Vec<_>>();
+        let count = changes.len();
+
+        // Tags renamed before a failure are kept, they can be undone. Favorites and the settings
+        // of the commands are read again under their new tags.
+        let result = self.perform(changes);
+        self.refresh_library()?;
+        result?;
+
+        self.status
+            .info(format!("{} command(s) tagged with {}", count, prefix));
+        Ok(())
+    }
+
+    /// Marked commands as a workflow run once cm exits, `None` when one of them is dangerous.
+    pub fn get_sequence(&mut self) -> Option<Workflow> {
+        let namespace = self.namespaces.as_ref().borrow().current_item().clone();
+        let commands = self.get_marked_commands();
+
+        if commands
+            .iter()
+            .any(|(command, tag)| self.get_risk(&namespace, command, tag) == Risk::Dangerous)
+        {
+            self.status
+                .warn(self.config.text_config.dangerous_refused.clone());
+            return None;
+        }
+
+        Some(Workflow {
+            tag: self.config.name_config.sequence_tag.clone(),
+            continue_on_failure: false,
+            steps: commands
+                .into_iter()
+                .map(|(command, tag)| Step {
+                    tag,
+                    command: Some(command),
+                })
+                .collect(),
         })
     }
+
+    pub fn is_favorite(&self, tag: &str) -> bool {
+        self.favorites
+            .as_ref()
+            .borrow()
+            .items
+            .iter()
+            .any(|favorite| favorite.tag == tag)
+    }
+
+    /// Selects a favorite in the Library tab, where every action on it is available.
+    pub fn go_to_favorite(&mut self, favorite: &Favorite) -> Result<(), Box<dyn Error>> {
+        let namespaces = self.get_namespaces()?;
+        let namespace_index = namespaces
+            .iter()
+            .position(|namespace| {
+                namespace.origin == Origin::Personal && namespace.name == favorite.namespace
+            })
+            .ok_or_else(|| format!("Unknown namespace: {}", favorite.namespace))?;
+        let (commands, tags) = self.get_commands_and_tags(Some(&namespaces[namespace_index]))?;
+        let tag_index = tags
+            .iter()
+            .position(|tag| tag == &favorite.tag)
+            .unwrap_or(0);
+
+        let mut app_namespaces = self.namespaces.as_ref().borrow_mut();
+        app_namespaces.set_items(namespaces);
+        app_namespaces.state.select(Some(namespace_index));
+        app_namespaces.is_selected = false;
+
+        for (list, items) in [(&self.commands, commands), (&self.tags, tags)] {
+            let mut list = list.as_ref().borrow_mut();
+            list.set_items(items);
+            list.state.select(Some(tag_index));
+            list.is_selected = true;
+        }
+        drop(app_namespaces);
+
+        self.tabs.as_ref().borrow_mut().is_selected = false;
+        self.set_tab(Tab::Library);
+
+        Ok(())
+    }
+
+    /// Names and values shown by the Settings tab, paths that can't be resolved are described.
+    fn get_settings_summary(&self) -> Vec<(String, String)> {
+        let describe =
+            |path: Result<String, Box<dyn Error>>| path.unwrap_or_else(|e| e.to_string());
+        let hooks = &self.settings.hooks;
+
+        vec![
+            ("Version", env!("CARGO_PKG_VERSION").to_string()),
+            (
+                "Config",
+                describe(Settings::get_path().map(|path| path.display().to_string())),
+            ),
+            (
+                "Database",
+                describe(
+                    Db::get_db_path(&self.settings, self.profile.as_deref())
+                        .map(|path| path.display().to_string()),
+                ),
+            ),
+            (
+                "Profile",
+                self.profile.clone().unwrap_or("default".to_string()),
+            ),
+            (
+                "Team database",
+                describe(Db::get_team_db_path().map(|path| path.unwrap_or("none".to_string()))),
+            ),
+            (
+                "Project",
+                self.project
+                    .as_ref()
+                    .map_or("none".to_string(), |project| project.name.clone()),
+            ),
+            (
+                "Sync directory",
+                self.settings
+                    .sync
+                    .as_ref()
+                    .map_or("none".to_string(), |sync| sync.dir.clone()),
+            ),
+            ("Fan-out jobs", self.settings.fanout.max_jobs.to_string()),
+            ("Retry delay", format!("{}s", self.settings.run.retry_delay)),
+            ("Notify after", format!("{}s", self.settings.notify.after)),
+            (
+                "Watch debounce",
+                format!("{}ms", self.settings.watch.debounce_ms),
+            ),
+            (
+                "Trash",
+                match self.settings.trash.purge_after_days {
+                    0 => "kept forever".to_string(),
+                    days => format!("purged after {} days", days),
+                },
+            ),
+            (
+                "Mouse",
+                match self.settings.mouse.enabled {
+                    true => "enabled".to_string(),
+                    false => "disabled".to_string(),
+                },
+            ),
+            (
+                "Hooks",
+                format!(
+                    "{} pre, {} post, {} namespaces, {} commands",
+                    hooks.pre.len(),
+                    hooks.post.len(),
+                    hooks.namespaces.len(),
+                    hooks.commands.len()
+                ),
+            ),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect()
+    }
+
+    /// Offers the other personal namespaces for the marked commands, or for the marked
+    /// namespaces when merging. Duplicates can also go to the same namespace.
+    pub fn open_picker(&mut self, action: PickAction) -> Result<(), Box<dyn Error>> {
+        let source = self
+            .namespaces
+            .as_ref()
+            .borrow()
+            .current_item()
+            .name
+            .clone();
+        let items = match action {
+            PickAction::Merge => self.get_marked_namespaces(),
+            _ => self
+                .get_marked_commands()
+                .into_iter()
+                .map(|(_, tag)| tag)
+                .collect(),
+        };
+
+        let namespaces = self
+            .db
+            .get_namespaces()?
+            .into_iter()
+            .filter(|name| match action {
+                PickAction::Move => name != &source,
+                PickAction::Duplicate => true,
+                PickAction::Merge => !items.contains(name),
+            })
+            .collect::<Vec<_>>();
+        if namespaces.is_empty() {
+            self.status.warn("No other namespace, press n to add one");
+            return Ok(());
+        }
+
+        let mut namespaces = StatefulList::with_items(namespaces);
+        namespaces.state.select(Some(0));
+        namespaces.is_selected = true;
+
+        self.picker = Some(NamespacePicker {
+            action,
+            source,
+            items,
+            namespaces,
+        });
+        self.event_state.set_mode(Mode::Pick);
+
+        Ok(())
+    }
+
+    /// Moves, duplicates or merges into the namespace picked, which is then shown when merging.
+    pub fn apply_pick(&mut self) -> Result<(), Box<dyn Error>> {
+        let picker = match self.picker.take() {
+            Some(picker) => picker,
+            None => return Ok(()),
+        };
+        self.event_state.set_mode(Mode::Normal);
+
+        let target = picker.namespaces.current_item().clone();
+        match picker.action {
+            PickAction::Move => {
+                self.perform(
+                    picker
+                        .items
+                        .iter()
+                        .map(|tag| Change::Move {
+                            tag: tag.clone(),
+                            namespace: target.clone(),
+                        })
+                        .collect(),
+                )?;
+                self.status
+                    .info(format!("{} moved to {}", picker.items.join(", "), target));
+            }
+            PickAction::Duplicate => {
+                let mut copies = Vec::new();
+                for tag in &picker.items {
+                    copies.push(self.db.duplicate_command(tag, &target)?);
+                }
+                self.changes.record(
+                    copies
+                        .iter()
+                        .map(|tag| Change::Trash(Item::Command(tag.clone())))
+                        .collect(),
+                );
+                self.status
+                    .info(format!("{} added to {}", copies.join(", "), target));
+            }
+            PickAction::Merge => {
+                self.perform(
+                    picker
+                        .items
+                        .iter()
+                        .map(|namespace| Change::Merge {
+                            namespace: namespace.clone(),
+                            target: target.clone(),
+                        })
+                        .collect(),
+                )?;
+                self.status.info(format!(
+                    "{} merged into {}",
+                    picker.items.join(", "),
+                    target
+                ));
+                return self.reload_library(&target);
+            }
+        }
+
+        self.reload_library(&picker.source)
+    }
+
+    pub fn rename_namespace(&mut self, name: &str) -> Result<(), Box<dyn Error>> {
+        let namespace = self
+            .namespaces
+            .as_ref()
+            .borrow()
+            .current_item()
+            .name
+            .clone();
+
+        self.perform(vec![Change::Rename {
+            namespace: namespace.clone(),
+            name: name.to_string(),
+        }])?;
+        self.status
+            .info(format!("{} renamed to {}", namespace, name));
+
+        self.reload_library(name)
+    }
+
+    /// Applies the changes of an action, the changes reverting those applied are kept to undo
+    /// it, even when one of them failed.
+    fn perform(&mut self, changes: Vec<Change>) -> Result<(), Box<dyn Error>> {
+        let (reverts, result) = self.apply_changes(changes);
+        self.changes.record(reverts);
+
+        result
+    }
+
+    /// Returns the changes reverting those applied, in the order to apply them.
+    fn apply_changes(&self, changes: Vec<Change>) -> (Vec<Change>, Result<(), Box<dyn Error>>) {
+        let mut reverts = Vec::new();
+
+        for change in changes {
+            match change.apply(&self.db) {
+                Ok(revert) => reverts.insert(0, revert),
+                Err(e) => return (reverts, Err(e)),
+            }
+        }
+
+        (reverts, Ok(()))
+    }
+
+    /// Reverts the last action of the session, it can be redone afterwards.
+    pub fn undo(&mut self) -> Result<(), Box<dyn Error>> {
+        let changes = match self.changes.take_undo() {
+            Some(changes) => changes,
+            None => {
+                self.status.warn("Nothing to undo");
+                return Ok(());
+            }
+        };
+
+        let message = App::describe_changes(&changes);
+        let (reverts, result) = self.apply_changes(changes);
+        self.changes.push_redo(reverts);
+        result?;
+
+        self.status.info(format!("Undo: {}", message));
+        self.refresh_library()
+    }
+
+    pub fn redo(&mut self) -> Result<(), Box<dyn Error>> {
+        let changes = match self.changes.take_redo() {
+            Some(changes) => changes,
+            None => {
+                self.status.warn("Nothing to redo");
+                return Ok(());
+            }
+        };
+
+        let message = App::describe_changes(&changes);
+        let (reverts, result) = self.apply_changes(changes);
+        self.changes.push_undo(reverts);
+        result?;
+
+        self.status.info(format!("Redo: {}", message));
+        self.refresh_library()
+    }
+
+    fn describe_changes(changes: &[Change]) -> String {
+        match changes {
+            [] => String::new(),
+            [change] => change.describe(),
+            [change, others @ ..] => format!("{} and {} more", change.describe(), others.len()),
+        }
+    }
+
+    /// Restores the selected entry of the Trash tab, which can be undone.
+    pub fn restore_trash(&mut self) -> Result<(), Box<dyn Error>> {
+        let entry = self.trash.as_ref().borrow().current_item().clone();
+
+        self.perform(vec![Change::Restore {
+            id: entry.id,
+            item: Item::from_entry(&entry),
+        }])?;
+        self.status.info(format!("{} restored", entry.get_name()));
+
+        self.refresh_library()
+    }
+
+    /// Deletes the selected entry of the Trash tab for good.
+    pub fn purge_trash_entry(&mut self) -> Result<(), Box<dyn Error>> {
+        let entry = self.trash.as_ref().borrow().current_item().clone();
+
+        self.db.purge_trash_entry(entry.id)?;
+        self.status
+            .info(format!("{} deleted for good", entry.get_name()));
+
+        self.load_tab(Tab::Trash);
+        Ok(())
+    }
+
+    /// Reads the library, the favorites, the settings of the commands and the trash again
+    /// after an undo or a restoration, the current namespace stays selected when it exists.
+    fn refresh_library(&mut self) -> Result<(), Box<dyn Error>> {
+        let namespace = self.get_current_namespace_name().unwrap_or_default();
+        let is_on_tabs = self.namespaces.as_ref().borrow().state.selected().is_none();
+
+        App::replace_items(&self.favorites, self.db.get_favorites()?);
+        App::replace_items(&self.trash, self.db.get_trash()?);
+        self.load_command_settings()?;
+
+        self.reload_library(&namespace)?;
+        if is_on_tabs {
+            self.namespaces.as_ref().borrow_mut().unselect();
+        }
+
+        Ok(())
+    }
+
+    /// Reads the library again with a personal namespace selected, the selection of the
+    /// commands is kept when they were focused.
+    fn reload_library(&mut self, namespace: &str) -> Result<(), Box<dyn Error>> {
+        let namespaces = self.get_namespaces()?;
+        let index = namespaces
+            .iter()
+            .position(|item| item.origin == Origin::Personal && item.name == namespace)
+            .unwrap_or(0);
+        let (commands, tags) = self.get_commands_and_tags(namespaces.get(index))?;
+
+        let selected = self
+            .commands
+            .as_ref()
+            .borrow()
+            .state
+            .selected()
+            .map(|selected| selected.min(commands.len().saturating_sub(1)));
+
+        let mut app_namespaces = self.namespaces.as_ref().borrow_mut();
+        app_namespaces.set_items(namespaces);
+        app_namespaces.state.select(Some(index));
+
+        for (list, items) in [(&self.commands, commands), (&self.tags, tags)] {
+            let mut list = list.as_ref().borrow_mut();
+            list.set_items(items);
+            list.state.select(selected);
+        }
+
+        Ok(())
+    }
+
+    pub fn get_current_namespace_name(&self) -> Option<String> {
+        let namespaces = self.namespaces.as_ref().borrow();
+
+        namespaces
+            .items
+            .get(namespaces.current())
+            .map(|namespace| namespace.name.clone())
+    }
+
+    pub fn get_run_options(&self, tag: &str) -> RunOptions {
+        self.run_options.get(tag).cloned().unwrap_or_default()
+    }
+
+    fn get_limits(&self, tag: &str) -> Limits {
+        Limits::new(&self.get_run_options(tag), self.settings.run.retry_delay)
+    }
+
+    /// Opens the database of the next configured profile, a missing file is created.
+    pub fn switch_profile(&mut self) -> Result<(), Box<dyn Error>> {
+        let names = self.settings.profiles.keys().cloned().collect::<Vec<_>>();
+        if names.is_empty() {
+            return Ok(());
+        }
+
+        let next = match &self.profile {
+            Some(profile) => names
+                .iter()
+                .position(|name| name == profile)
+                .map_or(0, |i| (i + 1) % names.len()),
+            None => 0,
+        };
+
+        let db = Db::new(&self.settings, Some(&names[next]))?;
+        db.init_db()?;
+
+        self.db = db;
+        self.profile = Some(names[next].clone());
+        self.event_state = EventState::default();
+        self.load_lists()
+    }
+
+    /// The project namespace comes first, followed by the personal, the team ones and the workflows.
+    pub fn get_namespaces(&self) -> Result<Vec<Namespace>, Box<dyn Error>> {
+        let mut namespaces = Vec::new();
+
+        if let Some(project) = &self.project {
+            namespaces.push(Namespace::new(project.name.clone(), Origin::Project));
+        }
+
+        namespaces.extend(
+            self.db
+                .get_namespaces()?
+                .into_iter()
+                .map(Namespace::personal),
+        );
+        namespaces.extend(
+            self.db
+                .get_team_namespaces()?
+                .into_iter()
+                .map(|name| Namespace::new(name, Origin::Team)),
+        );
+
+        if !self.db.get_workflows()?.is_empty() {
+            namespaces.push(Namespace::new(
+                self.config.name_config.workflows_namespace.clone(),
+                Origin::Workflow,
+            ));
+        }
+
+        Ok(namespaces)
+    }
+
+    pub fn get_risk(&self, namespace: &Namespace, command: &str, tag: &str) -> Risk {
+        if namespace.is_workflow() {
+            return self.risks.get(tag).copied().unwrap_or(Risk::Confirm);
+        }
+
+        self.risks
+            .get(tag)
+            .copied()
+            .unwrap_or_else(|| Risk::detect(command))
+    }
+
+    /// Risk of the selected command, `None` without selection.
+    pub fn get_current_risk(&self) -> Option<Risk> {
+        let namespaces = self.namespaces.as_ref().borrow();
+        let commands = self.commands.as_ref().borrow();
+        let tags = self.tags.as_ref().borrow();
+
+        if commands.state.selected().is_none() || commands.items.is_empty() {
+            return None;
+        }
+
+        Some(self.get_risk(
+            namespaces.current_item(),
+            commands.current_item(),
+            tags.current_item(),
+        ))
+    }
+
+    /// What confirming the selected command executes, an error replaces the preview.
+    pub fn get_preview_lines(&self) -> Vec<String> {
+        let namespaces = self.namespaces.as_ref().borrow();
+        let commands = self.commands.as_ref().borrow();
+        let tags = self.tags.as_ref().borrow();
+
+        if commands.state.selected().is_none() || commands.items.is_empty() {
+            return vec![];
+        }
+
+        let params = BTreeMap::new();
+        let lines: Result<Vec<String>, Box<dyn Error>> = if namespaces.current_item().is_workflow()
+        {
+            self.db
+                .get_workflow(tags.current_item())
+                .and_then(|workflow| workflow.ok_or_else(|| "Unknown workflow".into()))
+                .and_then(|workflow| {
+                    let mut lines = Vec::new();
+                    for (i, (step, preview)) in workflow
+                        .steps
+                        .iter()
+                        .zip(workflow.preview(&params)?)
+                        .enumerate()
+                    {
+                        lines.push(format!("{}. {}", i + 1, step.tag));
+                        lines.extend(
+                            preview
+                                .get_lines()
+                                .into_iter()
+                                .map(|line| format!("   {}", line)),
+                        );
+                    }
+
+                    Ok(lines)
+                })
+        } else {
+            Preview::new(commands.current_item(), &params).map(|preview| preview.get_lines())
+        };
+
+        lines.unwrap_or_else(|e| vec![e.to_string()])
+    }
+
+    /// Background processes end with the interface, only the database is kept.
+    pub fn into_db(self) -> Db {
+        self.db
+    }
+
+    pub fn get_commands_and_tags(
+        &self,
+        namespace: Option<&Namespace>,
+    ) -> Result<(Vec<String>, Vec<String>), Box<dyn Error>> {
+        match namespace {
+            Some(namespace) => match namespace.origin {
+                Origin::Project => Ok(self
+                    .project
+                    .as_ref()
+                    .map(|project| project.get_commands_and_tags())
+                    .unwrap_or_default()),
+                Origin::Personal => self
+                    .db
+                    .get_commands_and_tags(Some(namespace.name.clone()))
+                    .map(|(commands, tags)| self.pin_favorites(commands, tags)),
+                Origin::Team => self.db.get_team_commands_and_tags(&namespace.name),
+                Origin::Workflow => Ok(self
+                    .db
+                    .get_workflows()?
+                    .iter()
+                    .map(|workflow| (workflow.describe(), workflow.tag.clone()))
+                    .unzip()),
+            },
+            None => Ok((vec![], vec![])),
+        }
+    }
+
+    /// Moves the favorites before the other commands, both keeping their order.
+    fn pin_favorites(
+        &self,
+        commands: Vec<String>,
+        tags: Vec<String>,
+    ) -> (Vec<String>, Vec<String>) {
+        let (pinned, others): (Vec<_>, Vec<_>) = commands
+            .into_iter()
+            .zip(tags)
+            .partition(|(_, tag)| self.is_favorite(tag));
+
+        pinned.into_iter().chain(others).unzip()
+    }
+
+    /// Copies commands of a read-only namespace into the personal namespace of the same name.
+    /// Tags already present in the personal database are skipped, returns the copied tags.
+    pub fn fork_commands(
+        &self,
+        namespace: &Namespace,
+        commands: &[(String, String)],
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        if self.db.get_namespace(&namespace.name)?.is_none() {
+            self.db.add_namespace(&namespace.name)?;
+        }
+
+        let mut copied = Vec::new();
+        for (command, tag) in commands {
+            if self.db.has_tag(tag)? {
+                continue;
+            }
+
+            self.db
+                .add_command_and_tag(Some(command), Some(tag), &namespace.name)?;
+            copied.push(tag.clone());
+        }
+
+        Ok(copied)
+    }
 }
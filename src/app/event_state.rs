@@ -2,13 +2,22 @@
 pub enum Mode {
     Add,
     Delete,
+    FanOut,
+    Fork,
     Normal,
+    Pick,
+    Sequence,
+    TypeTag,
 }
 
 pub enum SubMode {
     Command,
+    Directories,
     Namespace,
     None,
+    /// Prefix added to the tags of the marked commands.
+    Prefix,
+    Rename,
 }
 
 #[derive(PartialEq)]
@@ -26,8 +35,39 @@ pub enum Confirm {
     Hide,
 }
 
+#[derive(Clone, Copy, PartialEq)]
 pub enum Tab {
-    Tab1,
+    Library,
+    History,
+    Favorites,
+    Jobs,
+    FanOut,
+    Schedule,
+    Settings,
+    Trash,
+}
+
+impl Tab {
+    /// Tabs in the order of the tab bar titles.
+    pub const ALL: [Tab; 8] = [
+        Tab::Library,
+        Tab::History,
+        Tab::Favorites,
+        Tab::Jobs,
+        Tab::FanOut,
+        Tab::Schedule,
+        Tab::Settings,
+        Tab::Trash,
+    ];
+
+    /// Tab matching the index of the tab bar titles.
+    pub fn from_index(index: usize) -> Self {
+        Tab::ALL.get(index).copied().unwrap_or(Tab::Library)
+    }
+
+    pub fn get_index(&self) -> usize {
+        Tab::ALL.iter().position(|tab| tab == self).unwrap_or(0)
+    }
 }
 
 pub struct EventState {
@@ -45,7 +85,7 @@ impl Default for EventState {
             event_type: EventType::None,
             mode: Mode::Normal,
             sub_mode: SubMode::None,
-            tab: Tab::Tab1,
+            tab: Tab::Library,
         }
     }
 }
@@ -86,4 +126,8 @@ impl EventState {
     pub fn set_confirm(&mut self, confirm: Confirm) {
         self.confirm = confirm;
     }
+
+    pub fn set_tab(&mut self, tab: Tab) {
+        self.tab = tab;
+    }
 }
@@ -26,7 +26,11 @@ impl CursorPosition {
     }
 
     fn inc(&mut self) {
-        if self.input.len() % self.width.saturating_sub(1) != 0 {
+        if !self
+            .input
+            .len()
+            .is_multiple_of(self.width.saturating_sub(1))
+        {
             self.x += 1;
         } else {
             self.x = self.initial_x;
@@ -35,12 +39,17 @@ impl CursorPosition {
     }
 
     fn dec(&mut self) {
-        if self.input.len() == 0 {
+        if self.input.is_empty() {
             self.x = self.initial_x;
             return;
         }
 
-        if self.input.len() % self.width.saturating_sub(1) != 0 && self.x > self.initial_x {
+        if !self
+            .input
+            .len()
+            .is_multiple_of(self.width.saturating_sub(1))
+            && self.x > self.initial_x
+        {
             self.x = self.x.saturating_sub(1);
         } else {
             if self.x > self.initial_x {
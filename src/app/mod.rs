@@ -1,4 +1,8 @@
+#[allow(clippy::module_inception)]
 pub mod app;
 pub mod event_state;
 pub mod input;
+pub mod picker;
 pub mod state;
+pub mod status;
+pub mod undo;
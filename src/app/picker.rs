@@ -0,0 +1,19 @@
+use crate::app::state::StatefulList;
+
+/// What is done once a namespace is picked.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PickAction {
+    Move,
+    Duplicate,
+    Merge,
+}
+
+/// Popup listing the personal namespaces the commands are moved, duplicated or merged into.
+pub struct NamespacePicker {
+    pub action: PickAction,
+    /// Namespace selected when the picker was opened.
+    pub source: String,
+    /// Tags of the commands to move or duplicate, names of the namespaces to merge.
+    pub items: Vec<String>,
+    pub namespaces: StatefulList<String>,
+}
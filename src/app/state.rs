@@ -1,4 +1,7 @@
 use crate::core::config::Config;
+use std::collections::BTreeSet;
+use std::ops::Range;
+use tui::layout::Rect;
 use tui::widgets::ListState;
 
 pub trait State {
@@ -24,12 +27,7 @@ impl State for TabsState {
 
 impl TabsState {
     pub fn new(config: &Config) -> Self {
-        let titles = config
-            .name_config
-            .tabs_title
-            .iter()
-            .map(|tab| tab.clone())
-            .collect();
+        let titles = config.name_config.tabs_title.to_vec();
 
         TabsState {
             titles,
@@ -37,12 +35,60 @@ impl TabsState {
             is_selected: false,
         }
     }
+
+    /// Tab under a column of the tab bar, counted from its inner left edge. Each title is
+    /// padded by a space on both sides and followed by a divider.
+    pub fn get_index_at(&self, column: u16) -> Option<usize> {
+        let mut end = 0;
+
+        self.titles.iter().position(|title| {
+            end += title.chars().count() + 3;
+            (column as usize) < end
+        })
+    }
+}
+
+/// Inner areas, inside the borders, of the last drawing where mouse events are mapped.
+#[derive(Clone, Copy, Default)]
+pub struct Areas {
+    pub tabs: Rect,
+    pub namespaces: Rect,
+    pub commands: Rect,
+    pub tags: Rect,
+    pub details: Rect,
+    pub output: Rect,
+    /// Rows of the list or table of the other tabs, below the header of a table.
+    pub rows: Rect,
+}
+
+/// Whether the cell is in the area, never in an empty one.
+pub fn contains(area: Rect, column: u16, row: u16) -> bool {
+    column >= area.x && column < area.right() && row >= area.y && row < area.bottom()
+}
+
+/// Moves of the selection further than the next or previous item.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Jump {
+    First,
+    Last,
+    PageUp,
+    PageDown,
+    /// Next item whose name starts with the letter, from the current one and round.
+    Letter(char),
 }
 
 pub struct StatefulList<T> {
     pub state: ListState,
     pub items: Vec<T>,
     pub is_selected: bool,
+    /// Indexes of the items marked for a bulk action.
+    pub marked: BTreeSet<usize>,
+    /// Last item marked or unmarked, where a range starts.
+    anchor: Option<usize>,
+    /// First item shown, only the rows from there are drawn.
+    offset: usize,
+    /// Rows shown at the last drawing, the size of a page.
+    height: usize,
 }
 
 fn get_next_state_to_select(state: &ListState, item_count: usize) -> Option<usize> {
@@ -89,21 +135,238 @@ impl<T> StatefulList<T> {
             state: ListState::default(),
             items,
             is_selected: false,
+            marked: BTreeSet::new(),
+            anchor: None,
+            offset: 0,
+            height: 1,
+        }
+    }
+
+    /// Scrolls just enough to show the selection in `height` rows, called with the inner
+    /// height of the list before each drawing.
+    pub fn update_offset(&mut self, height: usize) {
+        self.height = height.max(1);
+
+        if self.items.is_empty() {
+            self.offset = 0;
+            return;
+        }
+
+        let selected = self.current().min(self.items.len() - 1);
+        self.offset = self.offset.min(self.items.len() - 1);
+
+        if selected >= self.offset + self.height {
+            self.offset = selected + 1 - self.height;
+        }
+        if selected < self.offset {
+            self.offset = selected;
+        }
+    }
+
+    /// Indexes of the items shown, the only ones turned into rows.
+    pub fn get_visible(&self) -> Range<usize> {
+        self.offset.min(self.items.len())..(self.offset + self.height).min(self.items.len())
+    }
+
+    /// State of the widget drawing the visible items, its selection is relative to them.
+    pub fn get_visible_state(&self) -> ListState {
+        let mut state = ListState::default();
+        state.select(
+            self.state
+                .selected()
+                .filter(|index| self.get_visible().contains(index))
+                .map(|index| index - self.offset),
+        );
+
+        state
+    }
+
+    /// Item selected by the jump, `None` without items or when no name matches the letter.
+    pub fn get_jump_index(&self, jump: Jump, get_name: impl Fn(&T) -> &str) -> Option<usize> {
+        let count = self.items.len();
+        let last = count.checked_sub(1)?;
+        let current = self.current().min(last);
+
+        match jump {
+            Jump::First => Some(0),
+            Jump::Last => Some(last),
+            Jump::PageUp => Some(current.saturating_sub(self.height)),
+            Jump::PageDown => Some((current + self.height).min(last)),
+            Jump::Letter(letter) => {
+                let start = self.state.selected().map_or(0, |index| index + 1);
+
+                (0..count)
+                    .map(|step| (start + step) % count)
+                    .find(|&index| {
+                        get_name(&self.items[index])
+                            .chars()
+                            .next()
+                            .is_some_and(|first| first.to_lowercase().eq(letter.to_lowercase()))
+                    })
+            }
+        }
+    }
+
+    /// Item shown on a row of the list, counted from its first row.
+    pub fn get_index_at(&self, row: u16) -> Option<usize> {
+        let index = self.offset + row as usize;
+
+        (index < self.items.len()).then_some(index)
+    }
+
+    /// Replaces the items, the marks of the former ones are cleared.
+    pub fn set_items(&mut self, items: Vec<T>) {
+        self.items = items;
+        self.clear_marks();
+    }
+
+    pub fn toggle_mark(&mut self) {
+        let current = self.current();
+
+        if !self.marked.remove(&current) {
+            self.marked.insert(current);
+        }
+        self.anchor = Some(current);
+    }
+
+    /// Marks the items from the last one marked to the current one.
+    pub fn mark_range(&mut self) {
+        let current = self.current();
+        let anchor = self.anchor.unwrap_or(current);
+
+        self.marked
+            .extend(anchor.min(current)..=anchor.max(current));
+        self.anchor = Some(current);
+    }
+
+    /// Marks every item, or clears the marks once they all are.
+    pub fn toggle_all(&mut self) {
+        if self.marked.len() == self.items.len() {
+            self.clear_marks();
+        } else {
+            self.marked = (0..self.items.len()).collect();
         }
     }
 
+    pub fn clear_marks(&mut self) {
+        self.marked.clear();
+        self.anchor = None;
+    }
+
+    /// Indexes of the marked items, the current one when none is marked.
+    pub fn get_marked(&self) -> Vec<usize> {
+        if self.marked.is_empty() {
+            return self
+                .state
+                .selected()
+                .filter(|&index| index < self.items.len())
+                .into_iter()
+                .collect();
+        }
+
+        self.marked
+            .iter()
+            .copied()
+            .filter(|&index| index < self.items.len())
+            .collect()
+    }
+
     pub fn unselect(&mut self) {
         self.state.select(None);
     }
 
     pub fn current(&self) -> usize {
-        match self.state.selected() {
-            Some(i) => i,
-            None => 0,
-        }
+        self.state.selected().unwrap_or_default()
     }
 
     pub fn current_item(&self) -> &T {
         &self.items[self.current()]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_marks() {
+        let mut list = StatefulList::with_items(vec!["a", "b", "c", "d", "e"]);
+        list.state.select(Some(1));
+        assert_eq!(list.get_marked(), vec![1]);
+
+        list.toggle_mark();
+        list.state.select(Some(3));
+        list.mark_range();
+        assert_eq!(list.get_marked(), vec![1, 2, 3]);
+
+        list.toggle_mark();
+        assert_eq!(list.get_marked(), vec![1, 2]);
+
+        list.toggle_all();
+        assert_eq!(list.get_marked().len(), 5);
+        list.toggle_all();
+        assert_eq!(list.get_marked(), vec![3]);
+
+        list.toggle_mark();
+        list.set_items(vec!["f"]);
+        assert!(list.marked.is_empty());
+    }
+
+    #[test]
+    fn test_get_index_at() {
+        let mut list = StatefulList::with_items(vec!["a", "b", "c", "d", "e"]);
+        list.state.select(Some(3));
+        list.update_offset(2);
+        assert_eq!(list.get_index_at(0), Some(2));
+        assert_eq!(list.get_index_at(1), Some(3));
+        assert_eq!(list.get_index_at(3), None);
+
+        // Going back up scrolls only once the selection is above the rows shown
+        list.state.select(Some(2));
+        list.update_offset(2);
+        assert_eq!(list.get_index_at(0), Some(2));
+        list.state.select(Some(0));
+        list.update_offset(2);
+        assert_eq!(list.get_index_at(0), Some(0));
+
+        list.state.select(Some(4));
+        list.update_offset(2);
+        assert_eq!(list.get_visible(), 3..5);
+        assert_eq!(list.get_visible_state().selected(), Some(1));
+
+        let tabs = TabsState {
+            titles: vec!["1 Library".to_string(), "2 History".to_string()],
+            index: 0,
+            is_selected: false,
+        };
+        assert_eq!(tabs.get_index_at(0), Some(0));
+        assert_eq!(tabs.get_index_at(11), Some(0));
+        assert_eq!(tabs.get_index_at(12), Some(1));
+        assert_eq!(tabs.get_index_at(24), None);
+    }
+
+    #[test]
+    fn test_get_jump_index() {
+        let mut list = StatefulList::with_items(vec!["apt", "brew", "Bash", "cargo", "docker"]);
+        fn name<'a>(item: &'a &str) -> &'a str {
+            item
+        }
+
+        assert_eq!(list.get_jump_index(Jump::Letter('a'), name), Some(0));
+
+        list.state.select(Some(1));
+        list.update_offset(2);
+        assert_eq!(list.get_jump_index(Jump::PageDown, name), Some(3));
+        assert_eq!(list.get_jump_index(Jump::PageUp, name), Some(0));
+        assert_eq!(list.get_jump_index(Jump::Last, name), Some(4));
+
+        // Letters go round the items, whatever their case
+        assert_eq!(list.get_jump_index(Jump::Letter('b'), name), Some(2));
+        list.state.select(Some(2));
+        assert_eq!(list.get_jump_index(Jump::Letter('B'), name), Some(1));
+        assert_eq!(list.get_jump_index(Jump::Letter('z'), name), None);
+
+        list.set_items(vec![]);
+        assert_eq!(list.get_jump_index(Jump::First, name), None);
+    }
+}
@@ -0,0 +1,94 @@
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Level {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Level {
+    /// Errors stay longer, they are usually read after noticing something went wrong.
+    fn get_timeout(&self) -> Duration {
+        match self {
+            Level::Info => Duration::from_secs(3),
+            Level::Warning => Duration::from_secs(5),
+            Level::Error => Duration::from_secs(8),
+        }
+    }
+}
+
+pub struct Message {
+    pub level: Level,
+    pub text: String,
+    shown_at: Instant,
+}
+
+/// Message of the status bar, hidden once its timeout is over or by a newer one.
+#[derive(Default)]
+pub struct Status {
+    message: Option<Message>,
+}
+
+impl Status {
+    pub fn info(&mut self, text: impl ToString) {
+        self.set(Level::Info, text.to_string());
+    }
+
+    pub fn warn(&mut self, text: impl ToString) {
+        self.set(Level::Warning, text.to_string());
+    }
+
+    pub fn error(&mut self, text: impl ToString) {
+        self.set(Level::Error, text.to_string());
+    }
+
+    fn set(&mut self, level: Level, text: String) {
+        self.message = Some(Message {
+            level,
+            text,
+            shown_at: Instant::now(),
+        });
+    }
+
+    pub fn clear(&mut self) {
+        self.message = None;
+    }
+
+    /// Current message, `None` once it has expired.
+    pub fn get(&self) -> Option<&Message> {
+        self.get_at(Instant::now())
+    }
+
+    fn get_at(&self, now: Instant) -> Option<&Message> {
+        self.message
+            .as_ref()
+            .filter(|message| now.duration_since(message.shown_at) < message.level.get_timeout())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expiry() {
+        let mut status = Status::default();
+        assert!(status.get().is_none());
+
+        status.info("Namespace added");
+        let shown_at = Instant::now();
+        assert_eq!(status.get().map(|m| m.level), Some(Level::Info));
+        assert!(status.get_at(shown_at + Duration::from_secs(4)).is_none());
+
+        status.error("Database is locked");
+        let shown_at = Instant::now();
+        assert_eq!(
+            status
+                .get_at(shown_at + Duration::from_secs(4))
+                .map(|m| m.text.as_str()),
+            Some("Database is locked")
+        );
+        assert!(status.get_at(shown_at + Duration::from_secs(9)).is_none());
+    }
+}
@@ -0,0 +1,300 @@
+use crate::db::db::Db;
+use crate::db::namespace::TrashEntry;
+use std::error::Error;
+use std::fmt;
+
+/// Actions kept in the undo stack.
+const UNDO_LIMIT: usize = 100;
+
+/// A command, by tag, or a namespace of the personal library.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Item {
+    Command(String),
+    Namespace(String),
+}
+
+impl Item {
+    pub fn from_entry(entry: &TrashEntry) -> Self {
+        match &entry.tag {
+            Some(tag) => Item::Command(tag.clone()),
+            None => Item::Namespace(entry.namespace.clone()),
+        }
+    }
+}
+
+impl fmt::Display for Item {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Item::Command(tag) => write!(f, "{}", tag),
+            Item::Namespace(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// A change of the personal library. Applying one returns the change reverting it, so a
+/// deletion is reverted by a restoration from the trash and the other way round.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    /// Reverts an addition or a restoration, the item can still be restored from the trash.
+    Trash(Item),
+    Restore {
+        id: i64,
+        item: Item,
+    },
+    Move {
+        tag: String,
+        namespace: String,
+    },
+    Rename {
+        namespace: String,
+        name: String,
+    },
+    RenameTag {
+        tag: String,
+        name: String,
+    },
+    Merge {
+        namespace: String,
+        target: String,
+    },
+    /// Creates a merged namespace again with the commands it had.
+    Split {
+        namespace: String,
+        target: String,
+        tags: Vec<String>,
+    },
+    /// Stars a command, or removes its star.
+    Star {
+        tag: String,
+        favorite: bool,
+    },
+}
+
+impl Change {
+    pub fn apply(self, db: &Db) -> Result<Change, Box<dyn Error>> {
+        let revert = match self {
+            Change::Trash(item) => {
+                let id = match &item {
+                    Item::Command(tag) => db.delete_command(tag)?,
+                    Item::Namespace(name) => db.delete_namespace(name)?,
+                };
+                Change::Restore { id, item }
+            }
+            Change::Restore { id, .. } => Change::Trash(Item::from_entry(&db.restore_trash(id)?)),
+            Change::Move { tag, namespace } => {
+                let from = db
+                    .get_tag_namespace(&tag)?
+                    .ok_or_else(|| format!("Unknown command tag: {}", tag))?;
+                db.move_command(&tag, &namespace)?;
+                Change::Move {
+                    tag,
+                    namespace: from,
+                }
+            }
+            Change::Rename { namespace, name } => {
+                db.rename_namespace(&namespace, &name)?;
+                Change::Rename {
+                    namespace: name,
+                    name: namespace,
+                }
+            }
+            Change::RenameTag { tag, name } => {
+                db.rename_tag(&tag, &name)?;
+                Change::RenameTag {
+                    tag: name,
+                    name: tag,
+                }
+            }
+            Change::Merge { namespace, target } => {
+                let (_, tags) = db.get_commands_and_tags(Some(namespace.clone()))?;
+                db.merge_namespace(&namespace, &target)?;
+                Change::Split {
+                    namespace,
+                    target,
+                    tags,
+                }
+            }
+            Change::Split {
+                namespace,
+                target,
+                tags,
+            } => {
+                db.add_namespace(&namespace)?;
+                for tag in &tags {
+                    db.move_command(tag, &namespace)?;
+                }
+                Change::Merge { namespace, target }
+            }
+            Change::Star { tag, favorite } => {
+                db.set_favorite(&tag, favorite)?;
+                Change::Star {
+                    tag,
+                    favorite: !favorite,
+                }
+            }
+        };
+
+        Ok(revert)
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Change::Trash(item) => format!("{} deleted", item),
+            Change::Restore { item, .. } => format!("{} restored", item),
+            Change::Move { tag, namespace } => format!("{} moved to {}", tag, namespace),
+            Change::Rename { namespace, name } => format!("{} renamed to {}", namespace, name),
+            Change::RenameTag { tag, name } => format!("{} tagged {}", tag, name),
+            Change::Merge { namespace, target } => format!("{} merged into {}", namespace, target),
+            Change::Split { namespace, .. } => format!("{} split out again", namespace),
+            Change::Star {
+                tag,
+                favorite: true,
+            } => format!("{} starred", tag),
+            Change::Star { tag, .. } => format!("{} unstarred", tag),
+        }
+    }
+}
+
+/// Changes reverting the last actions of the session, and those reverting the undone ones.
+/// Each entry is a whole action, like a bulk deletion, in the order to apply its changes.
+#[derive(Default)]
+pub struct UndoStack {
+    undo: Vec<Vec<Change>>,
+    redo: Vec<Vec<Change>>,
+}
+
+impl UndoStack {
+    /// Keeps the changes reverting a new action, the undone actions can't be redone anymore.
+    pub fn record(&mut self, changes: Vec<Change>) {
+        if changes.is_empty() {
+            return;
+        }
+
+        self.undo.push(changes);
+        if self.undo.len() > UNDO_LIMIT {
+            self.undo.remove(0);
+        }
+        self.redo.clear();
+    }
+
+    pub fn take_undo(&mut self) -> Option<Vec<Change>> {
+        self.undo.pop()
+    }
+
+    pub fn take_redo(&mut self) -> Option<Vec<Change>> {
+        self.redo.pop()
+    }
+
+    /// Keeps the changes reverting an undo.
+    pub fn push_redo(&mut self, changes: Vec<Change>) {
+        if !changes.is_empty() {
+            self.redo.push(changes);
+        }
+    }
+
+    /// Keeps the changes reverting a redo, the other undone actions can still be redone.
+    pub fn push_undo(&mut self, changes: Vec<Change>) {
+        if !changes.is_empty() {
+            self.undo.push(changes);
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trash(tag: &str) -> Vec<Change> {
+        vec![Change::Trash(Item::Command(tag.to_string()))]
+    }
+
+    #[test]
+    fn test_undo_stack() {
+        let mut stack = UndoStack::default();
+        assert!(!stack.can_undo());
+
+        stack.record(trash("a"));
+        stack.record(vec![]);
+        stack.record(trash("b"));
+
+        let changes = stack.take_undo().unwrap();
+        assert_eq!(changes, trash("b"));
+        stack.push_redo(trash("b"));
+        assert!(stack.can_redo());
+
+        // Redoing keeps the other undone actions
+        stack.take_undo();
+        stack.push_redo(trash("a"));
+        assert_eq!(stack.take_redo().unwrap(), trash("a"));
+        stack.push_undo(trash("a"));
+        assert!(stack.can_redo());
+
+        // A new action drops them
+        stack.record(trash("c"));
+        assert!(!stack.can_redo());
+        assert_eq!(stack.take_undo().unwrap(), trash("c"));
+    }
+
+    #[test]
+    fn test_star_reverts() {
+        let db = Db::in_memory().unwrap();
+        let namespace = "docker".to_string();
+        db.add_namespace(&namespace).unwrap();
+        db.add_command_and_tag(
+            Some(&"docker ps".to_string()),
+            Some(&"ps".to_string()),
+            &namespace,
+        )
+        .unwrap();
+
+        let star = Change::Star {
+            tag: "ps".to_string(),
+            favorite: true,
+        };
+        let revert = star.clone().apply(&db).unwrap();
+        assert_eq!(db.get_favorites().unwrap().len(), 1);
+        assert_eq!(revert.apply(&db).unwrap(), star);
+        assert!(db.get_favorites().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_rename_tag_reverts() {
+        let db = Db::in_memory().unwrap();
+        let namespace = "docker".to_string();
+        db.add_namespace(&namespace).unwrap();
+        for (command, tag) in [("docker ps", "ps"), ("docker ps -a", "legacy:ps")] {
+            db.add_command_and_tag(
+                Some(&command.to_string()),
+                Some(&tag.to_string()),
+                &namespace,
+            )
+            .unwrap();
+        }
+
+        let rename = Change::RenameTag {
+            tag: "ps".to_string(),
+            name: "legacy:ps".to_string(),
+        };
+        assert!(rename.apply(&db).is_err());
+
+        let rename = Change::RenameTag {
+            tag: "ps".to_string(),
+            name: "old:ps".to_string(),
+        };
+        let revert = rename.clone().apply(&db).unwrap();
+        assert_eq!(
+            db.get_command("old:ps").unwrap().as_deref(),
+            Some("docker ps")
+        );
+        assert_eq!(revert.apply(&db).unwrap(), rename);
+        assert!(db.has_tag(&"ps".to_string()).unwrap());
+    }
+}
@@ -0,0 +1,150 @@
+use crate::core::config::Config;
+use crate::core::params;
+use crate::db::command_file::{CommandFile, FileCommand};
+use crate::db::db::Db;
+use std::error::Error;
+
+pub enum ExportFormat {
+    Markdown,
+}
+
+impl ExportFormat {
+    pub fn from_name(name: &str) -> Result<Self, Box<dyn Error>> {
+        match name {
+            "markdown" | "md" => Ok(ExportFormat::Markdown),
+            _ => Err(format!("Unknown export format: {}", name).into()),
+        }
+    }
+}
+
+pub fn export(db: &Db, format: &ExportFormat) -> Result<String, Box<dyn Error>> {
+    let files = db.export_files()?;
+
+    match format {
+        ExportFormat::Markdown => Ok(render_markdown(
+            &Config::new().name_config.app_title,
+            &files,
+        )),
+    }
+}
+
+/// One section per namespace, a command comes with its description, working directory and
+/// parameters when it has some.
+pub fn render_markdown(title: &str, files: &[CommandFile]) -> String {
+    let mut out = format!("# {}\n", title);
+
+    for file in files {
+        out.push_str(&format!(
+            "\n## {}\n",
+            file.name.as_deref().unwrap_or_default()
+        ));
+
+        if file.commands.is_empty() {
+            out.push_str("\n_No commands._\n");
+            continue;
+        }
+
+        for command in &file.commands {
+            let fence = get_fence(&command.command);
+
+            out.push_str(&format!("\n### `{}`\n\n", command.tag));
+            if let Some(description) = &command.description {
+                out.push_str(&format!("{}\n\n", description));
+            }
+            out.push_str(&get_details(command));
+            out.push_str(&format!("{}sh\n{}\n{}\n", fence, command.command, fence));
+        }
+    }
+
+    out
+}
+
+/// List of the working directory and the parameters, empty when there are none.
+fn get_details(command: &FileCommand) -> String {
+    let mut details = String::new();
+
+    if let Some(dir) = &command.options.dir {
+        details.push_str(&format!("- Working directory: `{}`\n", dir));
+    }
+
+    for (name, default) in params::get_placeholders(&command.command) {
+        match default {
+            Some(default) => details.push_str(&format!(
+                "- Parameter `{}`, defaults to `{}`\n",
+                name, default
+            )),
+            None => details.push_str(&format!("- Parameter `{}`\n", name)),
+        }
+    }
+
+    if !details.is_empty() {
+        details.push('\n');
+    }
+
+    details
+}
+
+/// A fence must be longer than any backtick run inside the command.
+fn get_fence(command: &str) -> String {
+    let longest_run = command
+        .split(|c| c != '`')
+        .map(|run| run.len())
+        .max()
+        .unwrap_or(0);
+
+    "`".repeat(std::cmp::max(3, longest_run + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_markdown() {
+        let files = vec![
+            CommandFile {
+                name: Some("docker".to_string()),
+                commands: vec![FileCommand {
+                    tag: "docker:run:alpine".to_string(),
+                    command: "docker run --rm -it alpine".to_string(),
+                    ..FileCommand::default()
+                }],
+            },
+            CommandFile {
+                name: Some("empty".to_string()),
+                commands: vec![],
+            },
+        ];
+
+        assert_eq!(
+            render_markdown("Command Manager", &files),
+            "# Command Manager\n\n## docker\n\n### `docker:run:alpine`\n\n```sh\ndocker run --rm -it alpine\n```\n\n## empty\n\n_No commands._\n"
+        );
+    }
+
+    #[test]
+    fn test_render_details() {
+        let mut command = FileCommand {
+            tag: "deploy".to_string(),
+            command: "deploy {{env}} --tag {{ version:latest }}".to_string(),
+            description: Some("Deploys the current branch.".to_string()),
+            ..FileCommand::default()
+        };
+        command.options.dir = Some("~/app".to_string());
+        let files = vec![CommandFile {
+            name: Some("ops".to_string()),
+            commands: vec![command],
+        }];
+
+        assert_eq!(
+            render_markdown("cm", &files),
+            "# cm\n\n## ops\n\n### `deploy`\n\nDeploys the current branch.\n\n- Working directory: `~/app`\n- Parameter `env`\n- Parameter `version`, defaults to `latest`\n\n```sh\ndeploy {{env}} --tag {{ version:latest }}\n```\n"
+        );
+    }
+
+    #[test]
+    fn test_fence_longer_than_backticks() {
+        assert_eq!(get_fence("echo `date`"), "```");
+        assert_eq!(get_fence("echo ````"), "`````");
+    }
+}
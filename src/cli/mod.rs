@@ -0,0 +1,724 @@
+pub mod export;
+
+use crate::cli::export::ExportFormat;
+use crate::core::clipboard;
+use crate::core::hooks::HookContext;
+use crate::core::params;
+use crate::core::preview::Preview;
+use crate::core::risk::Risk;
+use crate::core::runner;
+use crate::core::schedule::{self, Schedule};
+use crate::core::settings::Settings;
+use crate::core::watch::Watcher;
+use crate::core::workflow::StepResult;
+use crate::db::db::Db;
+use crate::db::sync::Syncer;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fs;
+use std::io;
+use std::io::Write;
+use std::time::Duration;
+
+const USAGE: &str = r"Usage: cm [--profile NAME] [COMMAND]
+
+Without command, the interactive interface is started.
+
+Options:
+  --profile NAME
+      Use the database of a profile from the [profiles] section of the configuration
+
+Commands:
+  copy [--substitute] TAG [KEY=VALUE...]
+      Copy a command of the library to the clipboard, through the terminal with OSC 52 and
+      wl-copy, xclip or pbcopy when available. With --substitute or values, {{KEY}}
+      placeholders are replaced like for run
+  export [--format markdown] [--output FILE]
+      Render every namespace and its commands as a Markdown cheatsheet, with their
+      descriptions, working directories and parameters
+  mv TAG NAMESPACE
+      Move a command of the library into a namespace, created when missing
+  namespace rename NAMESPACE NAME
+      Rename a namespace of the library
+  namespace merge NAMESPACE TARGET
+      Move every command of a namespace into the target one, then delete it
+  run [--dry-run] [--yes] TAG [KEY=VALUE...]
+      Run a workflow or a command of the library, {{KEY}} placeholders are replaced by the values.
+      With --dry-run, print what would be executed instead.
+      Dangerous commands ask to type their tag, unless --yes is given
+  set TAG KEY=VALUE...
+      Change a stored command, keys: risk=safe|confirm|dangerous|auto,
+      timeout=SECONDS|none, retries=COUNT|none, schedule=CRON|none, dir=PATH|none
+      and description=TEXT|none
+  scheduler
+      Run the commands with a schedule when they are due, until interrupted.
+      Results are recorded in the history
+  sync
+      Commit the library into the [sync] git directory, merge and push the remote
+  trash [list]
+      Print the deleted commands and namespaces with their id, oldest ones are purged
+      after [trash] purge_after_days
+  trash restore ID
+      Put a deleted command, or a namespace with its commands, back in the library
+  trash purge [ID]
+      Delete an entry of the trash for good, or every entry without id
+  watch TAG [PATH...]
+      Run a command again whenever files change under the paths or globs, the current
+      directory by default. Paths ignored by git are skipped
+  workflow add TAG STEP_TAG... [--continue-on-failure]
+      Chain stored commands, by default the workflow stops at the first failing step
+  workflow list
+      Print every workflow and its steps
+  workflow show TAG
+      Print the steps of a workflow and the results of its last run
+  workflow rm TAG
+      Delete a workflow
+  help
+      Print this message";
+
+pub struct Cli {
+    pub profile: Option<String>,
+    pub command: Option<Command>,
+}
+
+pub enum Command {
+    Copy {
+        tag: String,
+        params: Vec<String>,
+        substitute: bool,
+    },
+    Export {
+        format: ExportFormat,
+        output: Option<String>,
+    },
+    Help,
+    Move {
+        tag: String,
+        namespace: String,
+    },
+    Namespace(NamespaceCommand),
+    Run {
+        tag: String,
+        params: Vec<String>,
+        dry_run: bool,
+        yes: bool,
+    },
+    Set {
+        tag: String,
+        values: Vec<String>,
+    },
+    Scheduler,
+    Sync,
+    Trash(TrashCommand),
+    Watch {
+        tag: String,
+        paths: Vec<String>,
+    },
+    Workflow(WorkflowCommand),
+}
+
+pub enum NamespaceCommand {
+    Rename { namespace: String, name: String },
+    Merge { namespace: String, target: String },
+}
+
+pub enum TrashCommand {
+    List,
+    Restore(i64),
+    Purge(Option<i64>),
+}
+
+pub enum WorkflowCommand {
+    Add {
+        tag: String,
+        steps: Vec<String>,
+        continue_on_failure: bool,
+    },
+    List,
+    Show(String),
+    Remove(String),
+}
+
+impl Cli {
+    pub fn parse(args: impl Iterator<Item = String>) -> Result<Self, Box<dyn Error>> {
+        let (profile, args) = Cli::extract_profile(args.collect())?;
+        let mut args = args.into_iter();
+
+        let command = match args.next().as_deref() {
+            None => None,
+            Some("copy") => Some(Cli::parse_copy(args)?),
+            Some("export") => Some(Cli::parse_export(args)?),
+            Some("mv") => Some(Command::Move {
+                tag: args.next().ok_or("Missing command tag")?,
+                namespace: args.next().ok_or("Missing namespace")?,
+            }),
+            Some("namespace") => Some(Command::Namespace(Cli::parse_namespace(args)?)),
+            Some("run") => Some(Cli::parse_run(args)?),
+            Some("set") => Some(Cli::parse_set(args)?),
+            Some("scheduler") => Some(Command::Scheduler),
+            Some("sync") => Some(Command::Sync),
+            Some("trash") => Some(Command::Trash(Cli::parse_trash(args)?)),
+            Some("watch") => Some(Command::Watch {
+                tag: args.next().ok_or("Missing tag to watch")?,
+                paths: args.collect(),
+            }),
+            Some("workflow") => Some(Command::Workflow(Cli::parse_workflow(args)?)),
+            Some("help") | Some("-h") | Some("--help") => Some(Command::Help),
+            Some(other) => return Err(format!("Unknown command: {}\n\n{}", other, USAGE).into()),
+        };
+
+        Ok(Cli { profile, command })
+    }
+
+    /// `--profile` is only read before the command, after it belongs to the command, like a
+    /// `profile=` parameter or a watched path.
+    fn extract_profile(args: Vec<String>) -> Result<(Option<String>, Vec<String>), Box<dyn Error>> {
+        let mut profile = None;
+
+        let mut args = args.into_iter().peekable();
+        while let Some(arg) = args.next_if(|arg| arg.starts_with("--profile")) {
+            if arg == "--profile" {
+                profile = Some(Cli::get_value(&arg, None, &mut args)?);
+            } else if let Some(value) = arg.strip_prefix("--profile=") {
+                profile = Some(value.to_string());
+            } else {
+                return Err(format!("Unknown option: {}\n\n{}", arg, USAGE).into());
+            }
+        }
+
+        Ok((profile, args.collect()))
+    }
+
+    fn parse_export(mut args: impl Iterator<Item = String>) -> Result<Command, Box<dyn Error>> {
+        let mut format = ExportFormat::Markdown;
+        let mut output = None;
+
+        while let Some(arg) = args.next() {
+            let (flag, value) = match arg.split_once('=') {
+                Some((flag, value)) => (flag.to_string(), Some(value.to_string())),
+                None => (arg, None),
+            };
+
+            match flag.as_str() {
+                "--format" | "-f" => {
+                    format = ExportFormat::from_name(&Cli::get_value(&flag, value, &mut args)?)?
+                }
+                "--output" | "-o" => output = Some(Cli::get_value(&flag, value, &mut args)?),
+                _ => return Err(format!("Unknown export option: {}", flag).into()),
+            }
+        }
+
+        Ok(Command::Export { format, output })
+    }
+
+    fn parse_copy(args: impl Iterator<Item = String>) -> Result<Command, Box<dyn Error>> {
+        let (flags, mut rest): (Vec<String>, Vec<String>) =
+            args.partition(|arg| arg.starts_with("--"));
+
+        if let Some(flag) = flags.iter().find(|flag| *flag != "--substitute") {
+            return Err(format!("Unknown copy option: {}", flag).into());
+        }
+        if rest.is_empty() {
+            return Err("Missing tag to copy".into());
+        }
+
+        Ok(Command::Copy {
+            tag: rest.remove(0),
+            substitute: !flags.is_empty() || rest.len() > 1,
+            params: rest,
+        })
+    }
+
+    fn parse_run(args: impl Iterator<Item = String>) -> Result<Command, Box<dyn Error>> {
+        let (flags, mut rest): (Vec<String>, Vec<String>) =
+            args.partition(|arg| arg.starts_with("--"));
+
+        if let Some(flag) = flags
+            .iter()
+            .find(|flag| !["--dry-run", "--yes"].contains(&flag.as_str()))
+        {
+            return Err(format!("Unknown run option: {}", flag).into());
+        }
+        if rest.is_empty() {
+            return Err("Missing tag to run".into());
+        }
+
+        Ok(Command::Run {
+            tag: rest.remove(0),
+            params: rest,
+            dry_run: flags.iter().any(|flag| flag == "--dry-run"),
+            yes: flags.iter().any(|flag| flag == "--yes"),
+        })
+    }
+
+    fn parse_set(mut args: impl Iterator<Item = String>) -> Result<Command, Box<dyn Error>> {
+        let tag = args.next().ok_or("Missing command tag")?;
+        let values = args.collect::<Vec<_>>();
+
+        if values.is_empty() {
+            return Err("Missing KEY=VALUE to set".into());
+        }
+
+        Ok(Command::Set { tag, values })
+    }
+
+    fn parse_namespace(
+        mut args: impl Iterator<Item = String>,
+    ) -> Result<NamespaceCommand, Box<dyn Error>> {
+        let action = args.next().ok_or("Missing namespace action")?;
+        let namespace = args.next().ok_or("Missing namespace")?;
+
+        match action.as_str() {
+            "rename" => Ok(NamespaceCommand::Rename {
+                namespace,
+                name: args.next().ok_or("Missing new name")?,
+            }),
+            "merge" => Ok(NamespaceCommand::Merge {
+                namespace,
+                target: args.next().ok_or("Missing target namespace")?,
+            }),
+            _ => Err(format!("Unknown namespace action: {}", action).into()),
+        }
+    }
+
+    fn parse_trash(mut args: impl Iterator<Item = String>) -> Result<TrashCommand, Box<dyn Error>> {
+        let parse_id = |id: String| {
+            id.parse::<i64>()
+                .map_err(|_| format!("Invalid trash id: {}", id))
+        };
+
+        match args.next().as_deref() {
+            None | Some("list") => Ok(TrashCommand::List),
+            Some("restore") => Ok(TrashCommand::Restore(parse_id(
+                args.next().ok_or("Missing trash id")?,
+            )?)),
+            Some("purge") => Ok(TrashCommand::Purge(args.next().map(parse_id).transpose()?)),
+            Some(action) => Err(format!("Unknown trash action: {}", action).into()),
+        }
+    }
+
+    fn parse_workflow(
+        mut args: impl Iterator<Item = String>,
+    ) -> Result<WorkflowCommand, Box<dyn Error>> {
+        let action = args.next().ok_or("Missing workflow action")?;
+
+        match action.as_str() {
+            "add" => {
+                let tag = args.next().ok_or("Missing workflow tag")?;
+                let (flags, steps): (Vec<String>, Vec<String>) =
+                    args.partition(|arg| arg.starts_with("--"));
+
+                if let Some(flag) = flags.iter().find(|flag| *flag != "--continue-on-failure") {
+                    return Err(format!("Unknown workflow option: {}", flag).into());
+                }
+
+                Ok(WorkflowCommand::Add {
+                    tag,
+                    steps,
+                    continue_on_failure: !flags.is_empty(),
+                })
+            }
+            "list" => Ok(WorkflowCommand::List),
+            "show" => Ok(WorkflowCommand::Show(
+                args.next().ok_or("Missing workflow tag")?,
+            )),
+            "rm" => Ok(WorkflowCommand::Remove(
+                args.next().ok_or("Missing workflow tag")?,
+            )),
+            _ => Err(format!("Unknown workflow action: {}", action).into()),
+        }
+    }
+
+    fn get_value(
+        flag: &str,
+        value: Option<String>,
+        args: &mut impl Iterator<Item = String>,
+    ) -> Result<String, Box<dyn Error>> {
+        value
+            .or_else(|| args.next())
+            .ok_or_else(|| format!("Missing value for {}", flag).into())
+    }
+}
+
+impl Command {
+    pub fn run(self, profile: Option<&str>) -> Result<(), Box<dyn Error>> {
+        let settings = Settings::load()?;
+
+        match self {
+            Command::Copy {
+                tag,
+                params,
+                substitute,
+            } => {
+                let db = Db::new(&settings, profile)?;
+                db.init_db()?;
+
+                let mut command = db
+                    .get_command(&tag)?
+                    .ok_or_else(|| format!("Unknown command: {}", tag))?;
+                if substitute {
+                    command = params::substitute(&command, &params::parse(&params)?)?;
+                }
+
+                let method = clipboard::copy(&command)?;
+                println!("{} copied ({})", tag, method);
+            }
+            Command::Export { format, output } => {
+                let db = Db::new(&settings, profile)?;
+                db.init_db()?;
+
+                let content = export::export(&db, &format)?;
+
+                match output {
+                    Some(path) => fs::write(path, content)?,
+                    None => print!("{}", content),
+                }
+            }
+            Command::Help => println!("{}", USAGE),
+            Command::Run {
+                tag,
+                params,
+                dry_run,
+                yes,
+            } => {
+                let params = params::parse(&params)?;
+
+                let db = Db::new(&settings, profile)?;
+                db.init_db()?;
+                let risks = db.get_risks()?;
+
+                if let Some(workflow) = db.get_workflow(&tag)? {
+                    if dry_run {
+                        for (step, preview) in workflow.steps.iter().zip(workflow.preview(&params)?)
+                        {
+                            println!("==> {}", step.tag);
+                            for line in preview.get_lines() {
+                                println!("    {}", line);
+                            }
+                        }
+
+                        return Ok(());
+                    }
+
+                    if !yes && workflow.get_risk(&risks) == Risk::Dangerous {
+                        confirm_tag(&tag)?;
+                    }
+
+                    if !workflow.run(&db, &params, &settings)? {
+                        return Err(format!("Workflow {} failed", tag).into());
+                    }
+
+                    return Ok(());
+                }
+
+                let command = db
+                    .get_command(&tag)?
+                    .ok_or_else(|| format!("Unknown workflow or command: {}", tag))?;
+
+                if dry_run {
+                    for line in Preview::new(&command, &params)?.get_lines() {
+                        println!("{}", line);
+                    }
+
+                    return Ok(());
+                }
+
+                let command = params::substitute(&command, &params)?;
+                let risk = risks
+                    .get(&tag)
+                    .copied()
+                    .unwrap_or_else(|| Risk::detect(&command));
+
+                if !yes && risk == Risk::Dangerous {
+                    confirm_tag(&tag)?;
+                }
+
+                let options = db.get_run_options()?.remove(&tag).unwrap_or_default();
+                let namespace = db.get_tag_namespace(&tag)?;
+                let context = HookContext::new(&tag, &command, namespace.as_deref());
+                let outcome = runner::run(&context, &options, &settings)?;
+                db.add_history(&outcome.to_step_result(&tag), &command, None)?;
+                runner::notify(&settings.notify, &tag, outcome.exit_code, outcome.duration);
+
+                if outcome.timed_out {
+                    eprintln!("{}: {}", tag, outcome.describe());
+                }
+                if !outcome.is_success() {
+                    std::process::exit(outcome.exit_code.unwrap_or(1));
+                }
+            }
+            Command::Set { tag, values } => {
+                let db = Db::new(&settings, profile)?;
+                db.init_db()?;
+
+                let mut options = db.get_run_options()?.remove(&tag).unwrap_or_default();
+
+                for (key, value) in params::parse(&values)? {
+                    match key.as_str() {
+                        "risk" if value == "auto" => db.set_risk(&tag, None)?,
+                        "risk" => db.set_risk(&tag, Some(Risk::from_name(&value)?))?,
+                        "timeout" | "retries" | "dir" => options.set(&key, &value)?,
+                        "schedule" if value == "none" => db.set_schedule(&tag, None)?,
+                        "schedule" => {
+                            Schedule::new(&tag, &value)?;
+                            db.set_schedule(&tag, Some(&value))?;
+                        }
+                        "description" if value == "none" => db.set_description(&tag, None)?,
+                        "description" => db.set_description(&tag, Some(&value))?,
+                        _ => return Err(format!("Unknown setting: {}", key).into()),
+                    }
+                }
+
+                db.set_run_options(&tag, &options)?;
+            }
+            Command::Move { tag, namespace } => {
+                let db = Db::new(&settings, profile)?;
+                db.init_db()?;
+
+                if db.get_namespace(&namespace)?.is_none() {
+                    db.add_namespace(&namespace)?;
+                }
+                db.move_command(&tag, &namespace)?;
+            }
+            Command::Namespace(command) => {
+                let db = Db::new(&settings, profile)?;
+                db.init_db()?;
+
+                match command {
+                    NamespaceCommand::Rename { namespace, name } => {
+                        db.rename_namespace(&namespace, &name)?
+                    }
+                    NamespaceCommand::Merge { namespace, target } => {
+                        db.merge_namespace(&namespace, &target)?
+                    }
+                }
+            }
+            Command::Scheduler => {
+                let db = Db::new(&settings, profile)?;
+                db.init_db()?;
+
+                schedule::run_scheduler(&db, &settings)?;
+            }
+            Command::Watch { tag, paths } => {
+                let db = Db::new(&settings, profile)?;
+                db.init_db()?;
+
+                watch(&db, &settings, &tag, &paths)?;
+            }
+            Command::Trash(command) => {
+                let db = Db::new(&settings, profile)?;
+                db.init_db()?;
+                db.purge_expired_trash(&settings)?;
+
+                command.run(&db)?;
+            }
+            Command::Workflow(command) => {
+                let db = Db::new(&settings, profile)?;
+                db.init_db()?;
+
+                command.run(&db)?;
+            }
+            Command::Sync => {
+                let sync_settings = settings
+                    .sync
+                    .as_ref()
+                    .ok_or("No [sync] section in the configuration file")?;
+
+                let db = Db::new(&settings, profile)?;
+                db.init_db()?;
+
+                let report = Syncer::new(&db, sync_settings).run()?;
+
+                if report.is_empty() {
+                    println!("Already up to date");
+                }
+                for line in report {
+                    println!("{}", line);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl TrashCommand {
+    fn run(self, db: &Db) -> Result<(), Box<dyn Error>> {
+        match self {
+            TrashCommand::List => {
+                for entry in db.get_trash()? {
+                    match &entry.command {
+                        Some(command) => println!(
+                            "{}. {}  {} in {}: {}",
+                            entry.id,
+                            entry.deleted_at,
+                            entry.get_name(),
+                            entry.namespace,
+                            command
+                        ),
+                        None => println!(
+                            "{}. {}  namespace {} with {} command(s)",
+                            entry.id, entry.deleted_at, entry.namespace, entry.commands
+                        ),
+                    }
+                }
+            }
+            TrashCommand::Restore(id) => {
+                let entry = db.restore_trash(id)?;
+                println!("{} restored", entry.get_name());
+            }
+            TrashCommand::Purge(Some(id)) => db.purge_trash_entry(id)?,
+            TrashCommand::Purge(None) => {
+                let purged = db.purge_trash(None)?;
+                println!("{} item(s) purged", purged);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl WorkflowCommand {
+    fn run(self, db: &Db) -> Result<(), Box<dyn Error>> {
+        match self {
+            WorkflowCommand::Add {
+                tag,
+                steps,
+                continue_on_failure,
+            } => db.add_workflow(&tag, &steps, continue_on_failure)?,
+            WorkflowCommand::List => {
+                for workflow in db.get_workflows()? {
+                    println!("{}: {}", workflow.tag, workflow.describe());
+                }
+            }
+            WorkflowCommand::Show(tag) => {
+                let workflow = db
+                    .get_workflow(&tag)?
+                    .ok_or_else(|| format!("Unknown workflow: {}", tag))?;
+                let last_run = db.get_last_workflow_run(&tag)?;
+
+                println!("{}: {}", workflow.tag, workflow.describe());
+                match &last_run {
+                    Some(run) => println!("Last run: {}", run.started_at),
+                    None => println!("Never run"),
+                }
+
+                for (i, step) in workflow.steps.iter().enumerate() {
+                    let result = last_run
+                        .as_ref()
+                        .and_then(|run| run.results.get(i))
+                        .map_or("skipped".to_string(), format_result);
+
+                    match &step.command {
+                        Some(command) => {
+                            println!("{}. {}: {}  [{}]", i + 1, step.tag, command, result)
+                        }
+                        None => println!("{}. {}: deleted command", i + 1, step.tag),
+                    }
+                }
+            }
+            WorkflowCommand::Remove(tag) => {
+                if !db.delete_workflow(&tag)? {
+                    return Err(format!("Unknown workflow: {}", tag).into());
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Runs a command, then again after every change, until interrupted.
+fn watch(db: &Db, settings: &Settings, tag: &str, paths: &[String]) -> Result<(), Box<dyn Error>> {
+    let command = db
+        .get_command(tag)?
+        .ok_or_else(|| format!("Unknown command: {}", tag))?;
+    let command = params::substitute(&command, &BTreeMap::new())?;
+
+    let risk = db
+        .get_risks()?
+        .get(tag)
+        .copied()
+        .unwrap_or_else(|| Risk::detect(&command));
+    if risk == Risk::Dangerous {
+        return Err(format!("{} is dangerous and can't be watched", tag).into());
+    }
+
+    let options = db.get_run_options()?.remove(tag).unwrap_or_default();
+    let namespace = db.get_tag_namespace(tag)?;
+    let context = HookContext::new(tag, &command, namespace.as_deref());
+
+    let dir = options.get_dir().map_or_else(std::env::current_dir, Ok)?;
+    let debounce = Duration::from_millis(settings.watch.debounce_ms);
+    let mut watcher = Watcher::new(paths, &dir, debounce)?;
+    let mut reason = "started".to_string();
+
+    loop {
+        // Clear the screen, the latest run is shown alone
+        print!("\x1b[2J\x1b[H");
+        println!("==> {}: {}", tag, reason);
+
+        match runner::run(&context, &options, settings) {
+            Ok(outcome) => println!("==> {}: {}, watching for changes", tag, outcome.describe()),
+            Err(e) => eprintln!("{}", e),
+        }
+
+        let changed = watcher.wait();
+        let first = changed[0].strip_prefix(&dir).unwrap_or(&changed[0]);
+        reason = match changed.len() {
+            1 => format!("{} changed", first.display()),
+            count => format!("{} and {} more changed", first.display(), count - 1),
+        };
+    }
+}
+
+/// Dangerous commands only run once their tag has been typed back.
+fn confirm_tag(tag: &str) -> Result<(), Box<dyn Error>> {
+    print!("{} is dangerous, type the tag to confirm: ", tag);
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+
+    if answer.trim() != tag {
+        return Err("Cancelled, the tag does not match".into());
+    }
+
+    Ok(())
+}
+
+fn format_result(result: &StepResult) -> String {
+    let state = match result.exit_code {
+        Some(code) => format!("exit {}", code),
+        None => "killed".to_string(),
+    };
+
+    format!("{} in {:.1}s", state, result.duration.as_secs_f32())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(line: &str) -> Result<Cli, Box<dyn Error>> {
+        Cli::parse(line.split_whitespace().map(str::to_string))
+    }
+
+    #[test]
+    fn test_profile_before_the_command() {
+        let cli = parse("--profile work run deploy").unwrap();
+        assert_eq!(cli.profile.as_deref(), Some("work"));
+
+        let cli = parse("--profile=work").unwrap();
+        assert_eq!(cli.profile.as_deref(), Some("work"));
+        assert!(cli.command.is_none());
+
+        // After the command it is left to the command
+        let cli = parse("watch build --profile src").unwrap();
+        assert!(cli.profile.is_none());
+        match cli.command {
+            Some(Command::Watch { paths, .. }) => assert_eq!(paths, vec!["--profile", "src"]),
+            _ => panic!("expected a watch command"),
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use std::error::Error;
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+/// Local clipboard tools, the first one working is used.
+const TOOLS: &[(&str, &[&str])] = &[
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("pbcopy", &[]),
+];
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Copies the text with an OSC 52 sequence when stdout is a terminal, which reaches the local
+/// clipboard over SSH and from tmux. Terminals ignoring it are covered by `wl-copy`, `xclip`
+/// or `pbcopy` when one is available. Returns how the text was copied, e.g. `OSC 52, xclip`.
+pub fn copy(text: &str) -> Result<String, Box<dyn Error>> {
+    let mut methods = Vec::new();
+
+    if unsafe { libc::isatty(libc::STDOUT_FILENO) } == 1 {
+        let mut stdout = io::stdout();
+        stdout.write_all(get_osc52(text, std::env::var("TMUX").is_ok()).as_bytes())?;
+        stdout.flush()?;
+        methods.push("OSC 52");
+    }
+
+    if let Some(tool) = TOOLS.iter().find(|(tool, args)| pipe(tool, args, text)) {
+        methods.push(tool.0);
+    }
+
+    if methods.is_empty() {
+        return Err(
+            "No clipboard: stdout is not a terminal and wl-copy, xclip or pbcopy failed".into(),
+        );
+    }
+
+    Ok(methods.join(", "))
+}
+
+/// Inside tmux the sequence is also passed through to the outer terminal, tmux itself only
+/// takes it with `set-clipboard on` and passes it with `allow-passthrough on`.
+fn get_osc52(text: &str, tmux: bool) -> String {
+    let sequence = format!("\x1b]52;c;{}\x07", encode_base64(text.as_bytes()));
+
+    match tmux {
+        true => format!(
+            "{}\x1bPtmux;{}\x1b\\",
+            sequence,
+            sequence.replace('\x1b', "\x1b\x1b")
+        ),
+        false => sequence,
+    }
+}
+
+/// False when the tool is missing or fails, like `xclip` without a display.
+fn pipe(tool: &str, args: &[&str], text: &str) -> bool {
+    let child = Command::new(tool)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+
+    let mut child = match child {
+        Ok(child) => child,
+        Err(_) => return false,
+    };
+
+    let written = child
+        .stdin
+        .take()
+        .is_some_and(|mut stdin| stdin.write_all(text.as_bytes()).is_ok());
+
+    child.wait().is_ok_and(|status| status.success()) && written
+}
+
+fn encode_base64(bytes: &[u8]) -> String {
+    let mut encoded = String::new();
+
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as usize) << 16 | (b[1] as usize) << 8 | b[2] as usize;
+
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => encoded.push(BASE64[(n >> (18 - 6 * i)) & 63] as char),
+                false => encoded.push('='),
+            }
+        }
+    }
+
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_base64() {
+        assert_eq!(encode_base64(b""), "");
+        assert_eq!(encode_base64(b"f"), "Zg==");
+        assert_eq!(encode_base64(b"fo"), "Zm8=");
+        assert_eq!(encode_base64(b"foo"), "Zm9v");
+        assert_eq!(encode_base64("ls ~/é".as_bytes()), "bHMgfi/DqQ==");
+    }
+
+    #[test]
+    fn test_get_osc52() {
+        assert_eq!(get_osc52("foo", false), "\x1b]52;c;Zm9v\x07");
+        assert_eq!(
+            get_osc52("foo", true),
+            "\x1b]52;c;Zm9v\x07\x1bPtmux;\x1b\x1b]52;c;Zm9v\x07\x1b\\"
+        );
+    }
+}
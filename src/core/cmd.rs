@@ -1,5 +1,13 @@
 use std::error::Error;
-use std::process::Command;
+use std::io;
+use std::os::unix::process::CommandExt;
+use std::path::Path;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Time left to a command to exit after the timeout, before it is killed.
+const KILL_DELAY: Duration = Duration::from_secs(2);
 
 #[derive(Debug)]
 pub struct Cmd {
@@ -14,58 +22,128 @@ impl Cmd {
         Ok(Cmd { cmd, args })
     }
 
+    /// Lines starting with `sh` run as they are, others with `sh -c`.
     fn parse_line(line: &str) -> Result<(String, Vec<String>), Box<dyn Error>> {
-        let line = line.to_string();
-
         let mut split = line
             .split_whitespace()
             .map(|s| s.to_string())
             .collect::<Vec<_>>();
 
-        match split.len() {
-            x if x > 1 && split[0] != "sh" && split[1] != "-c" => {
-                let cmd = "sh".to_string();
-                let mut args = vec!["-c".to_string()];
-                args.extend(vec![split.join(" ")]);
+        match split.first().map(String::as_str) {
+            None => Err("Command line is empty".into()),
+            Some("sh") => {
+                let cmd = split.remove(0);
 
-                return Ok((cmd, args));
+                Ok((cmd, split))
             }
+            Some(_) => Ok(("sh".to_string(), vec!["-c".to_string(), split.join(" ")])),
+        }
+    }
 
-            x if x > 1 && split[0] == "sh" => {
-                let cmd = split.remove(0);
+    pub fn get_program(&self) -> &str {
+        &self.cmd
+    }
+
+    pub fn get_args(&self) -> &[String] {
+        &self.args
+    }
+
+    pub fn run(&self) -> Result<(), Box<dyn Error>> {
+        let mut cmd = Command::new(self.cmd.clone());
+        cmd.args(self.args.clone());
+        cmd.spawn()?.wait()?;
+        Ok(())
+    }
+
+    /// Runs in the terminal, in `dir` when given, and returns the exit status.
+    pub fn status(&self, dir: Option<&Path>) -> io::Result<ExitStatus> {
+        let mut cmd = Command::new(&self.cmd);
 
-                return Ok((cmd, split));
+        if let Some(dir) = dir {
+            cmd.current_dir(dir);
+        }
+
+        cmd.args(&self.args).status()
+    }
+
+    /// Runs in the terminal, the process group of the command is terminated after `timeout`,
+    /// then killed if still running a few seconds later. Returns the exit status and whether
+    /// the command timed out.
+    pub fn status_with_timeout(
+        &self,
+        timeout: Duration,
+        dir: Option<&Path>,
+    ) -> io::Result<(ExitStatus, bool)> {
+        let mut cmd = Command::new(&self.cmd);
+
+        if let Some(dir) = dir {
+            cmd.current_dir(dir);
+        }
+
+        let mut child = cmd.args(&self.args).process_group(0).spawn()?;
+        let pgid = child.id() as i32;
+
+        // The command leads its own group, give it the terminal as a shell would
+        let terminal = unsafe { libc::isatty(libc::STDIN_FILENO) } == 1;
+        if terminal {
+            unsafe {
+                libc::signal(libc::SIGTTOU, libc::SIG_IGN);
+                libc::tcsetpgrp(libc::STDIN_FILENO, pgid);
             }
+        }
 
-            x if x == 1 && split[0] == "sh" => {
-                let cmd = split.remove(0);
+        let started = Instant::now();
+        let mut signal = None;
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
 
-                return Ok((cmd, vec![]));
+            if let Some(next) = get_timeout_signal(started.elapsed(), timeout, signal) {
+                unsafe {
+                    libc::kill(-pgid, next);
+                }
+                signal = Some(next);
             }
-            x if x == 1 && split[0] != "sh" => {
-                let args = vec!["-c".to_string(), split.remove(0)]
-                    .into_iter()
-                    .map(|s| s.to_string())
-                    .collect();
 
-                return Ok(("sh".to_string(), args));
+            thread::sleep(Duration::from_millis(50));
+        };
+
+        if terminal {
+            unsafe {
+                libc::tcsetpgrp(libc::STDIN_FILENO, libc::getpgrp());
+                libc::signal(libc::SIGTTOU, libc::SIG_DFL);
             }
-            0 => panic!("Command line is empty"),
-            _ => panic!("Command line is invalid"),
         }
+
+        Ok((status, signal.is_some()))
     }
 
-    pub fn run(&self) -> Result<(), Box<dyn Error>> {
-        let mut cmd = Command::new(self.cmd.clone());
-        cmd.args(self.args.clone());
-        cmd.spawn()?.wait()?;
-        Ok(())
+    /// Spawns without a terminal, outputs are piped for the caller to read.
+    /// The child leads its own process group so signals also reach the commands it starts.
+    pub fn spawn_piped(&self, dir: Option<&Path>) -> io::Result<Child> {
+        let mut cmd = Command::new(&self.cmd);
+
+        if let Some(dir) = dir {
+            cmd.current_dir(dir);
+        }
+
+        cmd.args(&self.args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .process_group(0)
+            .spawn()
     }
+}
 
-    pub fn create_and_run(line: &str) -> Result<Self, Box<dyn Error>> {
-        let cmd = Cmd::new(line)?;
-        cmd.run()?;
-        Ok(cmd)
+/// Signal to send now to a command started `elapsed` ago: SIGTERM once it has run longer than
+/// `timeout`, then SIGKILL when it is still running a few seconds later.
+pub fn get_timeout_signal(elapsed: Duration, timeout: Duration, sent: Option<i32>) -> Option<i32> {
+    match sent {
+        None if elapsed >= timeout => Some(libc::SIGTERM),
+        Some(libc::SIGTERM) if elapsed >= timeout + KILL_DELAY => Some(libc::SIGKILL),
+        _ => None,
     }
 }
 
@@ -97,4 +175,16 @@ mod tests {
     fn test_initial_sh_with_args() {
         exec_valid_cmd("sh -c cd")
     }
+
+    #[test]
+    fn test_parse_line() {
+        assert!(Cmd::new("  ").is_err());
+
+        let cmd = Cmd::new("bash -c 'ls'").unwrap();
+        assert_eq!(cmd.get_program(), "sh");
+        assert_eq!(cmd.get_args(), ["-c", "bash -c 'ls'"]);
+
+        let cmd = Cmd::new("sh -c ls").unwrap();
+        assert_eq!(cmd.get_args(), ["-c", "ls"]);
+    }
 }
@@ -28,6 +28,16 @@ pub struct FontConfig {
     pub text_fg: Color,
     pub first_letter_fg: Color,
     pub input_fg: Color,
+    pub project_fg: Color,
+    pub workflow_fg: Color,
+    pub team_fg: Color,
+    pub stderr_fg: Color,
+    pub dangerous_fg: Color,
+    pub info_fg: Color,
+    pub warning_fg: Color,
+    pub error_fg: Color,
+    pub hint_fg: Color,
+    pub marked_fg: Color,
 }
 
 impl FontConfig {
@@ -41,6 +51,16 @@ impl FontConfig {
             text_fg: Color::White,
             first_letter_fg: Color::Red,
             input_fg: Color::Yellow,
+            project_fg: Color::Cyan,
+            workflow_fg: Color::LightBlue,
+            team_fg: Color::Magenta,
+            stderr_fg: Color::LightRed,
+            dangerous_fg: Color::Red,
+            info_fg: Color::Green,
+            warning_fg: Color::Yellow,
+            error_fg: Color::LightRed,
+            hint_fg: Color::DarkGray,
+            marked_fg: Color::LightGreen,
         }
     }
 }
@@ -50,15 +70,46 @@ pub struct NameConfig {
     pub namespace: String,
     pub command: String,
     pub tag: String,
+    pub directories: String,
     pub tabs_title: Vec<String>,
     pub namespaces_title: String,
     pub commands_title: String,
     pub tags_title: String,
     pub highlight_symbol: String,
+    pub project_marker: String,
+    pub workflow_marker: String,
+    pub dangerous_marker: String,
+    pub favorite_marker: String,
+    pub workflows_namespace: String,
+    pub team_marker: String,
     pub confirm_title: String,
     pub add_namespace_title: String,
+    pub rename_namespace_title: String,
+    pub prefix_tags_title: String,
+    pub move_title: String,
+    pub duplicate_title: String,
+    pub merge_title: String,
+    pub sequence_tag: String,
+    pub mark_marker: String,
+    pub scrollbar_thumb: String,
+    pub line_break_marker: String,
     pub add_command_title: String,
     pub add_tag_title: String,
+    pub add_directories_title: String,
+    pub confirm_tag_title: String,
+    pub sync_conflicts_title: String,
+    pub help_title: String,
+    pub profile_title: String,
+    pub output_title: String,
+    pub watching: String,
+    pub jobs_title: String,
+    pub fanout_title: String,
+    pub schedule_title: String,
+    pub history_title: String,
+    pub favorites_title: String,
+    pub settings_title: String,
+    pub trash_title: String,
+    pub spinner: Vec<String>,
 }
 
 impl NameConfig {
@@ -68,19 +119,58 @@ impl NameConfig {
             namespace: "namespace".to_string(),
             command: "command".to_string(),
             tag: "tag".to_string(),
+            directories: "directories".to_string(),
             tabs_title: vec![
-                "Tab 1".to_string(),
-                "Tab 2".to_string(),
-                "Tab 3".to_string(),
+                "1 Library".to_string(),
+                "2 History".to_string(),
+                "3 Favorites".to_string(),
+                "4 Jobs".to_string(),
+                "5 Fan-out".to_string(),
+                "6 Schedule".to_string(),
+                "7 Settings".to_string(),
+                "8 Trash".to_string(),
             ],
             namespaces_title: "Namespaces".to_string(),
             commands_title: "Commands".to_string(),
             tags_title: "Tags".to_string(),
             highlight_symbol: "⟩".to_string(),
+            project_marker: "◆".to_string(),
+            workflow_marker: "↻".to_string(),
+            dangerous_marker: "⚠".to_string(),
+            favorite_marker: "★".to_string(),
+            workflows_namespace: "workflows".to_string(),
+            team_marker: "◇".to_string(),
             confirm_title: "Confirm".to_string(),
             add_namespace_title: "Type the namespace name".to_string(),
+            rename_namespace_title: "Type the new namespace name".to_string(),
+            prefix_tags_title: "Type the prefix to add to the tags".to_string(),
+            move_title: "Move to".to_string(),
+            duplicate_title: "Duplicate into".to_string(),
+            merge_title: "Merge into".to_string(),
+            sequence_tag: "marked commands".to_string(),
+            mark_marker: "●".to_string(),
+            scrollbar_thumb: "┃".to_string(),
+            line_break_marker: "↵".to_string(),
             add_command_title: "Type the command script".to_string(),
             add_tag_title: "Type the tag name".to_string(),
+            add_directories_title: "Type directories, globs or @sets".to_string(),
+            confirm_tag_title: "Type the tag".to_string(),
+            sync_conflicts_title: "Sync conflicts".to_string(),
+            help_title: "Keys (press any key to close)".to_string(),
+            profile_title: "Profile (p)".to_string(),
+            output_title: "Output".to_string(),
+            watching: "watching for changes".to_string(),
+            jobs_title: "Jobs".to_string(),
+            fanout_title: "Fan-out".to_string(),
+            schedule_title: "Schedule".to_string(),
+            history_title: "History".to_string(),
+            favorites_title: "Favorites".to_string(),
+            settings_title: "Settings".to_string(),
+            trash_title: "Trash".to_string(),
+            spinner: ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"]
+                .iter()
+                .map(|frame| frame.to_string())
+                .collect(),
         }
     }
 }
@@ -89,13 +179,19 @@ pub struct LayoutConfig {
     pub app_block: Vec<Constraint>,
     pub main_block: Vec<Constraint>,
     pub lists_block: Vec<Constraint>,
+    pub jobs_block: Vec<Constraint>,
+    pub fanout_block: Vec<Constraint>,
     pub highlight_border_fg: Color,
 }
 
 impl LayoutConfig {
     pub fn new() -> LayoutConfig {
         LayoutConfig {
-            app_block: vec![Constraint::Length(3), Constraint::Min(0)],
+            app_block: vec![
+                Constraint::Length(3),
+                Constraint::Min(0),
+                Constraint::Length(1),
+            ],
             main_block: vec![
                 Constraint::Percentage(50),
                 Constraint::Percentage(20),
@@ -106,6 +202,8 @@ impl LayoutConfig {
                 Constraint::Percentage(70),
                 Constraint::Percentage(15),
             ],
+            jobs_block: vec![Constraint::Percentage(40), Constraint::Percentage(60)],
+            fanout_block: vec![Constraint::Percentage(40), Constraint::Percentage(60)],
             highlight_border_fg: Color::Green,
         }
     }
@@ -114,6 +212,17 @@ impl LayoutConfig {
 pub struct TextConfig {
     pub confirm_command: String,
     pub confirm_command_answer: String,
+    pub confirm_fork: String,
+    pub sync_conflicts: String,
+    pub no_jobs: String,
+    pub no_fanout: String,
+    pub no_schedules: String,
+    pub no_history: String,
+    pub no_favorites: String,
+    pub no_trash: String,
+    pub confirm_dangerous: String,
+    pub dangerous_refused: String,
+    pub jump_to_letter: String,
 }
 
 impl TextConfig {
@@ -121,6 +230,20 @@ impl TextConfig {
         TextConfig {
             confirm_command: "Execute the selected command ? (press Esc to cancel)".to_string(),
             confirm_command_answer: "Press Enter".to_string(),
+            confirm_fork: "Copy into your library ? (press Esc to cancel)".to_string(),
+            sync_conflicts: "Resolve them, then run cm sync again (press Esc to close)".to_string(),
+            no_jobs: "No jobs, press b on a command to run it in the background".to_string(),
+            confirm_dangerous: "Dangerous command, type its tag and press Enter to execute it (press Esc to cancel)".to_string(),
+            dangerous_refused: "Dangerous commands only run with Enter, after typing their tag".to_string(),
+            jump_to_letter: "Type the first letter of the item to jump to".to_string(),
+            no_fanout: "No fan-out, press o on a command to run it in several directories"
+                .to_string(),
+            no_history: "No runs yet, commands run from cm or cm run are recorded here".to_string(),
+            no_favorites: "No favorites, press s on a command of the library to star it"
+                .to_string(),
+            no_trash: "Trash is empty, deleted commands and namespaces are kept here".to_string(),
+            no_schedules: "No schedules, add one with cm set TAG schedule=\"0 3 * * *\" and run cm scheduler"
+                .to_string(),
         }
     }
 }
@@ -10,7 +10,7 @@ use std::time::Duration;
 use tui::backend::CrosstermBackend;
 use tui::Terminal;
 
-pub fn run_app(terminal: &mut Terminal<CrosstermBackend<Stdout>>, mut app: App) -> ParserResult {
+pub fn run_app(terminal: &mut Terminal<CrosstermBackend<Stdout>>, app: &mut App) -> ParserResult {
     let mut tabs = app.tabs.as_ref().borrow_mut();
     tabs.is_selected = true;
     drop(tabs);
@@ -20,19 +20,25 @@ pub fn run_app(terminal: &mut Terminal<CrosstermBackend<Stdout>>, mut app: App)
             return Ok(None);
         }
 
-        terminal.draw(|f| ui(f, &mut app))?;
+        app.tick();
+        terminal.draw(|f| ui(f, app))?;
 
         if event::poll(Duration::from_millis(100))? {
             if app.event_state.get_confirm() == &Confirm::Confirmed {
                 app.event_state = EventState::default();
             }
 
-            if let Event::Key(key) = event::read()? {
-                let result = KeyParser::parse_event(key, &mut app)?;
+            let result = match event::read()? {
+                Event::Key(key) => KeyParser::parse_event(key, app),
+                Event::Mouse(mouse) => KeyParser::parse_mouse_event(mouse, app),
+                _ => Ok(None),
+            };
 
-                if let Some(key_parser_result) = result {
-                    return Ok(Some(key_parser_result));
-                }
+            // A failed action is reported in the status bar, the interface keeps running
+            match result {
+                Ok(Some(key_parser_result)) => return Ok(Some(key_parser_result)),
+                Ok(None) => {}
+                Err(e) => app.status.error(e),
             }
         }
     }
@@ -0,0 +1,323 @@
+use crate::core::hooks::{HookContext, PreHooks};
+use crate::core::process::{Limits, Process};
+use crate::core::settings::expand_home;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+use tui::widgets::TableState;
+
+/// A stored command run in several directories, at most `max_jobs` at the same time.
+pub struct FanOut {
+    pub tag: String,
+    pub command: String,
+    pub runs: Vec<Run>,
+    pub state: TableState,
+    /// Shows the output of the selected directory under the grid.
+    pub expanded: bool,
+    pub scroll: Option<u16>,
+    max_jobs: usize,
+    limits: Limits,
+    cancelled: bool,
+    started: Instant,
+    /// Set once the end of the fan-out has been reported by `tick`.
+    finished: bool,
+    /// Post-hooks to run once every directory has finished.
+    hooks: Option<HookContext>,
+    /// Result of the pre-hooks, run once on their own thread before any directory starts.
+    pre_hooks: Option<Receiver<Result<(), String>>>,
+}
+
+pub struct Run {
+    pub dir: PathBuf,
+    /// `None` until the run is started.
+    pub process: Option<Process>,
+}
+
+impl FanOut {
+    pub fn new(
+        command: &str,
+        tag: &str,
+        dirs: Vec<PathBuf>,
+        max_jobs: usize,
+        limits: Limits,
+    ) -> Self {
+        let mut state = TableState::default();
+        state.select(Some(0));
+
+        FanOut {
+            tag: tag.to_string(),
+            command: command.to_string(),
+            runs: dirs
+                .into_iter()
+                .map(|dir| Run { dir, process: None })
+                .collect(),
+            state,
+            expanded: false,
+            scroll: None,
+            max_jobs: max_jobs.max(1),
+            limits,
+            cancelled: false,
+            started: Instant::now(),
+            finished: false,
+            hooks: None,
+            pre_hooks: None,
+        }
+    }
+
+    pub fn with_hooks(mut self, context: HookContext, pre_hooks: PreHooks) -> Self {
+        let (sender, receiver) = channel();
+        thread::spawn(move || sender.send(pre_hooks.run()));
+
+        self.hooks = Some(context);
+        self.pre_hooks = Some(receiver);
+        self
+    }
+
+    pub fn get_hooks(&self) -> Option<&HookContext> {
+        self.hooks.as_ref()
+    }
+
+    /// Polls the running directories and starts pending ones while below the limit.
+    /// Returns true when the fan-out has just finished or been cancelled.
+    pub fn tick(&mut self) -> bool {
+        let mut running = 0;
+        for run in self.runs.iter_mut() {
+            if let Some(process) = run.process.as_mut() {
+                process.poll();
+                if process.is_running() {
+                    running += 1;
+                }
+            }
+        }
+
+        if !self.finished && !self.is_running() {
+            self.finished = true;
+            return true;
+        }
+
+        if self.cancelled || !self.has_pre_hooks_run() {
+            return false;
+        }
+
+        for run in self.runs.iter_mut().filter(|run| run.process.is_none()) {
+            if running >= self.max_jobs {
+                break;
+            }
+
+            // A directory failing to start is finished, with the error as its output
+            let tag = run.dir.display().to_string();
+            run.process = Some(
+                Process::spawn_in(&self.command, &tag, Some(&run.dir), None, self.limits)
+                    .unwrap_or_else(|e| Process::failed(&tag, &e.to_string(), 127)),
+            );
+            running += 1;
+        }
+
+        false
+    }
+
+    /// A failing pre-hook finishes every directory with its error, as exit 1.
+    fn has_pre_hooks_run(&mut self) -> bool {
+        let result = match self.pre_hooks.as_ref().map(Receiver::try_recv) {
+            None => return true,
+            Some(Err(TryRecvError::Empty)) => return false,
+            Some(Err(TryRecvError::Disconnected)) => Ok(()),
+            Some(Ok(result)) => result,
+        };
+        self.pre_hooks = None;
+
+        if let Err(error) = result {
+            for run in self.runs.iter_mut() {
+                let tag = run.dir.display().to_string();
+                run.process = Some(Process::failed(&tag, &error, 1));
+            }
+        }
+
+        true
+    }
+
+    /// 0 when every directory succeeded, else the first failure, `None` when killed or cancelled.
+    pub fn get_exit_code(&self) -> Option<i32> {
+        if self.cancelled {
+            return None;
+        }
+
+        self.runs
+            .iter()
+            .filter_map(|run| run.process.as_ref())
+            .map(|process| process.get_exit_code())
+            .find(|code| *code != Some(0))
+            .unwrap_or(Some(0))
+    }
+
+    pub fn get_duration(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    pub fn is_running(&self) -> bool {
+        !self.cancelled
+            && self.runs.iter().any(|run| {
+                run.process
+                    .as_ref()
+                    .is_none_or(|process| process.is_running())
+            })
+    }
+
+    /// Kills the running directories, pending ones are never started.
+    pub fn cancel(&mut self) {
+        self.cancelled = true;
+
+        for process in self.runs.iter_mut().filter_map(|run| run.process.as_mut()) {
+            process.kill();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled
+    }
+
+    pub fn current(&self) -> usize {
+        self.state.selected().unwrap_or_default()
+    }
+
+    pub fn next(&mut self) {
+        self.state
+            .select(Some((self.current() + 1) % self.runs.len()));
+        self.scroll = None;
+    }
+
+    pub fn previous(&mut self) {
+        self.state.select(Some(
+            (self.current() + self.runs.len() - 1) % self.runs.len(),
+        ));
+        self.scroll = None;
+    }
+}
+
+/// Resolves space separated directories, globs and `@name` directory sets,
+/// keeping the first occurrence of every existing directory.
+pub fn resolve_directories(
+    spec: &str,
+    dir_sets: &BTreeMap<String, Vec<String>>,
+) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut patterns = Vec::new();
+    for item in spec.split_whitespace() {
+        match item.strip_prefix('@') {
+            Some(name) => patterns.extend(
+                dir_sets
+                    .get(name)
+                    .ok_or_else(|| format!("Unknown directory set: {}", name))?
+                    .iter()
+                    .cloned(),
+            ),
+            None => patterns.push(item.to_string()),
+        }
+    }
+
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    for pattern in patterns {
+        let path = expand_home(&pattern);
+
+        let matches = if pattern.contains(['*', '?', '[']) {
+            glob::glob(&path.to_string_lossy())?
+                .filter_map(Result::ok)
+                .collect()
+        } else {
+            vec![path]
+        };
+
+        for dir in matches.into_iter().filter(|dir| dir.is_dir()) {
+            if !dirs.contains(&dir) {
+                dirs.push(dir);
+            }
+        }
+    }
+
+    Ok(dirs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::settings::HookSettings;
+    use std::fs;
+
+    #[test]
+    fn test_resolve_directories() {
+        let root = std::env::temp_dir().join(format!("cm-fanout-{}", std::process::id()));
+        for dir in ["repos/api", "repos/web", "other"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        fs::write(root.join("repos/README"), "").unwrap();
+
+        let root_path = root.display().to_string();
+        let dir_sets = BTreeMap::from([(
+            "all".to_string(),
+            vec![
+                format!("{}/repos/*", root_path),
+                format!("{}/other", root_path),
+            ],
+        )]);
+
+        let dirs = resolve_directories(
+            &format!("{}/repos/web @all {}/missing", root_path, root_path),
+            &dir_sets,
+        )
+        .unwrap();
+
+        assert_eq!(
+            dirs,
+            vec![
+                root.join("repos/web"),
+                root.join("repos/api"),
+                root.join("other")
+            ]
+        );
+        assert!(resolve_directories("@unknown", &dir_sets).is_err());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    /// Ticks until the fan-out reports its end.
+    fn wait(fanout: &mut FanOut) {
+        let started = Instant::now();
+        while !fanout.tick() {
+            assert!(started.elapsed() < Duration::from_secs(5));
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_spawn_error_finishes() {
+        let dirs = vec![PathBuf::from("/nonexistent/cm-fanout")];
+        let mut fanout = FanOut::new("true", "t", dirs, 2, Limits::default());
+
+        wait(&mut fanout);
+        assert!(!fanout.is_running());
+        assert_eq!(fanout.get_exit_code(), Some(127));
+        assert!(!fanout.runs[0].process.as_ref().unwrap().lines.is_empty());
+    }
+
+    #[test]
+    fn test_pre_hook_failure_finishes() {
+        let settings = HookSettings {
+            pre: vec!["false".to_string()],
+            ..HookSettings::default()
+        };
+        let context = HookContext::new("t", "true", None);
+        let dirs = vec![std::env::temp_dir(), std::env::temp_dir()];
+        let mut fanout = FanOut::new("true", "t", dirs, 2, Limits::default())
+            .with_hooks(context.clone(), PreHooks::new(&settings, &context));
+
+        wait(&mut fanout);
+        assert_eq!(fanout.get_exit_code(), Some(1));
+        for run in &fanout.runs {
+            assert!(run.process.as_ref().unwrap().lines[0]
+                .1
+                .starts_with("Pre-hook failed"));
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use crate::core::settings::HookSettings;
+use std::error::Error;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+/// The run a hook is called for, given to it as `CM_TAG`, `CM_COMMAND` and `CM_NAMESPACE`.
+#[derive(Clone)]
+pub struct HookContext {
+    pub tag: String,
+    pub command: String,
+    pub namespace: Option<String>,
+}
+
+impl HookContext {
+    pub fn new(tag: &str, command: &str, namespace: Option<&str>) -> Self {
+        HookContext {
+            tag: tag.to_string(),
+            command: command.to_string(),
+            namespace: namespace.map(str::to_string),
+        }
+    }
+
+    /// Global hooks first, then the namespace and the command ones.
+    fn get_pre<'a>(&self, settings: &'a HookSettings) -> Vec<&'a String> {
+        let mut hooks = settings.pre.iter().collect::<Vec<_>>();
+
+        if let Some(namespace) = self
+            .namespace
+            .as_ref()
+            .and_then(|n| settings.namespaces.get(n))
+        {
+            hooks.extend(&namespace.pre);
+        }
+        if let Some(command) = settings.commands.get(&self.tag) {
+            hooks.extend(&command.pre);
+        }
+
+        hooks
+    }
+
+    /// Command hooks first, then the namespace and the global ones.
+    fn get_post<'a>(&self, settings: &'a HookSettings) -> Vec<&'a String> {
+        let mut hooks = Vec::new();
+
+        if let Some(command) = settings.commands.get(&self.tag) {
+            hooks.extend(&command.post);
+        }
+        if let Some(namespace) = self
+            .namespace
+            .as_ref()
+            .and_then(|n| settings.namespaces.get(n))
+        {
+            hooks.extend(&namespace.post);
+        }
+        hooks.extend(&settings.post);
+
+        hooks
+    }
+
+    fn create_command(&self, hook: &str, kind: &str) -> Command {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", hook])
+            .env("CM_HOOK", kind)
+            .env("CM_TAG", &self.tag)
+            .env("CM_COMMAND", &self.command)
+            .env(
+                "CM_NAMESPACE",
+                self.namespace.as_deref().unwrap_or_default(),
+            );
+
+        cmd
+    }
+}
+
+/// Runs the pre-hooks in order, the first failing one aborts the run.
+/// Without `terminal`, the hooks run without input and their outputs are discarded.
+pub fn run_pre(
+    settings: &HookSettings,
+    context: &HookContext,
+    terminal: bool,
+) -> Result<(), Box<dyn Error>> {
+    PreHooks::new(settings, context)
+        .run_with(terminal)
+        .map_err(Into::into)
+}
+
+/// Pre-hooks of a run started in the interface, run on the thread of the command before it
+/// starts so a slow hook doesn't hold the interface.
+#[derive(Clone)]
+pub struct PreHooks {
+    context: HookContext,
+    hooks: Vec<String>,
+}
+
+impl PreHooks {
+    pub fn new(settings: &HookSettings, context: &HookContext) -> Self {
+        PreHooks {
+            context: context.clone(),
+            hooks: context.get_pre(settings).into_iter().cloned().collect(),
+        }
+    }
+
+    /// Runs them without input and outputs.
+    pub fn run(&self) -> Result<(), String> {
+        self.run_with(false)
+    }
+
+    fn run_with(&self, terminal: bool) -> Result<(), String> {
+        for hook in &self.hooks {
+            let mut cmd = self.context.create_command(hook, "pre");
+
+            if !terminal {
+                cmd.stdin(Stdio::null())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null());
+            }
+
+            let status = cmd.status().map_err(|e| e.to_string())?;
+            if !status.success() {
+                return Err(format!(
+                    "Pre-hook failed with {}, {} not run: {}",
+                    status
+                        .code()
+                        .map_or("a signal".to_string(), |code| format!("exit {}", code)),
+                    self.context.tag,
+                    hook
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Runs the post-hooks in order with `CM_EXIT_CODE` and `CM_DURATION` in seconds.
+/// In the terminal a failure is reported, otherwise they run in the background without outputs.
+pub fn run_post(
+    settings: &HookSettings,
+    context: &HookContext,
+    exit_code: Option<i32>,
+    duration: Duration,
+    terminal: bool,
+) {
+    let cmds = context
+        .get_post(settings)
+        .into_iter()
+        .map(|hook| {
+            let mut cmd = context.create_command(hook, "post");
+            cmd.env(
+                "CM_EXIT_CODE",
+                exit_code.map_or(String::new(), |code| code.to_string()),
+            )
+            .env("CM_DURATION", duration.as_secs().to_string());
+
+            (hook.clone(), cmd)
+        })
+        .collect::<Vec<_>>();
+
+    if !terminal {
+        thread::spawn(move || {
+            for (_, mut cmd) in cmds {
+                let _ = cmd
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .status();
+            }
+        });
+        return;
+    }
+
+    for (hook, mut cmd) in cmds {
+        match cmd.status() {
+            Ok(status) if status.success() => {}
+            Ok(_) | Err(_) => eprintln!("Post-hook failed: {}", hook),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::settings::Hooks;
+
+    #[test]
+    fn test_order() {
+        let hooks = |name: &str| Hooks {
+            pre: vec![format!("{} pre", name)],
+            post: vec![format!("{} post", name)],
+        };
+        let settings = HookSettings {
+            pre: vec!["global pre".to_string()],
+            post: vec!["global post".to_string()],
+            namespaces: [("aws".to_string(), hooks("aws"))].into(),
+            commands: [("aws:ls".to_string(), hooks("command"))].into(),
+        };
+
+        let context = HookContext::new("aws:ls", "aws s3 ls", Some("aws"));
+        assert_eq!(
+            context.get_pre(&settings),
+            ["global pre", "aws pre", "command pre"]
+        );
+        assert_eq!(
+            context.get_post(&settings),
+            ["command post", "aws post", "global post"]
+        );
+
+        let context = HookContext::new("build", "cargo build", Some("rust"));
+        assert_eq!(context.get_pre(&settings), ["global pre"]);
+    }
+}
@@ -1,41 +1,111 @@
-use crate::core::cmd::Cmd;
+use crate::core::hooks::HookContext;
+use crate::core::runner::RunOptions;
 use crate::App;
+use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
 use crossterm::execute;
 use crossterm::terminal::{
     disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
 };
+use std::collections::BTreeMap;
 use std::error::Error;
 use std::io;
 use tui::backend::CrosstermBackend;
 use tui::Terminal;
 
-mod cmd;
+pub mod clipboard;
+pub mod cmd;
 pub mod config;
 mod engine;
-mod parser;
+pub mod fanout;
+pub mod hooks;
+pub mod params;
+pub mod parser;
+pub mod preview;
+pub mod process;
+pub mod risk;
+pub mod runner;
+pub mod schedule;
+pub mod settings;
+pub mod watch;
+pub mod workflow;
 
 pub struct Engine;
 
 impl Engine {
-    pub fn run(app: App) -> Result<(), Box<dyn Error>> {
+    pub fn run(mut app: App) -> Result<(), Box<dyn Error>> {
         // setup terminal
         enable_raw_mode()?;
         let mut stdout = io::stdout();
         execute!(stdout, EnterAlternateScreen)?;
+        let mouse = app.settings.mouse.enabled;
+        if mouse {
+            execute!(stdout, EnableMouseCapture)?;
+        }
 
         let backend = CrosstermBackend::new(stdout);
         let mut terminal = Terminal::new(backend)?;
 
-        let result = engine::run_app(&mut terminal, app);
+        let result = engine::run_app(&mut terminal, &mut app);
 
         // restore terminal
         disable_raw_mode()?;
         execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+        if mouse {
+            execute!(terminal.backend_mut(), DisableMouseCapture)?;
+        }
         terminal.show_cursor()?;
 
+        let workflow = app.workflow.take();
+        let sequence = app.sequence.take();
+        let settings = std::mem::take(&mut app.settings);
+        let options = match &result {
+            Ok(Some((_, tag))) => app.get_run_options(tag),
+            _ => RunOptions::default(),
+        };
+        let namespace = app.get_current_namespace_name();
+        let db = app.into_db();
+
+        if let Some(sequence) = sequence {
+            if let Err(e) = sequence.run_unsaved(&db, &settings) {
+                eprintln!("{}", e);
+            }
+
+            return Ok(());
+        }
+
         match &result {
-            Ok(Some((cmd_line, _tag))) => {
-                Cmd::create_and_run(cmd_line)?;
+            Ok(Some((cmd_line, tag))) => {
+                match workflow.and_then(|tag| db.get_workflow(&tag).transpose()) {
+                    Some(workflow) => {
+                        // Parameters come from the environment or the defaults
+                        if let Err(e) =
+                            workflow.and_then(|w| w.run(&db, &BTreeMap::new(), &settings))
+                        {
+                            eprintln!("{}", e);
+                        }
+                    }
+                    None => match params::substitute(cmd_line, &BTreeMap::new()) {
+                        Ok(cmd_line) => {
+                            let context = HookContext::new(tag, &cmd_line, namespace.as_deref());
+                            match runner::run(&context, &options, &settings) {
+                                Ok(outcome) => {
+                                    let result = outcome.to_step_result(tag);
+                                    if let Err(e) = db.add_history(&result, &cmd_line, None) {
+                                        eprintln!("{}", e);
+                                    }
+                                    runner::notify(
+                                        &settings.notify,
+                                        tag,
+                                        outcome.exit_code,
+                                        outcome.duration,
+                                    );
+                                }
+                                Err(e) => eprintln!("{}", e),
+                            }
+                        }
+                        Err(e) => eprintln!("{}", e),
+                    },
+                }
             }
             Err(e) => {
                 eprintln!("{}", e);
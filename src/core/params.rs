@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+
+/// Where the value of a parameter comes from.
+pub enum Source {
+    Argument,
+    Environment(String),
+    Default,
+}
+
+pub struct Param {
+    pub name: String,
+    pub value: String,
+    pub source: Source,
+}
+
+/// Replaces `{{name}}` and `{{name:default}}` placeholders. A value is taken from `params`,
+/// then from the environment variable of the same name or its uppercase, then from the default.
+pub fn substitute(
+    command: &str,
+    params: &BTreeMap<String, String>,
+) -> Result<String, Box<dyn Error>> {
+    Ok(resolve(command, params)?.0)
+}
+
+/// Substitutes the placeholders and returns the parameters used, in their order.
+pub fn resolve(
+    command: &str,
+    params: &BTreeMap<String, String>,
+) -> Result<(String, Vec<Param>), Box<dyn Error>> {
+    let mut result = String::new();
+    let mut used: Vec<Param> = Vec::new();
+    let mut rest = command;
+
+    while let Some((start, end, name, default)) = find_placeholder(rest) {
+        let param = get_param(name, default, params)?;
+        result.push_str(&rest[..start]);
+        result.push_str(&param.value);
+        rest = &rest[end + 2..];
+
+        if !used.iter().any(|p| p.name == param.name) {
+            used.push(param);
+        }
+    }
+
+    result.push_str(rest);
+
+    Ok((result, used))
+}
+
+/// Names of the placeholders in their order, with their default.
+pub fn get_placeholders(command: &str) -> Vec<(String, Option<String>)> {
+    let mut placeholders: Vec<(String, Option<String>)> = Vec::new();
+    let mut rest = command;
+
+    while let Some((_, end, name, default)) = find_placeholder(rest) {
+        if !placeholders.iter().any(|(used, _)| used == name) {
+            placeholders.push((name.to_string(), default.map(str::to_string)));
+        }
+        rest = &rest[end + 2..];
+    }
+
+    placeholders
+}
+
+/// Start and end of the next placeholder, with its name and default.
+fn find_placeholder(text: &str) -> Option<(usize, usize, &str, Option<&str>)> {
+    let start = text.find("{{")?;
+    let end = start + text[start..].find("}}")?;
+
+    let placeholder = text[start + 2..end].trim();
+    let (name, default) = match placeholder.split_once(':') {
+        Some((name, default)) => (name.trim_end(), Some(default)),
+        None => (placeholder, None),
+    };
+
+    Some((start, end, name, default))
+}
+
+fn get_param(
+    name: &str,
+    default: Option<&str>,
+    params: &BTreeMap<String, String>,
+) -> Result<Param, Box<dyn Error>> {
+    let (value, source) = if let Some(value) = params.get(name) {
+        (value.clone(), Source::Argument)
+    } else if let Some((var, value)) = [name.to_string(), name.to_uppercase()]
+        .into_iter()
+        .find_map(|var| std::env::var(&var).ok().map(|value| (var, value)))
+    {
+        (value, Source::Environment(var))
+    } else if let Some(default) = default {
+        (default.to_string(), Source::Default)
+    } else {
+        return Err(format!("Missing parameter: {}", name).into());
+    };
+
+    Ok(Param {
+        name: name.to_string(),
+        value,
+        source,
+    })
+}
+
+/// Parses `key=value` arguments.
+pub fn parse(args: &[String]) -> Result<BTreeMap<String, String>, Box<dyn Error>> {
+    args.iter()
+        .map(|arg| match arg.split_once('=') {
+            Some((key, value)) if !key.is_empty() => Ok((key.to_string(), value.to_string())),
+            _ => Err(format!("Invalid parameter, expected key=value: {}", arg).into()),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_substitute() {
+        let params = parse(&["env=prod".to_string()]).unwrap();
+
+        assert_eq!(
+            substitute("deploy {{env}} --tag {{ version:latest }}", &params).unwrap(),
+            "deploy prod --tag latest"
+        );
+        assert_eq!(
+            substitute("echo {{ unclosed", &params).unwrap(),
+            "echo {{ unclosed"
+        );
+        assert!(substitute("echo {{cm_test_missing}}", &params).is_err());
+        assert!(parse(&["=value".to_string()]).is_err());
+    }
+}
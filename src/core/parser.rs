@@ -1,316 +1,1191 @@
 use crate::app::app::App;
 use crate::app::event_state::{Confirm, EventState, EventType, Mode, SubMode, Tab};
-use crate::app::state::{State, StatefulList};
-use crossterm::event::{KeyCode, KeyEvent};
+use crate::app::picker::PickAction;
+use crate::app::state::{contains, Jump, State, StatefulList};
+use crate::app::undo::{Change, Item};
+use crate::core::risk::Risk;
+use crate::core::workflow::HistoryEntry;
+use crate::db::namespace::{Favorite, Namespace, TrashEntry};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use std::cell::RefCell;
 use std::error::Error;
 use std::rc::Rc;
+use std::time::Instant;
 
 pub struct KeyParser;
 
 pub type ParserResult = Result<Option<(String, String)>, Box<dyn Error>>;
 
-impl KeyParser {
-    pub fn parse_event(key_event: KeyEvent, app: &mut App) -> ParserResult {
-        KeyParser::process_key_code(key_event.code, app)
-    }
+/// Lines scrolled in the details and output panes by a turn of the wheel, or by Ctrl-D and
+/// Ctrl-U in the details.
+const SCROLL_LINES: i32 = 3;
+/// Milliseconds between two clicks on the same cell making a double-click.
+const DOUBLE_CLICK_MS: u128 = 400;
+
+/// A key of the dispatch tables, also listed in the help and the hint bar while enabled.
+pub struct Binding {
+    pub keys: &'static [KeyCode],
+    pub description: &'static str,
+    /// Short name shown in the hint bar.
+    pub hint: Option<&'static str>,
+    /// Ctrl or Alt pressed with the keys.
+    pub modifiers: KeyModifiers,
+    enabled: fn(&App) -> bool,
+    action: Action,
+}
 
-    fn quit(app: &mut App) -> ParserResult {
-        app.quit = true;
-        Ok(None)
-    }
+enum Action {
+    App(fn(&mut App) -> ParserResult),
+    /// For keys doing the same on a different target, like number keys.
+    Key(fn(KeyCode, &mut App) -> ParserResult),
+}
 
-    fn process_key_code(key_code: KeyCode, app: &mut App) -> ParserResult {
-        if key_code == KeyCode::Char('q')
-            && (app.event_state.get_mode() == &Mode::Normal
-                || app.event_state.get_mode() == &Mode::Delete)
-        {
-            KeyParser::quit(app)?;
-            return Ok(None);
+impl Binding {
+    const fn new(
+        keys: &'static [KeyCode],
+        description: &'static str,
+        enabled: fn(&App) -> bool,
+        action: fn(&mut App) -> ParserResult,
+    ) -> Self {
+        Binding {
+            keys,
+            description,
+            hint: None,
+            modifiers: KeyModifiers::NONE,
+            enabled,
+            action: Action::App(action),
         }
+    }
 
-        if key_code == KeyCode::Esc {
-            app.event_state = EventState::default();
-            return Ok(None);
+    const fn with_key(
+        keys: &'static [KeyCode],
+        description: &'static str,
+        enabled: fn(&App) -> bool,
+        action: fn(KeyCode, &mut App) -> ParserResult,
+    ) -> Self {
+        Binding {
+            keys,
+            description,
+            hint: None,
+            modifiers: KeyModifiers::NONE,
+            enabled,
+            action: Action::Key(action),
         }
+    }
 
-        match app.event_state.get_tab() {
-            Tab::Tab1 => KeyParser::process_tab_1(key_code, app),
-        }
+    const fn with_hint(mut self, hint: &'static str) -> Self {
+        self.hint = Some(hint);
+        self
     }
 
-    fn process_tab_1(key_code: KeyCode, app: &mut App) -> ParserResult {
-        match app.event_state.get_mode() {
-            Mode::Normal => KeyParser::process_normal_mode(key_code, app),
-            Mode::Add => KeyParser::process_add_mode(key_code, app),
-            Mode::Delete => KeyParser::process_delete_mode(key_code, app),
+    const fn with_ctrl(mut self) -> Self {
+        self.modifiers = KeyModifiers::CONTROL;
+        self
+    }
+
+    const fn with_alt(mut self) -> Self {
+        self.modifiers = KeyModifiers::ALT;
+        self
+    }
+
+    /// Keys as shown to the user, e.g. `j/↓`.
+    pub fn get_label(&self) -> String {
+        self.keys
+            .iter()
+            .map(|key| self.get_name(key))
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    fn get_name(&self, key: &KeyCode) -> String {
+        match self.modifiers {
+            KeyModifiers::CONTROL => {
+                format!("Ctrl-{}", Binding::get_key_name(key).to_uppercase())
+            }
+            KeyModifiers::ALT => format!("Alt-{}", Binding::get_key_name(key)),
+            _ => Binding::get_key_name(key),
         }
     }
 
-    fn process_normal_mode(key_code: KeyCode, app: &mut App) -> ParserResult {
-        match key_code {
-            KeyCode::Right | KeyCode::Char('l') => KeyParser::move_right(app),
-            KeyCode::Left | KeyCode::Char('h') => KeyParser::move_left(app),
-            KeyCode::Down | KeyCode::Char('j') => KeyParser::move_down(app),
-            KeyCode::Up | KeyCode::Char('k') => KeyParser::move_up(app),
-            KeyCode::Enter | KeyCode::Char(' ') => KeyParser::enter(app),
-            KeyCode::Char('n') => KeyParser::change_to_add_namespace_mode(app),
-            KeyCode::Char('c') => KeyParser::change_to_add_command_mode(app),
-            KeyCode::Char('d') => KeyParser::change_to_delete_mode(app),
-            _ => Ok(None),
+    fn get_key_name(key: &KeyCode) -> String {
+        match key {
+            KeyCode::Char(' ') => "Space".to_string(),
+            KeyCode::Char(c) => c.to_string(),
+            KeyCode::Enter => "Enter".to_string(),
+            KeyCode::Esc => "Esc".to_string(),
+            KeyCode::Backspace => "Backspace".to_string(),
+            KeyCode::Tab => "Tab".to_string(),
+            KeyCode::BackTab => "Shift-Tab".to_string(),
+            KeyCode::Left => "←".to_string(),
+            KeyCode::Right => "→".to_string(),
+            KeyCode::Up => "↑".to_string(),
+            KeyCode::Down => "↓".to_string(),
+            other => format!("{:?}", other),
         }
     }
+}
 
-    fn process_add_mode(key_code: KeyCode, app: &mut App) -> ParserResult {
-        match app.event_state.get_sub_mode() {
-            SubMode::Namespace => match app.event_state.get_confirm() {
-                Confirm::Hide => {
-                    KeyParser::input_handler(
-                        key_code,
-                        app,
-                        app.config.name_config.namespace.to_string(),
-                    );
-                    Ok(None)
-                }
-                Confirm::Display => KeyParser::process_add_namespace_mode_confirm(key_code, app),
-                _ => Ok(None),
-            },
-            SubMode::Command => match app.event_state.get_event_type() {
-                EventType::Command => {
-                    match app.event_state.get_confirm() {
-                        Confirm::Display => {
-                            KeyParser::input_handler(
-                                key_code,
-                                app,
-                                app.config.name_config.tag.to_string(),
-                            );
-                        }
-                        _ => {
-                            KeyParser::input_handler(
-                                key_code,
-                                app,
-                                app.config.name_config.command.to_string(),
-                            );
-                        }
-                    }
+const LEFT: &[KeyCode] = &[KeyCode::Char('h'), KeyCode::Left];
+const RIGHT: &[KeyCode] = &[KeyCode::Char('l'), KeyCode::Right];
+const DOWN: &[KeyCode] = &[KeyCode::Char('j'), KeyCode::Down];
+const UP: &[KeyCode] = &[KeyCode::Char('k'), KeyCode::Up];
+const CONFIRM: &[KeyCode] = &[KeyCode::Enter, KeyCode::Char(' ')];
+
+/// Keys of every tab and mode, looked up before the current table.
+const GLOBAL_BINDINGS: &[Binding] = &[
+    Binding::new(
+        &[KeyCode::Char('?')],
+        "Show this help",
+        KeyParser::is_not_typing,
+        KeyParser::show_help,
+    )
+    .with_hint("help"),
+    Binding::with_key(
+        &[
+            KeyCode::Char('1'),
+            KeyCode::Char('2'),
+            KeyCode::Char('3'),
+            KeyCode::Char('4'),
+            KeyCode::Char('5'),
+            KeyCode::Char('6'),
+            KeyCode::Char('7'),
+            KeyCode::Char('8'),
+        ],
+        "Go to the tab of that position",
+        KeyParser::is_idle,
+        KeyParser::go_to_tab,
+    ),
+    Binding::new(
+        &[KeyCode::Tab],
+        "Next tab",
+        KeyParser::is_idle,
+        KeyParser::next_tab,
+    ),
+    Binding::new(
+        &[KeyCode::BackTab],
+        "Previous tab",
+        KeyParser::is_idle,
+        KeyParser::previous_tab,
+    ),
+    Binding::new(
+        &[KeyCode::Esc],
+        "Cancel",
+        KeyParser::is_in_popup,
+        KeyParser::cancel,
+    )
+    .with_hint("cancel"),
+    Binding::new(
+        &[KeyCode::Esc],
+        "Close the finished output, stop watching and dismiss messages",
+        KeyParser::is_not_in_popup,
+        KeyParser::cancel,
+    ),
+    Binding::new(
+        &[KeyCode::Char('u')],
+        "Undo the last change of the library",
+        KeyParser::can_undo,
+        KeyParser::undo,
+    )
+    .with_hint("undo"),
+    Binding::new(
+        &[KeyCode::Char('r')],
+        "Redo the last change undone",
+        KeyParser::can_redo,
+        KeyParser::redo,
+    )
+    .with_ctrl(),
+    Binding::new(
+        &[KeyCode::PageDown],
+        "Next page of the list",
+        KeyParser::can_jump,
+        KeyParser::page_down,
+    ),
+    Binding::new(
+        &[KeyCode::PageUp],
+        "Previous page of the list",
+        KeyParser::can_jump,
+        KeyParser::page_up,
+    ),
+    Binding::new(
+        &[KeyCode::Home],
+        "First item of the list",
+        KeyParser::can_jump,
+        KeyParser::jump_to_first,
+    ),
+    Binding::with_key(
+        &[KeyCode::Char('g')],
+        "First item of the list, with g twice",
+        KeyParser::can_jump,
+        KeyParser::wait_for_key,
+    ),
+    Binding::new(
+        &[KeyCode::End, KeyCode::Char('G')],
+        "Last item of the list",
+        KeyParser::can_jump,
+        KeyParser::jump_to_last,
+    ),
+    Binding::with_key(
+        &[KeyCode::Char('t')],
+        "Next item starting with the letter typed after",
+        KeyParser::can_jump_to_letter,
+        KeyParser::wait_for_key,
+    ),
+    Binding::new(
+        &[KeyCode::Char('q')],
+        "Quit",
+        KeyParser::can_quit,
+        KeyParser::quit,
+    ),
+];
+
+const NORMAL_BINDINGS: &[Binding] = &[
+    Binding::new(
+        RIGHT,
+        "Next tab",
+        KeyParser::is_on_tabs,
+        KeyParser::next_tab,
+    ),
+    Binding::new(
+        LEFT,
+        "Previous tab",
+        KeyParser::is_on_tabs,
+        KeyParser::previous_tab,
+    ),
+    Binding::new(
+        DOWN,
+        "Select the namespaces",
+        KeyParser::is_on_tabs,
+        KeyParser::move_down,
+    ),
+    Binding::new(
+        DOWN,
+        "Next namespace",
+        KeyParser::is_in_namespaces,
+        KeyParser::move_down,
+    ),
+    Binding::new(
+        UP,
+        "Previous namespace",
+        KeyParser::is_in_namespaces,
+        KeyParser::move_up,
+    ),
+    Binding::new(
+        &[KeyCode::Char('l'), KeyCode::Right, KeyCode::Enter],
+        "Open the commands of the namespace",
+        KeyParser::is_in_namespaces,
+        KeyParser::move_right,
+    )
+    .with_hint("open"),
+    Binding::new(
+        LEFT,
+        "Back to the tabs",
+        KeyParser::is_in_namespaces,
+        KeyParser::move_left,
+    ),
+    Binding::new(
+        DOWN,
+        "Next command",
+        KeyParser::is_in_commands,
+        KeyParser::move_down,
+    ),
+    Binding::new(
+        UP,
+        "Previous command",
+        KeyParser::is_in_commands,
+        KeyParser::move_up,
+    ),
+    Binding::new(
+        LEFT,
+        "Back to the namespaces",
+        KeyParser::is_in_commands,
+        KeyParser::move_left,
+    ),
+    Binding::new(
+        &[KeyCode::Char(' ')],
+        "Mark the item for a bulk action, or remove its mark",
+        KeyParser::can_mark,
+        KeyParser::toggle_mark,
+    )
+    .with_hint("mark"),
+    Binding::new(
+        CONFIRM,
+        "Run the command once cm exits, after confirmation",
+        KeyParser::is_in_commands,
+        KeyParser::enter,
+    )
+    .with_hint("run"),
+    Binding::new(
+        &[KeyCode::Char('V')],
+        "Mark the items from the last marked one",
+        KeyParser::can_mark,
+        KeyParser::mark_range,
+    ),
+    Binding::new(
+        &[KeyCode::Char('a')],
+        "Mark every item, or clear the marks",
+        KeyParser::can_mark,
+        KeyParser::toggle_all_marks,
+    ),
+    Binding::new(
+        &[KeyCode::Char('S')],
+        "Run the marked commands one after the other once cm exits",
+        KeyParser::has_runnable_command,
+        KeyParser::change_to_sequence_mode,
+    ),
+    Binding::new(
+        &[KeyCode::Char('e')],
+        "Export the marked commands or namespaces to commands.md, unless it exists",
+        KeyParser::can_mark,
+        KeyParser::export_marked,
+    ),
+    Binding::new(
+        &[KeyCode::Char('T')],
+        "Add a prefix to the tags of the marked commands",
+        KeyParser::can_change_command,
+        KeyParser::change_to_prefix_mode,
+    ),
+    Binding::with_key(
+        &[
+            KeyCode::Char('1'),
+            KeyCode::Char('2'),
+            KeyCode::Char('3'),
+            KeyCode::Char('4'),
+            KeyCode::Char('5'),
+            KeyCode::Char('6'),
+            KeyCode::Char('7'),
+            KeyCode::Char('8'),
+            KeyCode::Char('9'),
+        ],
+        "Run the favorite of that position, number keys alone switch tabs",
+        KeyParser::can_run_favorite,
+        KeyParser::run_favorite,
+    )
+    .with_alt(),
+    Binding::new(
+        &[KeyCode::Char('s')],
+        "Star the command, or remove its star",
+        KeyParser::can_change_command,
+        KeyParser::toggle_favorite,
+    )
+    .with_hint("star"),
+    Binding::new(
+        &[KeyCode::Char('m')],
+        "Move the command to another namespace",
+        KeyParser::can_change_command,
+        KeyParser::move_command,
+    )
+    .with_hint("move"),
+    Binding::new(
+        &[KeyCode::Char('D')],
+        "Duplicate the command into a namespace",
+        KeyParser::can_change_command,
+        KeyParser::duplicate_command,
+    ),
+    Binding::new(
+        &[KeyCode::Char('m')],
+        "Merge the namespace into another one",
+        KeyParser::can_reorganize_namespace,
+        KeyParser::merge_namespace,
+    )
+    .with_hint("merge"),
+    Binding::new(
+        &[KeyCode::Char('R')],
+        "Rename the namespace",
+        KeyParser::can_reorganize_namespace,
+        KeyParser::change_to_rename_mode,
+    )
+    .with_hint("rename"),
+    Binding::new(
+        &[KeyCode::Char('y')],
+        "Copy the command to the clipboard",
+        KeyParser::has_runnable_command,
+        KeyParser::copy_command,
+    )
+    .with_hint("yank"),
+    Binding::new(
+        &[KeyCode::Char('Y')],
+        "Copy the command with its placeholders replaced",
+        KeyParser::has_runnable_command,
+        KeyParser::copy_substituted_command,
+    ),
+    Binding::new(
+        &[KeyCode::Char('r')],
+        "Run in the output pane",
+        KeyParser::has_runnable_command,
+        KeyParser::run_in_output,
+    )
+    .with_hint("output"),
+    Binding::new(
+        &[KeyCode::Char('b')],
+        "Run as a background job",
+        KeyParser::has_runnable_command,
+        KeyParser::start_job,
+    )
+    .with_hint("job"),
+    Binding::new(
+        &[KeyCode::Char('w')],
+        "Run in the output pane again when files change, or stop watching",
+        KeyParser::can_watch,
+        KeyParser::toggle_watch,
+    )
+    .with_hint("watch"),
+    Binding::new(
+        &[KeyCode::Char('o')],
+        "Run in several directories",
+        KeyParser::has_runnable_command,
+        KeyParser::change_to_fanout_mode,
+    )
+    .with_hint("fan-out"),
+    Binding::new(
+        &[KeyCode::Char('x')],
+        "Kill the command of the output pane",
+        KeyParser::has_output,
+        KeyParser::stop_output,
+    ),
+    Binding::new(
+        &[KeyCode::Char('d')],
+        "Scroll the command details down",
+        KeyParser::can_scroll_details,
+        KeyParser::scroll_details_down,
+    )
+    .with_ctrl(),
+    Binding::new(
+        &[KeyCode::Char('u')],
+        "Scroll the command details up",
+        KeyParser::can_scroll_details,
+        KeyParser::scroll_details_up,
+    )
+    .with_ctrl(),
+    Binding::new(
+        &[KeyCode::Char('K')],
+        "Scroll the output back",
+        KeyParser::has_output,
+        KeyParser::scroll_output_back,
+    ),
+    Binding::new(
+        &[KeyCode::Char('J')],
+        "Scroll the output forward",
+        KeyParser::has_output,
+        KeyParser::scroll_output_forward,
+    ),
+    Binding::new(
+        &[KeyCode::Char('n')],
+        "Add a namespace",
+        KeyParser::always,
+        KeyParser::change_to_add_namespace_mode,
+    ),
+    Binding::new(
+        &[KeyCode::Char('c')],
+        "Add a command to the namespace",
+        KeyParser::can_edit,
+        KeyParser::change_to_add_command_mode,
+    )
+    .with_hint("add"),
+    Binding::new(
+        &[KeyCode::Char('d')],
+        "Delete the selected command or namespace",
+        KeyParser::can_edit,
+        KeyParser::change_to_delete_mode,
+    )
+    .with_hint("delete"),
+    Binding::new(
+        &[KeyCode::Char('f')],
+        "Copy into your library",
+        KeyParser::can_fork,
+        KeyParser::change_to_fork_mode,
+    )
+    .with_hint("copy"),
+    Binding::new(
+        &[KeyCode::Char('p')],
+        "Switch profile",
+        KeyParser::has_profiles,
+        KeyParser::switch_profile,
+    ),
+];
+
+const DELETE_BINDINGS: &[Binding] = &[Binding::new(
+    CONFIRM,
+    "Confirm the deletion",
+    KeyParser::always,
+    KeyParser::confirm_delete,
+)
+.with_hint("confirm")];
+
+const SEQUENCE_BINDINGS: &[Binding] = &[Binding::new(
+    CONFIRM,
+    "Run the marked commands once cm exits",
+    KeyParser::always,
+    KeyParser::confirm_sequence,
+)
+.with_hint("confirm")];
+
+const FORK_BINDINGS: &[Binding] = &[Binding::new(
+    CONFIRM,
+    "Copy into your library",
+    KeyParser::always,
+    KeyParser::confirm_fork,
+)
+.with_hint("confirm")];
+
+const PICK_BINDINGS: &[Binding] = &[
+    Binding::new(
+        DOWN,
+        "Next namespace",
+        KeyParser::always,
+        KeyParser::next_picked,
+    ),
+    Binding::new(
+        UP,
+        "Previous namespace",
+        KeyParser::always,
+        KeyParser::previous_picked,
+    ),
+    Binding::new(
+        CONFIRM,
+        "Pick the namespace",
+        KeyParser::always,
+        KeyParser::confirm_pick,
+    )
+    .with_hint("pick"),
+];
+
+/// Shared by the modes typing into an input.
+const ERASE: Binding = Binding::new(
+    &[KeyCode::Backspace],
+    "Erase the last character",
+    KeyParser::always,
+    KeyParser::erase_input,
+);
+
+/// Other characters are typed into the input.
+const ADD_BINDINGS: &[Binding] = &[
+    Binding::new(
+        &[KeyCode::Enter],
+        "Confirm",
+        KeyParser::always,
+        KeyParser::submit_input,
+    )
+    .with_hint("confirm"),
+    ERASE,
+];
+
+/// Other keys close the confirmation of a command without adding it.
+const ADD_CONFIRM_BINDINGS: &[Binding] = &[Binding::new(
+    CONFIRM,
+    "Add to the library",
+    KeyParser::always,
+    KeyParser::confirm_add,
+)
+.with_hint("add")];
+
+/// Other characters are typed into the input.
+const FANOUT_INPUT_BINDINGS: &[Binding] = &[
+    Binding::new(
+        &[KeyCode::Enter],
+        "Run in the directories",
+        KeyParser::always,
+        KeyParser::confirm_fanout,
+    )
+    .with_hint("run"),
+    ERASE,
+];
+
+/// Other characters are typed into the input.
+const TYPE_TAG_BINDINGS: &[Binding] = &[
+    Binding::new(
+        &[KeyCode::Enter],
+        "Run once the tag is typed",
+        KeyParser::always,
+        KeyParser::confirm_typed_tag,
+    )
+    .with_hint("run"),
+    ERASE,
+];
+
+const JOBS_BINDINGS: &[Binding] = &[
+    Binding::new(
+        RIGHT,
+        "Next tab",
+        KeyParser::is_on_tabs_of_jobs,
+        KeyParser::next_tab,
+    ),
+    Binding::new(
+        LEFT,
+        "Previous tab",
+        KeyParser::is_on_tabs_of_jobs,
+        KeyParser::previous_tab,
+    ),
+    Binding::new(
+        DOWN,
+        "Select the jobs",
+        KeyParser::can_select_jobs,
+        KeyParser::next_job,
+    ),
+    Binding::new(DOWN, "Next job", KeyParser::is_in_jobs, KeyParser::next_job),
+    Binding::new(
+        UP,
+        "Previous job",
+        KeyParser::is_in_jobs,
+        KeyParser::previous_job,
+    ),
+    Binding::new(
+        LEFT,
+        "Back to the tabs",
+        KeyParser::is_in_jobs,
+        KeyParser::unselect_job,
+    ),
+    Binding::new(
+        &[KeyCode::Char('i')],
+        "Interrupt (SIGINT)",
+        KeyParser::is_in_jobs,
+        KeyParser::interrupt_job,
+    )
+    .with_hint("interrupt"),
+    Binding::new(
+        &[KeyCode::Char('t')],
+        "Terminate (SIGTERM)",
+        KeyParser::is_in_jobs,
+        KeyParser::terminate_job,
+    ),
+    Binding::new(
+        &[KeyCode::Char('x')],
+        "Kill (SIGKILL)",
+        KeyParser::is_in_jobs,
+        KeyParser::kill_job,
+    )
+    .with_hint("kill"),
+    Binding::new(
+        &[KeyCode::Char('d')],
+        "Remove the finished job",
+        KeyParser::is_job_finished,
+        KeyParser::remove_job,
+    )
+    .with_hint("remove"),
+    Binding::new(
+        &[KeyCode::Char('K')],
+        "Scroll the output back",
+        KeyParser::has_jobs,
+        KeyParser::scroll_job_back,
+    ),
+    Binding::new(
+        &[KeyCode::Char('J')],
+        "Scroll the output forward",
+        KeyParser::has_jobs,
+        KeyParser::scroll_job_forward,
+    ),
+];
+
+const FANOUT_BINDINGS: &[Binding] = &[
+    Binding::new(RIGHT, "Next tab", KeyParser::always, KeyParser::next_tab),
+    Binding::new(
+        LEFT,
+        "Previous tab",
+        KeyParser::always,
+        KeyParser::previous_tab,
+    ),
+    Binding::new(
+        DOWN,
+        "Next directory",
+        KeyParser::has_fanout,
+        KeyParser::next_directory,
+    ),
+    Binding::new(
+        UP,
+        "Previous directory",
+        KeyParser::has_fanout,
+        KeyParser::previous_directory,
+    ),
+    Binding::new(
+        CONFIRM,
+        "Show or hide the output of the directory",
+        KeyParser::has_fanout,
+        KeyParser::toggle_fanout_output,
+    )
+    .with_hint("output"),
+    Binding::new(
+        &[KeyCode::Char('x')],
+        "Cancel the remaining directories",
+        KeyParser::has_fanout,
+        KeyParser::cancel_fanout,
+    )
+    .with_hint("cancel"),
+    Binding::new(
+        &[KeyCode::Char('K')],
+        "Scroll the output back",
+        KeyParser::has_fanout,
+        KeyParser::scroll_fanout_back,
+    ),
+    Binding::new(
+        &[KeyCode::Char('J')],
+        "Scroll the output forward",
+        KeyParser::has_fanout,
+        KeyParser::scroll_fanout_forward,
+    ),
+];
+
+const HISTORY_BINDINGS: &[Binding] = &[
+    Binding::new(RIGHT, "Next tab", KeyParser::always, KeyParser::next_tab),
+    Binding::new(
+        LEFT,
+        "Previous tab",
+        KeyParser::always,
+        KeyParser::previous_tab,
+    ),
+    Binding::new(
+        DOWN,
+        "Older run",
+        KeyParser::has_history,
+        KeyParser::next_run,
+    ),
+    Binding::new(
+        UP,
+        "Newer run",
+        KeyParser::has_history,
+        KeyParser::previous_run,
+    ),
+];
+
+const FAVORITES_BINDINGS: &[Binding] = &[
+    Binding::new(RIGHT, "Next tab", KeyParser::always, KeyParser::next_tab),
+    Binding::new(
+        LEFT,
+        "Previous tab",
+        KeyParser::always,
+        KeyParser::previous_tab,
+    ),
+    Binding::new(
+        DOWN,
+        "Next favorite",
+        KeyParser::has_favorites,
+        KeyParser::next_favorite,
+    ),
+    Binding::new(
+        UP,
+        "Previous favorite",
+        KeyParser::has_favorites,
+        KeyParser::previous_favorite,
+    ),
+    Binding::new(
+        CONFIRM,
+        "Show the command in the library",
+        KeyParser::is_in_favorites,
+        KeyParser::open_favorite,
+    )
+    .with_hint("open"),
+];
+
+/// Tabs without anything to select.
+const TAB_BINDINGS: &[Binding] = &[
+    Binding::new(RIGHT, "Next tab", KeyParser::always, KeyParser::next_tab),
+    Binding::new(
+        LEFT,
+        "Previous tab",
+        KeyParser::always,
+        KeyParser::previous_tab,
+    ),
+];
+
+const TRASH_BINDINGS: &[Binding] = &[
+    Binding::new(RIGHT, "Next tab", KeyParser::always, KeyParser::next_tab),
+    Binding::new(
+        LEFT,
+        "Previous tab",
+        KeyParser::always,
+        KeyParser::previous_tab,
+    ),
+    Binding::new(
+        DOWN,
+        "Next deleted item",
+        KeyParser::has_trash,
+        KeyParser::next_trash_entry,
+    ),
+    Binding::new(
+        UP,
+        "Previous deleted item",
+        KeyParser::has_trash,
+        KeyParser::previous_trash_entry,
+    ),
+    Binding::new(
+        &[KeyCode::Enter, KeyCode::Char('r')],
+        "Restore it into the library",
+        KeyParser::is_in_trash,
+        KeyParser::restore_trash,
+    )
+    .with_hint("restore"),
+    Binding::new(
+        &[KeyCode::Char('x')],
+        "Delete it for good",
+        KeyParser::is_in_trash,
+        KeyParser::purge_trash_entry,
+    )
+    .with_hint("purge"),
+];
 
-                    if app.event_state.get_confirm() == &Confirm::Display {
-                        app.event_state.set_event_type(EventType::Tag);
-                        app.event_state.set_confirm(Confirm::Hide);
-                    }
+impl KeyParser {
+    pub fn parse_event(key_event: KeyEvent, app: &mut App) -> ParserResult {
+        // Shift is part of the typed character
+        let modifiers = key_event.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT);
+        if !modifiers.is_empty() {
+            return KeyParser::process_modified_key_code(key_event.code, modifiers, app);
+        }
 
-                    return Ok(None);
-                }
-                EventType::Tag => {
-                    if app.event_state.get_confirm() == &Confirm::Display {
-                        KeyParser::process_add_command_mode_confirm(key_code, app)?;
+        KeyParser::process_key_code(key_event.code, app)
+    }
 
-                        return Ok(None);
-                    }
+    /// Keys valid in the current tab, mode and focus, then the global ones.
+    pub fn get_bindings(app: &App) -> Vec<&'static Binding> {
+        KeyParser::get_table(app)
+            .iter()
+            .chain(GLOBAL_BINDINGS)
+            .filter(|binding| (binding.enabled)(app))
+            .collect()
+    }
 
-                    KeyParser::input_handler(key_code, app, app.config.name_config.tag.to_string());
+    /// One line of hints like `Enter: run  c: add  d: delete`.
+    pub fn get_hints(app: &App) -> String {
+        KeyParser::get_bindings(app)
+            .into_iter()
+            .filter_map(|binding| {
+                let hint = binding.hint?;
+                Some(format!("{}: {}", binding.get_name(&binding.keys[0]), hint))
+            })
+            .collect::<Vec<_>>()
+            .join("  ")
+    }
 
-                    Ok(None)
+    fn get_table(app: &App) -> &'static [Binding] {
+        match app.event_state.get_tab() {
+            Tab::Library => match app.event_state.get_mode() {
+                Mode::Normal => NORMAL_BINDINGS,
+                Mode::Delete => DELETE_BINDINGS,
+                Mode::Fork => FORK_BINDINGS,
+                Mode::Pick => PICK_BINDINGS,
+                Mode::Sequence => SEQUENCE_BINDINGS,
+                Mode::Add if app.event_state.get_confirm() == &Confirm::Display => {
+                    ADD_CONFIRM_BINDINGS
                 }
-                _ => Ok(None),
+                Mode::Add => ADD_BINDINGS,
+                Mode::FanOut => FANOUT_INPUT_BINDINGS,
+                Mode::TypeTag => TYPE_TAG_BINDINGS,
             },
-            _ => Ok(None),
+            Tab::History => HISTORY_BINDINGS,
+            Tab::Favorites => FAVORITES_BINDINGS,
+            Tab::Jobs => JOBS_BINDINGS,
+            Tab::FanOut => FANOUT_BINDINGS,
+            Tab::Trash => TRASH_BINDINGS,
+            Tab::Schedule | Tab::Settings => TAB_BINDINGS,
         }
     }
 
-    fn process_add_command_mode_confirm(key_code: KeyCode, app: &mut App) -> ParserResult {
-        let namespace = app.namespaces.as_ref().borrow().current_item().clone();
+    /// Runs the first enabled binding of the key, `None` when there is none.
+    fn dispatch(
+        bindings: &[Binding],
+        key_code: KeyCode,
+        modifiers: KeyModifiers,
+        app: &mut App,
+    ) -> Option<ParserResult> {
+        bindings
+            .iter()
+            .find(|binding| {
+                binding.modifiers == modifiers
+                    && binding.keys.contains(&key_code)
+                    && (binding.enabled)(app)
+            })
+            .map(|binding| match binding.action {
+                Action::App(action) => action(app),
+                Action::Key(action) => action(key_code, app),
+            })
+    }
 
-        match key_code {
-            KeyCode::Enter | KeyCode::Char(' ') => {
-                app.db
-                    .add_command_and_tag(
-                        Some(&String::from_iter(
-                            app.inputs
-                                .get(&app.config.name_config.command)
-                                .unwrap()
-                                .clone(),
-                        )),
-                        Some(&String::from_iter(
-                            app.inputs.get(&app.config.name_config.tag).unwrap().clone(),
-                        )),
-                        &namespace,
-                    )
-                    .expect("Failed to add command and tag");
-            }
-            _ => {}
-        }
-
-        let (new_commands, new_tags) = app
-            .db
-            .get_commands_and_tags(Some(namespace))
-            .expect("Failed to get commands and tags");
+    fn quit(app: &mut App) -> ParserResult {
+        app.quit = true;
+        Ok(None)
+    }
 
-        app.commands = Rc::new(RefCell::new(StatefulList::with_items(new_commands)));
-        app.tags = Rc::new(RefCell::new(StatefulList::with_items(new_tags)));
-        app.cursor_position = None;
+    fn show_help(app: &mut App) -> ParserResult {
+        app.help = true;
+        Ok(None)
+    }
 
-        app.event_state = EventState::default();
+    fn cancel(app: &mut App) -> ParserResult {
+        if app.event_state.get_mode() == &Mode::Normal
+            && app
+                .output
+                .as_ref()
+                .is_some_and(|process| !process.is_running())
+        {
+            app.output = None;
+            app.watch = None;
+        }
 
+        let tab = *app.event_state.get_tab();
+        app.event_state = EventState::default();
+        app.event_state.set_tab(tab);
+        app.sync_conflicts.clear();
+        app.picker = None;
+        app.status.clear();
         Ok(None)
     }
 
-    fn process_add_namespace_mode_confirm(key_code: KeyCode, app: &mut App) -> ParserResult {
-        match key_code {
-            KeyCode::Enter | KeyCode::Char(' ') => {
-                if app.inputs.is_empty() {
-                    return Ok(None);
-                }
+    fn process_key_code(key_code: KeyCode, app: &mut App) -> ParserResult {
+        // Any key closes the help
+        if app.help {
+            app.help = false;
+            return Ok(None);
+        }
 
-                let namespace = app
-                    .inputs
-                    .remove("namespace")
-                    .expect("namespace input is empty")
-                    .iter()
-                    .collect::<String>();
-
-                let existing_namespace = app.db.get_namespace(&namespace)?;
-
-                if existing_namespace.is_some() {
-                    // let message = format!("Namespace {} already exists", s);
-                    //
-                    // app.add.error_message = Some(message);
-                    //Todo message
-                    return Ok(None);
-                }
+        if let Some(pending) = app.pending_key.take() {
+            if let Some(result) = KeyParser::process_pending_key(pending, key_code, app) {
+                return result;
+            }
+        }
+
+        if let Some(result) =
+            KeyParser::dispatch(GLOBAL_BINDINGS, key_code, KeyModifiers::NONE, app)
+        {
+            return result;
+        }
+
+        match app.event_state.get_tab() {
+            Tab::Library => KeyParser::process_library_tab(key_code, app),
+            _ => KeyParser::dispatch(KeyParser::get_table(app), key_code, KeyModifiers::NONE, app)
+                .unwrap_or(Ok(None)),
+        }
+    }
 
-                app.db.add_namespace(&namespace)?;
+    /// Second key of `gg` or of a jump to a letter, `None` lets another key after `g` do what it
+    /// does alone.
+    fn process_pending_key(
+        pending: KeyCode,
+        key_code: KeyCode,
+        app: &mut App,
+    ) -> Option<ParserResult> {
+        match (pending, key_code) {
+            (KeyCode::Char('g'), KeyCode::Char('g')) => Some(KeyParser::jump(app, Jump::First)),
+            (KeyCode::Char('t'), KeyCode::Char(letter)) => {
+                Some(KeyParser::jump(app, Jump::Letter(letter)))
+            }
+            (KeyCode::Char('t'), _) => {
+                app.status.clear();
+                Some(Ok(None))
+            }
+            _ => None,
+        }
+    }
 
-                let namespaces = app.db.get_namespaces()?;
+    fn wait_for_key(key_code: KeyCode, app: &mut App) -> ParserResult {
+        if key_code == KeyCode::Char('t') {
+            app.status
+                .info(app.config.text_config.jump_to_letter.clone());
+        }
+        app.pending_key = Some(key_code);
+
+        Ok(None)
+    }
 
-                let mut app_namespace = app.namespaces.as_ref().borrow_mut();
-                app_namespace.items = namespaces;
-                app_namespace.state.select(Some(0));
+    /// Keys pressed with Ctrl or Alt are never typed.
+    fn process_modified_key_code(
+        key_code: KeyCode,
+        modifiers: KeyModifiers,
+        app: &mut App,
+    ) -> ParserResult {
+        if app.help {
+            app.help = false;
+            return Ok(None);
+        }
 
-                app.cursor_position = None;
-                app.event_state.set_confirm(Confirm::Confirmed);
+        KeyParser::dispatch(KeyParser::get_table(app), key_code, modifiers, app)
+            .or_else(|| KeyParser::dispatch(GLOBAL_BINDINGS, key_code, modifiers, app))
+            .unwrap_or(Ok(None))
+    }
 
-                Ok(None)
+    /// Clicks focus and select, a double-click does what Enter does and the wheel moves in the
+    /// list or scrolls the pane under the pointer. Popups are only answered with the keyboard.
+    pub fn parse_mouse_event(mouse_event: MouseEvent, app: &mut App) -> ParserResult {
+        if app.help {
+            if let MouseEventKind::Down(_) = mouse_event.kind {
+                app.help = false;
             }
+            return Ok(None);
+        }
+
+        if !KeyParser::is_idle(app) {
+            return Ok(None);
+        }
+
+        let (column, row) = (mouse_event.column, mouse_event.row);
+
+        match mouse_event.kind {
+            MouseEventKind::Down(MouseButton::Left) => KeyParser::click(column, row, app),
+            MouseEventKind::ScrollUp => KeyParser::scroll(column, row, true, app),
+            MouseEventKind::ScrollDown => KeyParser::scroll(column, row, false, app),
             _ => Ok(None),
         }
     }
 
-    fn process_delete_mode(key_code: KeyCode, app: &mut App) -> ParserResult {
-        let mut app_commands = app.commands.as_ref().borrow_mut();
-        let mut app_namespaces = app.namespaces.as_ref().borrow_mut();
-        let mut app_tags = app.tags.as_ref().borrow_mut();
+    /// A third click starts over instead of making another double-click.
+    fn is_double_click(column: u16, row: u16, app: &mut App) -> bool {
+        let now = Instant::now();
+        let double = app.last_click.is_some_and(|(time, last_column, last_row)| {
+            (last_column, last_row) == (column, row)
+                && now.duration_since(time).as_millis() < DOUBLE_CLICK_MS
+        });
+
+        app.last_click = match double {
+            true => None,
+            false => Some((now, column, row)),
+        };
+
+        double
+    }
+
+    fn click(column: u16, row: u16, app: &mut App) -> ParserResult {
+        let double = KeyParser::is_double_click(column, row, app);
+        let areas = app.areas;
+
+        if contains(areas.tabs, column, row) {
+            let index = app
+                .tabs
+                .as_ref()
+                .borrow()
+                .get_index_at(column - areas.tabs.x);
+            if let Some(index) = index {
+                app.set_tab(Tab::from_index(index));
+            }
 
-        if app.event_state.get_confirm() != &Confirm::Display {
-            app.event_state.set_confirm(Confirm::Confirmed);
             return Ok(None);
         }
 
-        match key_code {
-            KeyCode::Enter | KeyCode::Char(' ') => {
-                if app_commands.is_selected {
-                    app.db.delete_command(
-                        app_commands.current_item(),
-                        app_namespaces.current_item(),
-                    )?;
-
-                    let (commands, tags) = app
-                        .db
-                        .get_commands_and_tags(Some(app_namespaces.current_item().clone()))?;
-
-                    app_commands.items = commands;
-                    app_commands.state.select(Some(0));
-
-                    app_tags.items = tags;
-                    app_tags.state.select(Some(0));
-                } else if app_namespaces.is_selected {
-                    app.db.delete_namespace(app_namespaces.current_item())?;
-
-                    let namespaces = app.db.get_namespaces()?;
-                    app_namespaces.items = namespaces;
-                    app_namespaces.state.select(Some(0));
-
-                    let (commands, tags) = app
-                        .db
-                        .get_commands_and_tags(Some(app_namespaces.current_item().clone()))?;
-
-                    app_commands.items = commands;
-                    app_commands.state.select(Some(0));
-
-                    app_tags.items = tags;
-                    app_tags.state.select(Some(0));
-                } else {
-                    return Ok(None);
+        let selected = match app.event_state.get_tab() {
+            Tab::Library => KeyParser::click_library(column, row, app)?,
+            Tab::Jobs if contains(areas.rows, column, row) => {
+                let mut jobs = app.jobs.as_ref().borrow_mut();
+
+                match jobs.get_index_at(row - areas.rows.y) {
+                    Some(index) => {
+                        app.tabs.as_ref().borrow_mut().is_selected = false;
+                        jobs.is_selected = true;
+                        jobs.state.select(Some(index));
+                        app.job_scroll = None;
+                        true
+                    }
+                    None => false,
                 }
-
-                app.event_state.set_confirm(Confirm::Confirmed);
-                app.event_state.set_mode(Mode::Normal);
-
-                return Ok(None);
             }
-            _ => {}
+            Tab::History if contains(areas.rows, column, row) => {
+                KeyParser::click_table(&app.history, row - areas.rows.y)
+            }
+            Tab::Favorites if contains(areas.rows, column, row) => {
+                KeyParser::click_table(&app.favorites, row - areas.rows.y)
+            }
+            Tab::Trash if contains(areas.rows, column, row) => {
+                KeyParser::click_table(&app.trash, row - areas.rows.y)
+            }
+            _ => false,
+        };
+
+        if selected && double {
+            return KeyParser::dispatch(
+                KeyParser::get_table(app),
+                KeyCode::Enter,
+                KeyModifiers::NONE,
+                app,
+            )
+            .unwrap_or(Ok(None));
         }
 
         Ok(None)
     }
 
-    fn move_right(app: &mut App) -> ParserResult {
-        let mut tabs = app.tabs.as_ref().borrow_mut();
-        let mut namespaces = app.namespaces.as_ref().borrow_mut();
-        let mut commands = app.commands.as_ref().borrow_mut();
-        let mut tags = app.tags.as_ref().borrow_mut();
+    /// Focuses the list clicked, true when an item was selected.
+    fn click_library(column: u16, row: u16, app: &mut App) -> Result<bool, Box<dyn Error>> {
+        let areas = app.areas;
 
-        match namespaces.state.selected() {
-            Some(_) => {
-                namespaces.is_selected = false;
+        if contains(areas.namespaces, column, row) {
+            let index = app
+                .namespaces
+                .as_ref()
+                .borrow()
+                .get_index_at(row - areas.namespaces.y);
 
-                commands.is_selected = true;
-                tags.is_selected = true;
+            if let Some(index) = index {
+                KeyParser::focus_namespaces(Some(index), app)?;
+                return Ok(true);
+            }
+        }
+
+        for area in [areas.commands, areas.tags] {
+            if !contains(area, column, row) {
+                continue;
+            }
 
-                commands.state.select(Some(0));
-                tags.state.select(Some(0));
+            let index = app.commands.as_ref().borrow().get_index_at(row - area.y);
+            if let Some(index) = index {
+                KeyParser::focus_commands(Some(index), app)?;
+                return Ok(true);
             }
-            None => tabs.next(),
         }
 
-        Ok(None)
+        Ok(false)
     }
 
-    fn move_left(app: &mut App) -> ParserResult {
-        let mut tabs = app.tabs.as_ref().borrow_mut();
-        let mut namespaces = app.namespaces.as_ref().borrow_mut();
-        let mut commands = app.commands.as_ref().borrow_mut();
-        let mut tags = app.tags.as_ref().borrow_mut();
+    fn click_table<T>(list: &Rc<RefCell<StatefulList<T>>>, row: u16) -> bool {
+        let mut list = list.as_ref().borrow_mut();
 
-        match commands.state.selected() {
-            Some(_) => {
-                namespaces.is_selected = true;
+        match list.get_index_at(row) {
+            Some(index) => {
+                list.state.select(Some(index));
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn scroll(column: u16, row: u16, up: bool, app: &mut App) -> ParserResult {
+        let areas = app.areas;
 
-                commands.is_selected = false;
-                tags.is_selected = false;
+        if contains(areas.tabs, column, row) {
+            return Ok(None);
+        }
 
-                commands.unselect();
-                tags.unselect();
+        // The output panes are scrolled with the keys of every tab
+        if contains(areas.output, column, row) {
+            let key_code = KeyCode::Char(if up { 'K' } else { 'J' });
+            for _ in 0..SCROLL_LINES {
+                KeyParser::dispatch(KeyParser::get_table(app), key_code, KeyModifiers::NONE, app)
+                    .transpose()?;
             }
-            None => match namespaces.state.selected() {
-                Some(_) => {
-                    namespaces.is_selected = false;
-                    commands.is_selected = false;
-                    tags.is_selected = false;
 
-                    namespaces.unselect();
-                    commands.unselect();
-                    tags.unselect();
+            return Ok(None);
+        }
 
-                    tabs.is_selected = true;
+        if app.event_state.get_tab() == &Tab::Library {
+            if contains(areas.details, column, row) {
+                return KeyParser::scroll_details(
+                    app,
+                    if up { -SCROLL_LINES } else { SCROLL_LINES },
+                );
+            } else if contains(areas.namespaces, column, row) {
+                // The first turn over another list only focuses it
+                if !KeyParser::is_in_namespaces(app) {
+                    return KeyParser::focus_namespaces(None, app);
                 }
-                None => tabs.previous(),
-            },
+            } else if contains(areas.commands, column, row) || contains(areas.tags, column, row) {
+                if !KeyParser::is_in_commands(app) {
+                    return KeyParser::focus_commands(None, app);
+                }
+            } else {
+                return Ok(None);
+            }
         }
-        Ok(None)
+
+        let key_code = if up { KeyCode::Up } else { KeyCode::Down };
+        KeyParser::dispatch(KeyParser::get_table(app), key_code, KeyModifiers::NONE, app)
+            .unwrap_or(Ok(None))
     }
 
-    fn move_down(app: &mut App) -> ParserResult {
+    /// Focuses the namespaces on one of them, or on the current one.
+    fn focus_namespaces(index: Option<usize>, app: &mut App) -> ParserResult {
         let mut tabs = app.tabs.as_ref().borrow_mut();
         let mut namespaces = app.namespaces.as_ref().borrow_mut();
         let mut commands = app.commands.as_ref().borrow_mut();
@@ -320,163 +1195,1731 @@ impl KeyParser {
             return Ok(None);
         }
 
-        match commands.state.selected() {
-            Some(_) => {
-                if !commands.items.is_empty() {
-                    commands.next();
-                    tags.next();
-                }
-            }
-            None => match namespaces.state.selected() {
-                Some(_) => {
-                    namespaces.next();
-
-                    let index = namespaces.current();
-                    let namespace = namespaces.items[index].clone();
-
-                    let (new_commands, new_tags) = app.db.get_commands_and_tags(Some(namespace))?;
-                    commands.items = new_commands;
-                    tags.items = new_tags;
-                }
-                None => {
-                    tabs.is_selected = false;
+        let index = index.unwrap_or_else(|| namespaces.current());
+        let changed = namespaces.state.selected() != Some(index);
 
-                    namespaces.is_selected = true;
-                    namespaces.state.select(Some(0));
+        tabs.is_selected = false;
+        namespaces.is_selected = true;
+        namespaces.state.select(Some(index));
 
-                    let index = namespaces.current();
-                    let namespace = namespaces.items[index].clone();
+        commands.is_selected = false;
+        tags.is_selected = false;
+        commands.unselect();
+        tags.unselect();
 
-                    let (new_commands, new_tags) = app.db.get_commands_and_tags(Some(namespace))?;
-                    commands.items = new_commands;
-                    tags.items = new_tags;
-                }
-            },
+        if changed {
+            let (new_commands, new_tags) =
+                app.get_commands_and_tags(Some(namespaces.current_item()))?;
+            commands.set_items(new_commands);
+            tags.set_items(new_tags);
         }
 
         Ok(None)
     }
 
-    fn move_up(app: &mut App) -> ParserResult {
+    /// Focuses the commands on one of them, or on the current one.
+    fn focus_commands(index: Option<usize>, app: &mut App) -> ParserResult {
+        if app.namespaces.as_ref().borrow().state.selected().is_none() {
+            KeyParser::focus_namespaces(None, app)?;
+        }
+
         let mut namespaces = app.namespaces.as_ref().borrow_mut();
         let mut commands = app.commands.as_ref().borrow_mut();
         let mut tags = app.tags.as_ref().borrow_mut();
 
-        match commands.state.selected() {
-            Some(_) => {
-                if !commands.items.is_empty() {
-                    commands.previous();
-                    tags.previous();
-                }
-            }
-            None => {
-                namespaces.previous();
+        if commands.items.is_empty() {
+            return Ok(None);
+        }
 
-                let index = namespaces.current();
-                let namespace = namespaces.items[index].clone();
+        let index = index.unwrap_or_else(|| commands.current());
 
-                let (new_commands, new_tags) = app.db.get_commands_and_tags(Some(namespace))?;
-                commands.items = new_commands;
-                tags.items = new_tags;
-            }
-        };
+        namespaces.is_selected = false;
+        commands.is_selected = true;
+        tags.is_selected = true;
+        commands.state.select(Some(index));
+        tags.state.select(Some(index));
 
         Ok(None)
     }
 
-    fn enter(app: &mut App) -> ParserResult {
-        let mut namespaces = app.namespaces.as_ref().borrow_mut();
-        let mut commands = app.commands.as_ref().borrow_mut();
-        let mut tags = app.tags.as_ref().borrow_mut();
-
-        match commands.state.selected() {
-            Some(_) => match app.event_state.get_confirm() {
-                Confirm::Display => {
-                    if commands.items.is_empty() {
-                        app.event_state.set_confirm(Confirm::Confirmed);
-                        return Ok(None);
-                    }
+    fn scroll_details_down(app: &mut App) -> ParserResult {
+        KeyParser::scroll_details(app, SCROLL_LINES)
+    }
 
-                    app.event_state.set_confirm(Confirm::Confirmed);
+    fn scroll_details_up(app: &mut App) -> ParserResult {
+        KeyParser::scroll_details(app, -SCROLL_LINES)
+    }
 
-                    return Ok(Some((
-                        commands.items[commands.current()].clone(),
-                        tags.items[tags.current()].clone(),
-                    )));
-                }
-                Confirm::Hide => {
-                    commands.is_selected = false;
-                    tags.is_selected = false;
+    /// A positive delta scrolls down. The last line can be scrolled up to the top, as wrapped
+    /// words make the count of lines approximate.
+    fn scroll_details(app: &mut App, delta: i32) -> ParserResult {
+        let commands = app.commands.as_ref().borrow();
+        let tags = app.tags.as_ref().borrow();
 
-                    app.event_state.set_confirm(Confirm::Display);
-                }
-                _ => {}
-            },
-            None => match namespaces.state.selected() {
-                Some(_) => {
-                    namespaces.is_selected = false;
+        if commands.state.selected().is_none() || commands.items.is_empty() {
+            return Ok(None);
+        }
 
-                    commands.is_selected = true;
-                    tags.is_selected = true;
+        let width = app.areas.details.width.max(1) as usize;
+        // The command starts after an empty line
+        let lines = 1 + commands
+            .current_item()
+            .lines()
+            .map(|line| line.chars().count().max(1).div_ceil(width))
+            .sum::<usize>();
+
+        let tag = tags.current_item().clone();
+        let scroll = match &app.details_scroll {
+            Some((scrolled, scroll)) if scrolled == &tag => *scroll as i32,
+            _ => 0,
+        };
+        let scroll = (scroll + delta).clamp(0, lines as i32 - 1) as u16;
+        drop(commands);
+        drop(tags);
 
-                    commands.state.select(Some(0));
-                    tags.state.select(Some(0));
-                }
-                None => {}
-            },
-        }
+        app.details_scroll = Some((tag, scroll));
 
         Ok(None)
     }
 
-    fn change_to_add_namespace_mode(app: &mut App) -> ParserResult {
-        app.event_state = EventState::default();
-        app.event_state.set_mode(Mode::Add);
-        app.event_state.set_sub_mode(SubMode::Namespace);
-        app.event_state.set_event_type(EventType::Namespace);
-        app.inputs.clear();
-        app.cursor_position = None;
+    /// A list has the focus, outside of the popups and confirmations.
+    fn can_jump(app: &App) -> bool {
+        if !KeyParser::is_idle(app) {
+            return false;
+        }
 
-        Ok(None)
+        match app.event_state.get_tab() {
+            Tab::Library => {
+                app.event_state.get_mode() == &Mode::Normal && !KeyParser::is_on_tabs(app)
+            }
+            Tab::History => KeyParser::has_history(app),
+            Tab::Favorites => KeyParser::has_favorites(app),
+            Tab::Trash => KeyParser::has_trash(app),
+            Tab::Jobs => KeyParser::is_in_jobs(app),
+            _ => false,
+        }
     }
 
-    fn change_to_add_command_mode(app: &mut App) -> ParserResult {
-        app.event_state = EventState::default();
-        app.event_state.set_mode(Mode::Add);
-        app.event_state.set_sub_mode(SubMode::Command);
-        app.event_state.set_event_type(EventType::Command);
-        app.inputs.clear();
-        app.cursor_position = None;
+    /// Jobs are terminated with `t`.
+    fn can_jump_to_letter(app: &App) -> bool {
+        app.event_state.get_tab() != &Tab::Jobs && KeyParser::can_jump(app)
+    }
 
-        Ok(None)
+    fn page_down(app: &mut App) -> ParserResult {
+        KeyParser::jump(app, Jump::PageDown)
     }
 
-    fn change_to_delete_mode(app: &mut App) -> ParserResult {
-        let commands = app.commands.as_ref().borrow();
-        let namespaces = app.namespaces.as_ref().borrow();
+    fn page_up(app: &mut App) -> ParserResult {
+        KeyParser::jump(app, Jump::PageUp)
+    }
 
-        if commands.is_selected || namespaces.is_selected {
-            app.event_state.set_mode(Mode::Delete);
-            app.event_state.set_confirm(Confirm::Display);
-        }
+    fn jump_to_first(app: &mut App) -> ParserResult {
+        KeyParser::jump(app, Jump::First)
+    }
 
-        Ok(None)
+    fn jump_to_last(app: &mut App) -> ParserResult {
+        KeyParser::jump(app, Jump::Last)
     }
 
-    fn input_handler(key_code: KeyCode, app: &mut App, k: String) -> () {
-        match key_code {
-            KeyCode::Enter => {
-                app.event_state.set_confirm(Confirm::Display);
+    /// Moves the selection of the focused list. Commands are found by their tag, without the
+    /// namespace most of them start with.
+    fn jump(app: &mut App, jump: Jump) -> ParserResult {
+        let found = match app.event_state.get_tab() {
+            Tab::Library if KeyParser::is_in_commands(app) => {
+                let prefix = format!("{}:", app.get_current_namespace_name().unwrap_or_default());
+                let index = app
+                    .tags
+                    .as_ref()
+                    .borrow()
+                    .get_jump_index(jump, |tag: &String| {
+                        tag.strip_prefix(prefix.as_str()).unwrap_or(tag)
+                    });
+
+                index.map(|index| KeyParser::focus_commands(Some(index), app))
             }
-            KeyCode::Char(c) => {
-                app.inputs.entry(k).or_insert_with(Vec::new).push(c);
-                app.cursor_position.as_mut().unwrap().push_inc(c);
+            Tab::Library => {
+                let index = app
+                    .namespaces
+                    .as_ref()
+                    .borrow()
+                    .get_jump_index(jump, |namespace: &Namespace| namespace.name.as_str());
+
+                index.map(|index| KeyParser::focus_namespaces(Some(index), app))
             }
-            KeyCode::Backspace => {
-                app.inputs.entry(k).or_insert_with(Vec::new).pop();
-                app.cursor_position.as_mut().unwrap().pop_dec();
+            Tab::History => KeyParser::jump_in(&app.history, jump, |entry: &HistoryEntry| {
+                entry.result.tag.as_str()
+            }),
+            Tab::Favorites => KeyParser::jump_in(&app.favorites, jump, |favorite: &Favorite| {
+                favorite.tag.as_str()
+            }),
+            Tab::Trash => KeyParser::jump_in(&app.trash, jump, TrashEntry::get_name),
+            Tab::Jobs => {
+                app.job_scroll = None;
+                KeyParser::jump_in(&app.jobs, jump, |_| "")
             }
-            _ => (),
+            _ => return Ok(None),
+        };
+
+        match (found, jump) {
+            (Some(result), _) => {
+                app.status.clear();
+                result
+            }
+            (None, Jump::Letter(letter)) => {
+                app.status.warn(format!("No item starting with {}", letter));
+                Ok(None)
+            }
+            (None, _) => Ok(None),
+        }
+    }
+
+    fn jump_in<T>(
+        list: &Rc<RefCell<StatefulList<T>>>,
+        jump: Jump,
+        get_name: impl Fn(&T) -> &str,
+    ) -> Option<ParserResult> {
+        let mut list = list.as_ref().borrow_mut();
+        let index = list.get_jump_index(jump, get_name)?;
+        list.state.select(Some(index));
+
+        Some(Ok(None))
+    }
+
+    fn always(_: &App) -> bool {
+        true
+    }
+
+    fn is_typing(app: &App) -> bool {
+        matches!(
+            app.event_state.get_mode(),
+            Mode::Add | Mode::FanOut | Mode::TypeTag
+        )
+    }
+
+    fn is_not_typing(app: &App) -> bool {
+        !KeyParser::is_typing(app)
+    }
+
+    fn is_in_popup(app: &App) -> bool {
+        app.event_state.get_mode() != &Mode::Normal
+    }
+
+    fn is_not_in_popup(app: &App) -> bool {
+        !KeyParser::is_in_popup(app)
+    }
+
+    fn can_quit(app: &App) -> bool {
+        matches!(app.event_state.get_mode(), Mode::Normal | Mode::Delete)
+    }
+
+    fn is_on_tabs(app: &App) -> bool {
+        app.namespaces.as_ref().borrow().state.selected().is_none()
+    }
+
+    fn is_in_namespaces(app: &App) -> bool {
+        !KeyParser::is_on_tabs(app) && !KeyParser::is_in_commands(app)
+    }
+
+    fn is_in_commands(app: &App) -> bool {
+        app.commands.as_ref().borrow().state.selected().is_some()
+    }
+    /// No popup, confirmation or sync conflict is waiting for an answer.
+    fn is_idle(app: &App) -> bool {
+        KeyParser::is_not_in_popup(app)
+            && app.event_state.get_confirm() != &Confirm::Display
+            && app.sync_conflicts.is_empty()
+    }
+    fn can_scroll_details(app: &App) -> bool {
+        KeyParser::is_idle(app) && KeyParser::is_in_commands(app)
+    }
+
+    fn has_command(app: &App) -> bool {
+        KeyParser::is_in_commands(app) && !app.commands.as_ref().borrow().items.is_empty()
+    }
+
+    /// Workflows only run once cm exits.
+    fn has_runnable_command(app: &App) -> bool {
+        KeyParser::has_command(app) && !KeyParser::is_current_namespace_workflow(app)
+    }
+
+    fn can_watch(app: &App) -> bool {
+        app.watch.is_some() || KeyParser::has_runnable_command(app)
+    }
+
+    fn has_output(app: &App) -> bool {
+        app.output.is_some()
+    }
+
+    fn can_edit(app: &App) -> bool {
+        !KeyParser::is_on_tabs(app) && !KeyParser::is_current_namespace_read_only(app)
+    }
+
+    fn can_fork(app: &App) -> bool {
+        !KeyParser::is_on_tabs(app)
+            && KeyParser::is_current_namespace_read_only(app)
+            && !KeyParser::is_current_namespace_workflow(app)
+            && !app.commands.as_ref().borrow().items.is_empty()
+    }
+
+    fn has_profiles(app: &App) -> bool {
+        !app.settings.profiles.is_empty()
+    }
+
+    fn next_tab(app: &mut App) -> ParserResult {
+        app.next_tab();
+        Ok(None)
+    }
+
+    fn previous_tab(app: &mut App) -> ParserResult {
+        app.previous_tab();
+        Ok(None)
+    }
+
+    /// Number keys go to the tab of that position in the tab bar.
+    fn go_to_tab(key_code: KeyCode, app: &mut App) -> ParserResult {
+        if let Some(index) = KeyParser::get_number(key_code) {
+            app.set_tab(Tab::from_index(index - 1));
+        }
+
+        Ok(None)
+    }
+
+    fn get_number(key_code: KeyCode) -> Option<usize> {
+        match key_code {
+            KeyCode::Char(c) => c.to_digit(10).map(|digit| digit as usize),
+            _ => None,
+        }
+    }
+
+    fn has_history(app: &App) -> bool {
+        !app.history.as_ref().borrow().items.is_empty()
+    }
+
+    fn next_run(app: &mut App) -> ParserResult {
+        app.history.as_ref().borrow_mut().next();
+        Ok(None)
+    }
+
+    fn previous_run(app: &mut App) -> ParserResult {
+        app.history.as_ref().borrow_mut().previous();
+        Ok(None)
+    }
+
+    fn has_favorites(app: &App) -> bool {
+        !app.favorites.as_ref().borrow().items.is_empty()
+    }
+
+    fn is_in_favorites(app: &App) -> bool {
+        app.favorites.as_ref().borrow().state.selected().is_some()
+    }
+
+    fn next_favorite(app: &mut App) -> ParserResult {
+        app.favorites.as_ref().borrow_mut().next();
+        Ok(None)
+    }
+
+    fn previous_favorite(app: &mut App) -> ParserResult {
+        app.favorites.as_ref().borrow_mut().previous();
+        Ok(None)
+    }
+
+    fn open_favorite(app: &mut App) -> ParserResult {
+        let favorite = app.favorites.as_ref().borrow().current_item().clone();
+        app.go_to_favorite(&favorite)?;
+
+        Ok(None)
+    }
+
+    fn can_run_favorite(app: &App) -> bool {
+        KeyParser::is_idle(app)
+            && app.event_state.get_tab() == &Tab::Library
+            && app.event_state.get_mode() == &Mode::Normal
+            && !KeyParser::is_on_tabs(app)
+            && KeyParser::has_favorites(app)
+    }
+
+    /// Selects the favorite in the library and runs it like Enter, confirmations included.
+    fn run_favorite(key_code: KeyCode, app: &mut App) -> ParserResult {
+        let number = match KeyParser::get_number(key_code) {
+            Some(number) => number,
+            None => return Ok(None),
+        };
+
+        let favorite = app
+            .favorites
+            .as_ref()
+            .borrow()
+            .items
+            .get(number - 1)
+            .cloned();
+        match favorite {
+            Some(favorite) => {
+                app.go_to_favorite(&favorite)?;
+                app.event_state.set_confirm(Confirm::Hide);
+                KeyParser::enter(app)
+            }
+            None => {
+                app.status.warn(format!("No favorite {}", number));
+                Ok(None)
+            }
+        }
+    }
+
+    /// Items of a namespace, or namespaces of the personal library, outside of a confirmation.
+    fn can_mark(app: &App) -> bool {
+        app.event_state.get_confirm() != &Confirm::Display
+            && (KeyParser::has_command(app) || KeyParser::can_reorganize_namespace(app))
+    }
+
+    /// Commands are marked in the tags list, which shares the indexes of the commands.
+    fn toggle_mark(app: &mut App) -> ParserResult {
+        match KeyParser::is_in_commands(app) {
+            true => app.tags.borrow_mut().toggle_mark(),
+            false => app.namespaces.borrow_mut().toggle_mark(),
+        }
+
+        Ok(None)
+    }
+
+    fn mark_range(app: &mut App) -> ParserResult {
+        match KeyParser::is_in_commands(app) {
+            true => app.tags.borrow_mut().mark_range(),
+            false => app.namespaces.borrow_mut().mark_range(),
+        }
+
+        Ok(None)
+    }
+
+    fn toggle_all_marks(app: &mut App) -> ParserResult {
+        match KeyParser::is_in_commands(app) {
+            true => app.tags.borrow_mut().toggle_all(),
+            false => app.namespaces.borrow_mut().toggle_all(),
+        }
+
+        Ok(None)
+    }
+
+    fn export_marked(app: &mut App) -> ParserResult {
+        app.export_marked()?;
+        Ok(None)
+    }
+
+    fn change_to_sequence_mode(app: &mut App) -> ParserResult {
+        app.event_state.set_mode(Mode::Sequence);
+        app.event_state.set_confirm(Confirm::Display);
+
+        Ok(None)
+    }
+
+    /// The workflow is returned to run once the terminal is restored.
+    fn confirm_sequence(app: &mut App) -> ParserResult {
+        app.event_state = EventState::default();
+
+        Ok(app.get_sequence().map(|sequence| {
+            let result = (sequence.describe(), sequence.tag.clone());
+            app.sequence = Some(sequence);
+            result
+        }))
+    }
+
+    fn can_reorganize_namespace(app: &App) -> bool {
+        KeyParser::is_in_namespaces(app) && !KeyParser::is_current_namespace_read_only(app)
+    }
+
+    /// Only commands of the personal library can be starred.
+    fn can_change_command(app: &App) -> bool {
+        KeyParser::has_command(app) && !KeyParser::is_current_namespace_read_only(app)
+    }
+
+    fn toggle_favorite(app: &mut App) -> ParserResult {
+        app.toggle_favorite()?;
+        Ok(None)
+    }
+
+    fn can_undo(app: &App) -> bool {
+        KeyParser::is_idle(app) && app.changes.can_undo()
+    }
+
+    fn can_redo(app: &App) -> bool {
+        KeyParser::is_idle(app) && app.changes.can_redo()
+    }
+
+    fn undo(app: &mut App) -> ParserResult {
+        app.undo()?;
+        Ok(None)
+    }
+
+    fn redo(app: &mut App) -> ParserResult {
+        app.redo()?;
+        Ok(None)
+    }
+
+    fn has_trash(app: &App) -> bool {
+        !app.trash.as_ref().borrow().items.is_empty()
+    }
+
+    fn is_in_trash(app: &App) -> bool {
+        app.trash.as_ref().borrow().state.selected().is_some()
+    }
+
+    fn next_trash_entry(app: &mut App) -> ParserResult {
+        app.trash.as_ref().borrow_mut().next();
+        Ok(None)
+    }
+
+    fn previous_trash_entry(app: &mut App) -> ParserResult {
+        app.trash.as_ref().borrow_mut().previous();
+        Ok(None)
+    }
+
+    fn restore_trash(app: &mut App) -> ParserResult {
+        app.restore_trash()?;
+        Ok(None)
+    }
+
+    fn purge_trash_entry(app: &mut App) -> ParserResult {
+        app.purge_trash_entry()?;
+        Ok(None)
+    }
+
+    fn has_fanout(app: &App) -> bool {
+        app.fanout.is_some()
+    }
+
+    fn next_directory(app: &mut App) -> ParserResult {
+        if let Some(fanout) = app.fanout.as_mut() {
+            fanout.next();
+        }
+
+        Ok(None)
+    }
+
+    fn previous_directory(app: &mut App) -> ParserResult {
+        if let Some(fanout) = app.fanout.as_mut() {
+            fanout.previous();
+        }
+
+        Ok(None)
+    }
+
+    fn toggle_fanout_output(app: &mut App) -> ParserResult {
+        if let Some(fanout) = app.fanout.as_mut() {
+            fanout.expanded = !fanout.expanded;
+            fanout.scroll = None;
+        }
+
+        Ok(None)
+    }
+
+    fn cancel_fanout(app: &mut App) -> ParserResult {
+        if let Some(fanout) = app.fanout.as_mut() {
+            fanout.cancel();
+        }
+
+        Ok(None)
+    }
+
+    fn scroll_fanout_back(app: &mut App) -> ParserResult {
+        KeyParser::scroll_fanout(app, 1)
+    }
+
+    fn scroll_fanout_forward(app: &mut App) -> ParserResult {
+        KeyParser::scroll_fanout(app, -1)
+    }
+
+    /// A positive delta scrolls back in the output of the selected directory.
+    fn scroll_fanout(app: &mut App, delta: i32) -> ParserResult {
+        if let Some(fanout) = app.fanout.as_mut() {
+            let lines = fanout.runs[fanout.current()]
+                .process
+                .as_ref()
+                .map_or(0, |process| process.lines.len());
+
+            fanout.scroll = KeyParser::get_scroll_back(fanout.scroll, delta, lines);
+        }
+
+        Ok(None)
+    }
+
+    fn has_jobs(app: &App) -> bool {
+        !app.jobs.as_ref().borrow().items.is_empty()
+    }
+
+    fn is_in_jobs(app: &App) -> bool {
+        app.jobs.as_ref().borrow().state.selected().is_some()
+    }
+
+    fn is_on_tabs_of_jobs(app: &App) -> bool {
+        !KeyParser::is_in_jobs(app)
+    }
+
+    fn can_select_jobs(app: &App) -> bool {
+        KeyParser::has_jobs(app) && !KeyParser::is_in_jobs(app)
+    }
+
+    fn is_job_finished(app: &App) -> bool {
+        let jobs = app.jobs.as_ref().borrow();
+
+        jobs.state.selected().is_some() && !jobs.current_item().is_running()
+    }
+
+    fn next_job(app: &mut App) -> ParserResult {
+        let mut tabs = app.tabs.as_ref().borrow_mut();
+        let mut jobs = app.jobs.as_ref().borrow_mut();
+
+        if jobs.state.selected().is_none() {
+            tabs.is_selected = false;
+            jobs.is_selected = true;
+            jobs.state.select(Some(0));
+        } else {
+            jobs.next();
+        }
+        app.job_scroll = None;
+
+        Ok(None)
+    }
+
+    fn previous_job(app: &mut App) -> ParserResult {
+        app.jobs.as_ref().borrow_mut().previous();
+        app.job_scroll = None;
+
+        Ok(None)
+    }
+
+    fn unselect_job(app: &mut App) -> ParserResult {
+        let mut jobs = app.jobs.as_ref().borrow_mut();
+
+        jobs.unselect();
+        jobs.is_selected = false;
+        app.tabs.as_ref().borrow_mut().is_selected = true;
+
+        Ok(None)
+    }
+
+    fn interrupt_job(app: &mut App) -> ParserResult {
+        app.jobs
+            .as_ref()
+            .borrow_mut()
+            .current_item()
+            .signal(libc::SIGINT);
+        Ok(None)
+    }
+
+    fn terminate_job(app: &mut App) -> ParserResult {
+        app.jobs
+            .as_ref()
+            .borrow_mut()
+            .current_item()
+            .signal(libc::SIGTERM);
+        Ok(None)
+    }
+
+    fn kill_job(app: &mut App) -> ParserResult {
+        app.jobs
+            .as_ref()
+            .borrow_mut()
+            .current_item()
+            .signal(libc::SIGKILL);
+        Ok(None)
+    }
+
+    fn remove_job(app: &mut App) -> ParserResult {
+        let mut jobs = app.jobs.as_ref().borrow_mut();
+
+        let index = jobs.current();
+        jobs.items.remove(index);
+        app.job_scroll = None;
+
+        if jobs.items.is_empty() {
+            jobs.unselect();
+            jobs.is_selected = false;
+            app.tabs.as_ref().borrow_mut().is_selected = true;
+        } else {
+            let last = jobs.items.len() - 1;
+            jobs.state.select(Some(index.min(last)));
+        }
+
+        Ok(None)
+    }
+
+    fn scroll_job_back(app: &mut App) -> ParserResult {
+        KeyParser::scroll_job(app, 1)
+    }
+
+    fn scroll_job_forward(app: &mut App) -> ParserResult {
+        KeyParser::scroll_job(app, -1)
+    }
+
+    fn scroll_job(app: &mut App, delta: i32) -> ParserResult {
+        let lines = app.jobs.as_ref().borrow_mut().current_item().lines.len();
+        app.job_scroll = KeyParser::get_scroll_back(app.job_scroll, delta, lines);
+
+        Ok(None)
+    }
+
+    fn process_library_tab(key_code: KeyCode, app: &mut App) -> ParserResult {
+        match app.event_state.get_mode() {
+            Mode::Normal => KeyParser::process_normal_mode(key_code, app),
+            Mode::Add | Mode::FanOut | Mode::TypeTag => {
+                KeyParser::process_input_mode(key_code, app)
+            }
+            Mode::Delete => KeyParser::process_delete_mode(key_code, app),
+            Mode::Pick => KeyParser::dispatch(PICK_BINDINGS, key_code, KeyModifiers::NONE, app)
+                .unwrap_or(Ok(None)),
+            Mode::Sequence => {
+                KeyParser::dispatch(SEQUENCE_BINDINGS, key_code, KeyModifiers::NONE, app)
+                    .unwrap_or(Ok(None))
+            }
+            Mode::Fork => KeyParser::process_fork_mode(key_code, app),
+        }
+    }
+
+    fn process_normal_mode(key_code: KeyCode, app: &mut App) -> ParserResult {
+        KeyParser::dispatch(NORMAL_BINDINGS, key_code, KeyModifiers::NONE, app).unwrap_or(Ok(None))
+    }
+
+    /// Dispatches the keys of the modes typing into an input, other characters are typed.
+    fn process_input_mode(key_code: KeyCode, app: &mut App) -> ParserResult {
+        if let Some(result) =
+            KeyParser::dispatch(KeyParser::get_table(app), key_code, KeyModifiers::NONE, app)
+        {
+            return result;
+        }
+
+        // The popups confirming an addition take no input
+        if app.event_state.get_mode() == &Mode::Add
+            && app.event_state.get_confirm() == &Confirm::Display
+        {
+            if matches!(app.event_state.get_sub_mode(), SubMode::Command) {
+                return KeyParser::close_add_command(app);
+            }
+
+            return Ok(None);
+        }
+
+        if let KeyCode::Char(c) = key_code {
+            let name = KeyParser::get_input_name(app);
+            app.inputs.entry(name).or_default().push(c);
+            // The cursor is placed once the input is drawn
+            if let Some(cursor) = app.cursor_position.as_mut() {
+                cursor.push_inc(c);
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Name of the input typed into in the current mode.
+    fn get_input_name(app: &App) -> String {
+        let names = &app.config.name_config;
+
+        match app.event_state.get_mode() {
+            Mode::FanOut => names.directories.clone(),
+            Mode::TypeTag => names.tag.clone(),
+            _ => match app.event_state.get_sub_mode() {
+                SubMode::Command if app.event_state.get_event_type() == &EventType::Command => {
+                    names.command.clone()
+                }
+                SubMode::Command | SubMode::Prefix => names.tag.clone(),
+                _ => names.namespace.clone(),
+            },
+        }
+    }
+
+    fn erase_input(app: &mut App) -> ParserResult {
+        let name = KeyParser::get_input_name(app);
+        app.inputs.entry(name).or_default().pop();
+        if let Some(cursor) = app.cursor_position.as_mut() {
+            cursor.pop_dec();
+        }
+
+        Ok(None)
+    }
+
+    /// A command is typed before its tag, the others are confirmed in a popup.
+    fn submit_input(app: &mut App) -> ParserResult {
+        match app.event_state.get_sub_mode() {
+            SubMode::Rename => return KeyParser::confirm_rename(app),
+            SubMode::Prefix => return KeyParser::confirm_prefix(app),
+            SubMode::Command if app.event_state.get_event_type() == &EventType::Command => {
+                app.event_state.set_event_type(EventType::Tag);
+            }
+            _ => app.event_state.set_confirm(Confirm::Display),
+        }
+
+        Ok(None)
+    }
+
+    fn confirm_add(app: &mut App) -> ParserResult {
+        match app.event_state.get_sub_mode() {
+            SubMode::Namespace => KeyParser::confirm_add_namespace(app),
+            _ => KeyParser::confirm_add_command(app),
+        }
+    }
+
+    fn confirm_add_command(app: &mut App) -> ParserResult {
+        let namespace = app.namespaces.as_ref().borrow().current_item().clone();
+
+        let get_input = |name: &String| {
+            app.inputs
+                .get(name)
+                .map(String::from_iter)
+                .unwrap_or_default()
+        };
+        let command = get_input(&app.config.name_config.command);
+        let tag = get_input(&app.config.name_config.tag);
+
+        if command.trim().is_empty() {
+            app.status.warn("The command is empty, nothing was added");
+        } else if app.db.has_tag(&tag)? {
+            app.status.warn(format!("Tag {} already exists", tag));
+        } else {
+            match app
+                .db
+                .add_command_and_tag(Some(&command), Some(&tag), &namespace.name)
+            {
+                Ok(()) => {
+                    app.changes
+                        .record(vec![Change::Trash(Item::Command(tag.clone()))]);
+                    app.status.info(format!("Command {} added", tag))
+                }
+                Err(e) => app.status.error(e),
+            }
+        }
+
+        KeyParser::close_add_command(app)
+    }
+
+    fn close_add_command(app: &mut App) -> ParserResult {
+        let namespace = app.namespaces.as_ref().borrow().current_item().clone();
+
+        app.cursor_position = None;
+        app.event_state = EventState::default();
+
+        let (new_commands, new_tags) = app.get_commands_and_tags(Some(&namespace))?;
+
+        app.commands = Rc::new(RefCell::new(StatefulList::with_items(new_commands)));
+        app.tags = Rc::new(RefCell::new(StatefulList::with_items(new_tags)));
+
+        Ok(None)
+    }
+
+    fn confirm_add_namespace(app: &mut App) -> ParserResult {
+        if app.inputs.is_empty() {
+            return Ok(None);
+        }
+
+        let namespace = app
+            .inputs
+            .remove(&app.config.name_config.namespace)
+            .unwrap_or_default()
+            .iter()
+            .collect::<String>();
+
+        let existing_namespace = app.db.get_namespace(&namespace)?;
+
+        if existing_namespace.is_some() {
+            app.status
+                .warn(format!("Namespace {} already exists", namespace));
+            return Ok(None);
+        }
+
+        app.db.add_namespace(&namespace)?;
+        app.changes
+            .record(vec![Change::Trash(Item::Namespace(namespace.clone()))]);
+        app.status.info(format!("Namespace {} added", namespace));
+
+        let namespaces = app.get_namespaces()?;
+
+        let mut app_namespace = app.namespaces.as_ref().borrow_mut();
+        app_namespace.set_items(namespaces);
+        app_namespace.state.select(Some(0));
+
+        app.cursor_position = None;
+        app.event_state.set_confirm(Confirm::Confirmed);
+
+        Ok(None)
+    }
+
+    fn process_delete_mode(key_code: KeyCode, app: &mut App) -> ParserResult {
+        if app.event_state.get_confirm() != &Confirm::Display {
+            app.event_state.set_confirm(Confirm::Confirmed);
+            return Ok(None);
+        }
+
+        KeyParser::dispatch(DELETE_BINDINGS, key_code, KeyModifiers::NONE, app).unwrap_or(Ok(None))
+    }
+
+    fn confirm_delete(app: &mut App) -> ParserResult {
+        app.delete_marked()?;
+
+        app.event_state.set_confirm(Confirm::Confirmed);
+        app.event_state.set_mode(Mode::Normal);
+
+        Ok(None)
+    }
+
+    fn move_right(app: &mut App) -> ParserResult {
+        let mut namespaces = app.namespaces.as_ref().borrow_mut();
+        let mut commands = app.commands.as_ref().borrow_mut();
+        let mut tags = app.tags.as_ref().borrow_mut();
+
+        if namespaces.state.selected().is_some() {
+            namespaces.is_selected = false;
+
+            commands.is_selected = true;
+            tags.is_selected = true;
+
+            commands.state.select(Some(0));
+            tags.state.select(Some(0));
+        }
+
+        Ok(None)
+    }
+
+    fn move_left(app: &mut App) -> ParserResult {
+        let mut tabs = app.tabs.as_ref().borrow_mut();
+        let mut namespaces = app.namespaces.as_ref().borrow_mut();
+        let mut commands = app.commands.as_ref().borrow_mut();
+        let mut tags = app.tags.as_ref().borrow_mut();
+
+        if commands.state.selected().is_some() {
+            namespaces.is_selected = true;
+
+            commands.is_selected = false;
+            tags.is_selected = false;
+
+            commands.unselect();
+            tags.unselect();
+        } else if namespaces.state.selected().is_some() {
+            namespaces.is_selected = false;
+            commands.is_selected = false;
+            tags.is_selected = false;
+
+            namespaces.unselect();
+            commands.unselect();
+            tags.unselect();
+
+            tabs.is_selected = true;
+        }
+
+        Ok(None)
+    }
+
+    fn move_down(app: &mut App) -> ParserResult {
+        let mut tabs = app.tabs.as_ref().borrow_mut();
+        let mut namespaces = app.namespaces.as_ref().borrow_mut();
+        let mut commands = app.commands.as_ref().borrow_mut();
+        let mut tags = app.tags.as_ref().borrow_mut();
+
+        if namespaces.items.is_empty() {
+            return Ok(None);
+        }
+
+        match commands.state.selected() {
+            Some(_) => {
+                if !commands.items.is_empty() {
+                    commands.next();
+                    tags.next();
+                }
+            }
+            None => match namespaces.state.selected() {
+                Some(_) => {
+                    namespaces.next();
+
+                    let (new_commands, new_tags) =
+                        app.get_commands_and_tags(Some(namespaces.current_item()))?;
+                    commands.set_items(new_commands);
+                    tags.set_items(new_tags);
+                }
+                None => {
+                    tabs.is_selected = false;
+
+                    namespaces.is_selected = true;
+                    namespaces.state.select(Some(0));
+
+                    let (new_commands, new_tags) =
+                        app.get_commands_and_tags(Some(namespaces.current_item()))?;
+                    commands.set_items(new_commands);
+                    tags.set_items(new_tags);
+                }
+            },
+        }
+
+        Ok(None)
+    }
+
+    fn move_up(app: &mut App) -> ParserResult {
+        let mut namespaces = app.namespaces.as_ref().borrow_mut();
+        let mut commands = app.commands.as_ref().borrow_mut();
+        let mut tags = app.tags.as_ref().borrow_mut();
+
+        match commands.state.selected() {
+            Some(_) => {
+                if !commands.items.is_empty() {
+                    commands.previous();
+                    tags.previous();
+                }
+            }
+            None => {
+                namespaces.previous();
+
+                let (new_commands, new_tags) =
+                    app.get_commands_and_tags(Some(namespaces.current_item()))?;
+                commands.set_items(new_commands);
+                tags.set_items(new_tags);
+            }
+        };
+
+        Ok(None)
+    }
+
+    fn enter(app: &mut App) -> ParserResult {
+        let risk = app.get_current_risk();
+
+        let mut namespaces = app.namespaces.as_ref().borrow_mut();
+        let mut commands = app.commands.as_ref().borrow_mut();
+        let mut tags = app.tags.as_ref().borrow_mut();
+
+        match commands.state.selected() {
+            Some(_) => match app.event_state.get_confirm() {
+                Confirm::Display => {
+                    drop(namespaces);
+                    drop(commands);
+                    drop(tags);
+
+                    return KeyParser::confirm_selected(app);
+                }
+                Confirm::Hide => {
+                    if risk == Some(Risk::Safe) {
+                        drop(namespaces);
+                        drop(commands);
+                        drop(tags);
+
+                        return KeyParser::confirm_selected(app);
+                    }
+
+                    commands.is_selected = false;
+                    tags.is_selected = false;
+
+                    // Dangerous commands are confirmed by typing their tag
+                    if risk == Some(Risk::Dangerous) {
+                        app.event_state.set_mode(Mode::TypeTag);
+                        app.inputs.clear();
+                        app.cursor_position = None;
+                    }
+
+                    app.event_state.set_confirm(Confirm::Display);
+                }
+                _ => {}
+            },
+            None => {
+                if namespaces.state.selected().is_some() {
+                    namespaces.is_selected = false;
+
+                    commands.is_selected = true;
+                    tags.is_selected = true;
+
+                    commands.state.select(Some(0));
+                    tags.state.select(Some(0));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Returns the selected command to run once the terminal is restored.
+    fn confirm_selected(app: &mut App) -> ParserResult {
+        // The selection may have changed since the confirmation was shown
+        if app.event_state.get_mode() != &Mode::TypeTag && KeyParser::refuse_dangerous(app) {
+            app.event_state.set_confirm(Confirm::Hide);
+            app.commands.as_ref().borrow_mut().is_selected = true;
+            app.tags.as_ref().borrow_mut().is_selected = true;
+            return Ok(None);
+        }
+
+        let namespaces = app.namespaces.as_ref().borrow();
+        let commands = app.commands.as_ref().borrow();
+        let tags = app.tags.as_ref().borrow();
+
+        app.event_state.set_confirm(Confirm::Confirmed);
+
+        if commands.items.is_empty() {
+            return Ok(None);
+        }
+
+        if namespaces.current_item().is_workflow() {
+            app.workflow = Some(tags.current_item().clone());
+        }
+
+        Ok(Some((
+            commands.current_item().clone(),
+            tags.current_item().clone(),
+        )))
+    }
+
+    /// The command runs once its tag is typed exactly.
+    fn confirm_typed_tag(app: &mut App) -> ParserResult {
+        let typed = app
+            .inputs
+            .get(&app.config.name_config.tag)
+            .map(String::from_iter)
+            .unwrap_or_default();
+
+        if &typed != app.tags.as_ref().borrow().current_item() {
+            return Ok(None);
+        }
+
+        let result = KeyParser::confirm_selected(app);
+        app.event_state.set_mode(Mode::Normal);
+        result
+    }
+
+    /// Dangerous commands only run after typing their tag, not from the output pane or as jobs.
+    fn refuse_dangerous(app: &mut App) -> bool {
+        if app.get_current_risk() != Some(Risk::Dangerous) {
+            return false;
+        }
+
+        app.status
+            .warn(app.config.text_config.dangerous_refused.clone());
+        true
+    }
+
+    fn copy_command(app: &mut App) -> ParserResult {
+        app.copy_command(false)?;
+        Ok(None)
+    }
+
+    fn copy_substituted_command(app: &mut App) -> ParserResult {
+        app.copy_command(true)?;
+        Ok(None)
+    }
+
+    fn run_in_output(app: &mut App) -> ParserResult {
+        if KeyParser::is_current_namespace_workflow(app) || KeyParser::refuse_dangerous(app) {
+            return Ok(None);
+        }
+
+        let commands = app.commands.as_ref().borrow();
+        let tags = app.tags.as_ref().borrow();
+
+        if commands.state.selected().is_none() || commands.items.is_empty() {
+            return Ok(None);
         }
+
+        let (command, tag) = (commands.current_item().clone(), tags.current_item().clone());
+        drop(commands);
+        drop(tags);
+
+        if let Err(e) = app.run_in_output(&command, &tag) {
+            app.status.error(e);
+        }
+
+        Ok(None)
+    }
+
+    fn start_job(app: &mut App) -> ParserResult {
+        if KeyParser::is_current_namespace_workflow(app) || KeyParser::refuse_dangerous(app) {
+            return Ok(None);
+        }
+
+        let commands = app.commands.as_ref().borrow();
+        let tags = app.tags.as_ref().borrow();
+
+        if commands.state.selected().is_none() || commands.items.is_empty() {
+            return Ok(None);
+        }
+
+        let (command, tag) = (commands.current_item().clone(), tags.current_item().clone());
+        drop(commands);
+        drop(tags);
+
+        if let Err(e) = app.start_job(&command, &tag) {
+            app.status.error(e);
+        }
+
+        Ok(None)
+    }
+
+    /// Watches the selected command, or stops watching.
+    fn toggle_watch(app: &mut App) -> ParserResult {
+        if let Some(watch) = app.watch.take() {
+            app.status.info(format!("Stopped watching {}", watch.tag));
+            return Ok(None);
+        }
+
+        if KeyParser::is_current_namespace_workflow(app) || KeyParser::refuse_dangerous(app) {
+            return Ok(None);
+        }
+
+        let commands = app.commands.as_ref().borrow();
+        let tags = app.tags.as_ref().borrow();
+
+        if commands.state.selected().is_none() || commands.items.is_empty() {
+            return Ok(None);
+        }
+
+        let (command, tag) = (commands.current_item().clone(), tags.current_item().clone());
+        drop(commands);
+        drop(tags);
+
+        if let Err(e) = app.start_watch(&command, &tag) {
+            app.status.error(e);
+        }
+
+        Ok(None)
+    }
+
+    fn change_to_fanout_mode(app: &mut App) -> ParserResult {
+        if KeyParser::is_current_namespace_workflow(app) || KeyParser::refuse_dangerous(app) {
+            return Ok(None);
+        }
+
+        let commands = app.commands.as_ref().borrow();
+
+        if commands.state.selected().is_none() || commands.items.is_empty() {
+            return Ok(None);
+        }
+
+        app.event_state = EventState::default();
+        app.event_state.set_mode(Mode::FanOut);
+        app.event_state.set_sub_mode(SubMode::Directories);
+        app.inputs.clear();
+        app.cursor_position = None;
+
+        Ok(None)
+    }
+
+    fn confirm_fanout(app: &mut App) -> ParserResult {
+        let spec = String::from_iter(
+            app.inputs
+                .remove(&app.config.name_config.directories)
+                .unwrap_or_default(),
+        );
+        let (command, tag) = {
+            let commands = app.commands.as_ref().borrow();
+            let tags = app.tags.as_ref().borrow();
+
+            (commands.current_item().clone(), tags.current_item().clone())
+        };
+
+        app.event_state = EventState::default();
+        app.cursor_position = None;
+
+        match app.start_fanout(&command, &tag, &spec) {
+            Ok(true) => app.set_tab(Tab::FanOut),
+            Ok(false) => {}
+            Err(e) => app.status.error(e),
+        }
+
+        Ok(None)
+    }
+
+    fn stop_output(app: &mut App) -> ParserResult {
+        if let Some(process) = app.output.as_mut() {
+            process.kill();
+        }
+
+        Ok(None)
+    }
+
+    fn scroll_output_back(app: &mut App) -> ParserResult {
+        KeyParser::scroll_output(app, 1)
+    }
+
+    fn scroll_output_forward(app: &mut App) -> ParserResult {
+        KeyParser::scroll_output(app, -1)
+    }
+
+    /// A positive delta scrolls back in the output.
+    fn scroll_output(app: &mut App, delta: i32) -> ParserResult {
+        let process = match &app.output {
+            Some(process) => process,
+            None => return Ok(None),
+        };
+
+        app.output_scroll =
+            KeyParser::get_scroll_back(app.output_scroll, delta, process.lines.len());
+
+        Ok(None)
+    }
+
+    /// A positive delta scrolls back, `None` is returned once back at the end of the lines.
+    fn get_scroll_back(scroll: Option<u16>, delta: i32, lines: usize) -> Option<u16> {
+        let back = (scroll.unwrap_or(0) as i32 + delta).clamp(0, lines as i32);
+
+        if back == 0 {
+            None
+        } else {
+            Some(back as u16)
+        }
+    }
+
+    fn switch_profile(app: &mut App) -> ParserResult {
+        app.switch_profile()?;
+
+        Ok(None)
+    }
+
+    fn change_to_add_namespace_mode(app: &mut App) -> ParserResult {
+        app.event_state = EventState::default();
+        app.event_state.set_mode(Mode::Add);
+        app.event_state.set_sub_mode(SubMode::Namespace);
+        app.event_state.set_event_type(EventType::Namespace);
+        app.inputs.clear();
+        app.cursor_position = None;
+
+        Ok(None)
+    }
+
+    fn change_to_rename_mode(app: &mut App) -> ParserResult {
+        app.event_state = EventState::default();
+        app.event_state.set_mode(Mode::Add);
+        app.event_state.set_sub_mode(SubMode::Rename);
+        app.event_state.set_event_type(EventType::Namespace);
+        app.inputs.clear();
+        app.cursor_position = None;
+
+        Ok(None)
+    }
+
+    /// An empty name keeps the namespace as it is.
+    fn confirm_rename(app: &mut App) -> ParserResult {
+        let name = app
+            .inputs
+            .remove(&app.config.name_config.namespace)
+            .map(String::from_iter)
+            .unwrap_or_default();
+
+        app.event_state = EventState::default();
+        app.cursor_position = None;
+
+        if !name.trim().is_empty() {
+            app.rename_namespace(name.trim())?;
+        }
+
+        Ok(None)
+    }
+
+    fn change_to_prefix_mode(app: &mut App) -> ParserResult {
+        app.event_state = EventState::default();
+        app.event_state.set_mode(Mode::Add);
+        app.event_state.set_sub_mode(SubMode::Prefix);
+        app.inputs.clear();
+        app.cursor_position = None;
+
+        Ok(None)
+    }
+
+    /// An empty prefix keeps the tags as they are.
+    fn confirm_prefix(app: &mut App) -> ParserResult {
+        let prefix = app
+            .inputs
+            .remove(&app.config.name_config.tag)
+            .map(String::from_iter)
+            .unwrap_or_default();
+
+        app.event_state = EventState::default();
+        app.cursor_position = None;
+
+        if !prefix.trim().is_empty() {
+            app.prefix_marked_tags(prefix.trim())?;
+        }
+
+        Ok(None)
+    }
+
+    fn move_command(app: &mut App) -> ParserResult {
+        app.open_picker(PickAction::Move)?;
+        Ok(None)
+    }
+
+    fn duplicate_command(app: &mut App) -> ParserResult {
+        app.open_picker(PickAction::Duplicate)?;
+        Ok(None)
+    }
+
+    fn merge_namespace(app: &mut App) -> ParserResult {
+        app.open_picker(PickAction::Merge)?;
+        Ok(None)
+    }
+
+    fn next_picked(app: &mut App) -> ParserResult {
+        if let Some(picker) = &mut app.picker {
+            picker.namespaces.next();
+        }
+
+        Ok(None)
+    }
+
+    fn previous_picked(app: &mut App) -> ParserResult {
+        if let Some(picker) = &mut app.picker {
+            picker.namespaces.previous();
+        }
+
+        Ok(None)
+    }
+
+    fn confirm_pick(app: &mut App) -> ParserResult {
+        app.apply_pick()?;
+        Ok(None)
+    }
+
+    fn change_to_add_command_mode(app: &mut App) -> ParserResult {
+        if KeyParser::is_current_namespace_read_only(app) {
+            return Ok(None);
+        }
+
+        app.event_state = EventState::default();
+        app.event_state.set_mode(Mode::Add);
+        app.event_state.set_sub_mode(SubMode::Command);
+        app.event_state.set_event_type(EventType::Command);
+        app.inputs.clear();
+        app.cursor_position = None;
+
+        Ok(None)
+    }
+
+    fn change_to_delete_mode(app: &mut App) -> ParserResult {
+        let commands = app.commands.as_ref().borrow();
+        let namespaces = app.namespaces.as_ref().borrow();
+
+        if KeyParser::is_current_namespace_read_only(app) {
+            return Ok(None);
+        }
+
+        if commands.is_selected || namespaces.is_selected {
+            app.event_state.set_mode(Mode::Delete);
+            app.event_state.set_confirm(Confirm::Display);
+        }
+
+        Ok(None)
+    }
+
+    fn change_to_fork_mode(app: &mut App) -> ParserResult {
+        let commands = app.commands.as_ref().borrow();
+        let namespaces = app.namespaces.as_ref().borrow();
+
+        if !KeyParser::is_current_namespace_read_only(app)
+            || KeyParser::is_current_namespace_workflow(app)
+            || commands.items.is_empty()
+        {
+            return Ok(None);
+        }
+
+        if commands.is_selected || namespaces.is_selected {
+            app.event_state.set_mode(Mode::Fork);
+            app.event_state.set_confirm(Confirm::Display);
+        }
+
+        Ok(None)
+    }
+
+    fn process_fork_mode(key_code: KeyCode, app: &mut App) -> ParserResult {
+        if app.event_state.get_confirm() != &Confirm::Display {
+            app.event_state.set_confirm(Confirm::Confirmed);
+            return Ok(None);
+        }
+
+        KeyParser::dispatch(FORK_BINDINGS, key_code, KeyModifiers::NONE, app).unwrap_or(Ok(None))
+    }
+
+    fn confirm_fork(app: &mut App) -> ParserResult {
+        let namespace = app.namespaces.as_ref().borrow().current_item().clone();
+        let commands = app.commands.as_ref().borrow();
+        let tags = app.tags.as_ref().borrow();
+
+        // With the commands focused only the selected one is copied, the whole namespace otherwise
+        let selection = if commands.is_selected && commands.state.selected().is_some() {
+            vec![(commands.current_item().clone(), tags.current_item().clone())]
+        } else {
+            commands
+                .items
+                .iter()
+                .cloned()
+                .zip(tags.items.iter().cloned())
+                .collect()
+        };
+
+        // Undoing removes the namespace when the copy created it
+        let is_new = app.db.get_namespace(&namespace.name)?.is_none();
+        let copied = app.fork_commands(&namespace, &selection)?;
+        app.changes.record(match is_new {
+            true => vec![Change::Trash(Item::Namespace(namespace.name.clone()))],
+            false => copied
+                .iter()
+                .map(|tag| Change::Trash(Item::Command(tag.clone())))
+                .collect(),
+        });
+        app.status.info(format!(
+            "{} of {} commands copied into your library",
+            copied.len(),
+            selection.len()
+        ));
+
+        // Keep the cursor on the forked namespace, the copy may be inserted before it
+        let namespaces = app.get_namespaces()?;
+        let mut app_namespaces = app.namespaces.as_ref().borrow_mut();
+        let index = namespaces.iter().position(|item| item == &namespace);
+
+        app_namespaces.set_items(namespaces);
+        if app_namespaces.state.selected().is_some() {
+            app_namespaces.state.select(index);
+        }
+
+        app.event_state.set_confirm(Confirm::Confirmed);
+        app.event_state.set_mode(Mode::Normal);
+
+        Ok(None)
+    }
+
+    fn is_current_namespace_read_only(app: &App) -> bool {
+        let namespaces = app.namespaces.as_ref().borrow();
+
+        namespaces
+            .items
+            .get(namespaces.current())
+            .is_some_and(|namespace| namespace.is_read_only())
+    }
+
+    fn is_current_namespace_workflow(app: &App) -> bool {
+        let namespaces = app.namespaces.as_ref().borrow();
+
+        namespaces
+            .items
+            .get(namespaces.current())
+            .is_some_and(|namespace| namespace.is_workflow())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::command_file::FileCommand;
+    use crate::db::project::Project;
+
+    #[test]
+    fn test_get_label() {
+        let label = |keys| Binding::new(keys, "", KeyParser::always, KeyParser::quit).get_label();
+
+        assert_eq!(label(RIGHT), "l/→");
+        assert_eq!(label(CONFIRM), "Enter/Space");
+        assert_eq!(label(&[KeyCode::Char('?')]), "?");
+        let binding = Binding::new(
+            &[KeyCode::Char('1')],
+            "",
+            KeyParser::always,
+            KeyParser::quit,
+        );
+        assert_eq!(binding.with_alt().get_label(), "Alt-1");
+    }
+
+    #[test]
+    fn test_ctrl_keys_wait_for_popups() {
+        let mut app = App::in_memory().unwrap();
+        let ctrl_r = KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL);
+        app.changes
+            .push_redo(vec![Change::Trash(Item::Command("unknown".to_string()))]);
+
+        app.event_state.set_confirm(Confirm::Display);
+        KeyParser::parse_event(ctrl_r, &mut app).unwrap();
+        assert!(app.changes.can_redo());
+
+        app.event_state.set_confirm(Confirm::Hide);
+        app.event_state.set_mode(Mode::Add);
+        KeyParser::parse_event(ctrl_r, &mut app).unwrap();
+        assert!(app.changes.can_redo());
+
+        app.event_state.set_mode(Mode::Normal);
+        assert!(KeyParser::parse_event(ctrl_r, &mut app).is_err());
+        assert!(!app.changes.can_redo());
+    }
+
+    #[test]
+    fn test_number_keys() {
+        let mut app = App::in_memory().unwrap();
+        let namespace = "shell".to_string();
+        app.db.add_namespace(&namespace).unwrap();
+        for tag in ["first", "second"] {
+            let command = format!("echo {}", tag);
+            app.db
+                .add_command_and_tag(Some(&command), Some(&tag.to_string()), &namespace)
+                .unwrap();
+        }
+        app.db.set_favorite("second", true).unwrap();
+        app.set_tab(Tab::Favorites);
+        app.set_tab(Tab::Library);
+        app.namespaces.as_ref().borrow_mut().state.select(Some(0));
+
+        let alt_1 = KeyEvent::new(KeyCode::Char('1'), KeyModifiers::ALT);
+        app.event_state.set_confirm(Confirm::Display);
+        KeyParser::parse_event(alt_1, &mut app).unwrap();
+        assert!(app.tags.as_ref().borrow().items.is_empty());
+
+        app.event_state.set_confirm(Confirm::Hide);
+        KeyParser::parse_event(alt_1, &mut app).unwrap();
+        assert_eq!(app.tags.as_ref().borrow().current_item(), "second");
+
+        app.event_state = EventState::default();
+        let key_2 = KeyEvent::new(KeyCode::Char('2'), KeyModifiers::NONE);
+        app.event_state.set_confirm(Confirm::Display);
+        KeyParser::parse_event(key_2, &mut app).unwrap();
+        assert!(app.event_state.get_tab() == &Tab::Library);
+
+        app.event_state.set_confirm(Confirm::Hide);
+        KeyParser::parse_event(key_2, &mut app).unwrap();
+        assert!(app.event_state.get_tab() == &Tab::History);
+    }
+
+    fn press(app: &mut App, keys: &str) {
+        for c in keys.chars() {
+            let key_code = match c {
+                '\n' => KeyCode::Enter,
+                '\x08' => KeyCode::Backspace,
+                c => KeyCode::Char(c),
+            };
+            KeyParser::parse_event(KeyEvent::new(key_code, KeyModifiers::NONE), app).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_add_command_dispatch() {
+        let mut app = App::in_memory().unwrap();
+        app.db.add_namespace(&"shell".to_string()).unwrap();
+        let namespaces = app.get_namespaces().unwrap();
+        app.namespaces.as_ref().borrow_mut().set_items(namespaces);
+        app.namespaces.as_ref().borrow_mut().state.select(Some(0));
+
+        KeyParser::change_to_add_command_mode(&mut app).unwrap();
+        assert!(KeyParser::get_table(&app).len() == ADD_BINDINGS.len());
+        press(&mut app, "lss\x08\nlist\n");
+        assert!(app.event_state.get_confirm() == &Confirm::Display);
+        assert_eq!(
+            KeyParser::get_hints(&app).split("  ").next(),
+            Some("Enter: add")
+        );
+
+        press(&mut app, "\n");
+        assert!(app.event_state.get_mode() == &Mode::Normal);
+        assert_eq!(app.db.get_command("list").unwrap(), Some("ls".to_string()));
+    }
+
+    #[test]
+    fn test_add_command_closes_on_other_keys() {
+        let mut app = App::in_memory().unwrap();
+        app.db.add_namespace(&"shell".to_string()).unwrap();
+        let namespaces = app.get_namespaces().unwrap();
+        app.namespaces.as_ref().borrow_mut().set_items(namespaces);
+        app.namespaces.as_ref().borrow_mut().state.select(Some(0));
+
+        KeyParser::change_to_add_command_mode(&mut app).unwrap();
+        press(&mut app, "ls\nlist\nn");
+        assert!(app.event_state.get_mode() == &Mode::Normal);
+        assert!(!app.db.has_tag(&"list".to_string()).unwrap());
+    }
+
+    #[test]
+    fn test_prefix_marked_tags() {
+        let mut app = App::in_memory().unwrap();
+        let namespace = "docker".to_string();
+        app.db.add_namespace(&namespace).unwrap();
+        for tag in ["images", "ps"] {
+            let command = format!("docker {}", tag);
+            app.db
+                .add_command_and_tag(Some(&command), Some(&tag.to_string()), &namespace)
+                .unwrap();
+        }
+        let namespaces = app.get_namespaces().unwrap();
+        app.namespaces.as_ref().borrow_mut().set_items(namespaces);
+        app.namespaces.as_ref().borrow_mut().state.select(Some(0));
+        let (commands, tags) = app.db.get_commands_and_tags(Some(namespace)).unwrap();
+        app.commands.as_ref().borrow_mut().set_items(commands);
+        app.tags.as_ref().borrow_mut().set_items(tags);
+        app.commands.as_ref().borrow_mut().state.select(Some(0));
+
+        press(&mut app, "aTold:\n");
+        assert!(app.db.has_tag(&"old:images".to_string()).unwrap());
+        assert!(app.db.has_tag(&"old:ps".to_string()).unwrap());
+
+        press(&mut app, "u");
+        assert!(app.db.has_tag(&"ps".to_string()).unwrap());
+    }
+
+    #[test]
+    fn test_fork_focused_list() {
+        let mut app = App::in_memory().unwrap();
+        let command = |tag: &str| FileCommand {
+            tag: tag.to_string(),
+            command: format!("npm run {}", tag),
+            ..FileCommand::default()
+        };
+        app.project = Some(Project {
+            name: "web".to_string(),
+            commands: vec![command("build"), command("test")],
+        });
+        let namespaces = app.get_namespaces().unwrap();
+        let (commands, tags) = app.get_commands_and_tags(Some(&namespaces[0])).unwrap();
+        app.namespaces.as_ref().borrow_mut().set_items(namespaces);
+        app.namespaces.as_ref().borrow_mut().state.select(Some(0));
+        app.commands.as_ref().borrow_mut().set_items(commands);
+        app.tags.as_ref().borrow_mut().set_items(tags);
+
+        // A command still selected doesn't matter while the namespaces are focused
+        app.namespaces.as_ref().borrow_mut().is_selected = true;
+        app.commands.as_ref().borrow_mut().state.select(Some(0));
+        KeyParser::confirm_fork(&mut app).unwrap();
+        assert!(app.db.has_tag(&"build".to_string()).unwrap());
+        assert!(app.db.has_tag(&"test".to_string()).unwrap());
+    }
+
+    #[test]
+    fn test_confirmation_keeps_the_selection() {
+        let mut app = App::in_memory().unwrap();
+        let namespace = "shell".to_string();
+        app.db.add_namespace(&namespace).unwrap();
+        for tag in ["first", "second"] {
+            let command = format!("echo {}", tag);
+            app.db
+                .add_command_and_tag(Some(&command), Some(&tag.to_string()), &namespace)
+                .unwrap();
+        }
+        app.risks.insert("first".to_string(), Risk::Confirm);
+        app.risks.insert("second".to_string(), Risk::Dangerous);
+        let namespaces = app.get_namespaces().unwrap();
+        let (commands, tags) = app.get_commands_and_tags(Some(&namespaces[0])).unwrap();
+        app.namespaces.as_ref().borrow_mut().set_items(namespaces);
+        app.namespaces.as_ref().borrow_mut().state.select(Some(0));
+        app.commands.as_ref().borrow_mut().set_items(commands);
+        app.tags.as_ref().borrow_mut().set_items(tags);
+        press(&mut app, "l\n");
+        assert!(app.event_state.get_confirm() == &Confirm::Display);
+
+        let end = KeyEvent::new(KeyCode::End, KeyModifiers::NONE);
+        KeyParser::parse_event(end, &mut app).unwrap();
+        assert_eq!(app.tags.as_ref().borrow().current_item(), "first");
+
+        // A dangerous command selected behind the confirmation is refused
+        app.commands.as_ref().borrow_mut().state.select(Some(1));
+        app.tags.as_ref().borrow_mut().state.select(Some(1));
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        assert!(KeyParser::parse_event(enter, &mut app).unwrap().is_none());
+        assert!(app.event_state.get_confirm() == &Confirm::Hide);
+    }
+
+    #[test]
+    fn test_type_tag_dispatch() {
+        let mut app = App::in_memory().unwrap();
+        app.set_tab(Tab::Library);
+        app.event_state.set_mode(Mode::TypeTag);
+        app.event_state.set_confirm(Confirm::Display);
+
+        press(&mut app, "rmm\x08");
+        assert_eq!(
+            app.inputs.get(&app.config.name_config.tag).unwrap(),
+            &vec!['r', 'm']
+        );
+        assert!(KeyParser::get_table(&app).len() == TYPE_TAG_BINDINGS.len());
     }
 }
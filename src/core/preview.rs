@@ -0,0 +1,108 @@
+use crate::core::cmd::Cmd;
+use crate::core::params::{self, Param, Source};
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::PathBuf;
+
+/// What a command line executes once its parameters are substituted, without running it.
+pub struct Preview {
+    pub program: String,
+    pub args: Vec<String>,
+    pub dir: PathBuf,
+    /// `NAME=value` assignments leading the command line.
+    pub env: Vec<(String, String)>,
+    pub params: Vec<Param>,
+}
+
+impl Preview {
+    pub fn new(command: &str, params: &BTreeMap<String, String>) -> Result<Self, Box<dyn Error>> {
+        let (line, params) = params::resolve(command, params)?;
+        let cmd = Cmd::new(&line)?;
+
+        let env = line
+            .split_whitespace()
+            .map_while(|word| {
+                let (name, value) = word.split_once('=')?;
+                let valid = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                    && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+                valid.then(|| (name.to_string(), value.to_string()))
+            })
+            .collect();
+
+        Ok(Preview {
+            program: cmd.get_program().to_string(),
+            args: cmd.get_args().to_vec(),
+            dir: std::env::current_dir()?,
+            env,
+            params,
+        })
+    }
+
+    pub fn get_lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!("Program: {}", self.program),
+            format!(
+                "Arguments: {}",
+                self.args
+                    .iter()
+                    .map(|arg| format!("{:?}", arg))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ),
+            format!("Directory: {}", self.dir.display()),
+        ];
+
+        if self.env.is_empty() {
+            lines.push("Environment: inherited".to_string());
+        } else {
+            let env = self
+                .env
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect::<Vec<_>>();
+            lines.push(format!("Environment: {}", env.join(" ")));
+        }
+
+        for param in &self.params {
+            let source = match &param.source {
+                Source::Argument => "argument".to_string(),
+                Source::Environment(var) => format!("${}", var),
+                Source::Default => "default".to_string(),
+            };
+            lines.push(format!(
+                "Parameter: {} = {} ({})",
+                param.name, param.value, source
+            ));
+        }
+
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_preview() {
+        let params = BTreeMap::from([("target".to_string(), "release".to_string())]);
+        let preview = Preview::new("RUST_LOG=debug cargo build --{{target}}", &params).unwrap();
+
+        assert_eq!(preview.program, "sh");
+        assert_eq!(
+            preview.args,
+            vec![
+                "-c".to_string(),
+                "RUST_LOG=debug cargo build --release".to_string()
+            ]
+        );
+        assert_eq!(
+            preview.env,
+            vec![("RUST_LOG".to_string(), "debug".to_string())]
+        );
+        assert!(preview
+            .get_lines()
+            .contains(&"Parameter: target = release (argument)".to_string()));
+    }
+}
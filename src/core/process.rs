@@ -0,0 +1,385 @@
+use crate::core::cmd::{self, Cmd};
+use crate::core::hooks::{HookContext, PreHooks};
+use crate::core::runner::{Outcome, RunOptions};
+use std::error::Error;
+use std::io::{BufRead, BufReader, Read};
+use std::os::unix::process::ExitStatusExt;
+use std::path::Path;
+use std::process::ExitStatus;
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often the thread of a command checks its timeout and the cancellation of its retries.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+/// Timeout and retries of a run in the interface, as in the terminal.
+#[derive(Clone, Copy, Default)]
+pub struct Limits {
+    /// The process group is terminated once an attempt runs longer, then killed.
+    pub timeout: Option<Duration>,
+    pub retries: u32,
+    /// Wait before the first retry, doubled for every next one.
+    pub retry_delay: Duration,
+}
+
+impl Limits {
+    pub fn new(options: &RunOptions, retry_delay: u64) -> Self {
+        Limits {
+            timeout: options.get_timeout(),
+            retries: options.retries.unwrap_or(0),
+            retry_delay: Duration::from_secs(retry_delay),
+        }
+    }
+}
+
+/// A command run with piped outputs on its own thread, after its pre-hooks. Its lines are
+/// collected while the TUI keeps running.
+pub struct Process {
+    pub tag: String,
+    pub lines: Vec<(Stream, String)>,
+    receiver: Receiver<Event>,
+    group: Arc<Mutex<Group>>,
+    started: Instant,
+    finished: Option<(ExitStatus, Duration)>,
+    timed_out: bool,
+    /// Post-hooks to run once finished.
+    hooks: Option<HookContext>,
+}
+
+enum Event {
+    Line(Stream, String),
+    /// Exit status of the last attempt, and whether it timed out.
+    Exit(ExitStatus, bool),
+}
+
+/// Process group of the running attempt. Signals sent by the user cancel the next attempts,
+/// and the command when it isn't spawned yet.
+#[derive(Default)]
+struct Group {
+    pgid: Option<i32>,
+    cancelled: bool,
+}
+
+impl Process {
+    /// Runs in `dir`, or in the working directory of cm. A failing pre-hook or a command that
+    /// can't be spawned finishes the process with the error as its output.
+    pub fn spawn_in(
+        command: &str,
+        tag: &str,
+        dir: Option<&Path>,
+        pre_hooks: Option<PreHooks>,
+        limits: Limits,
+    ) -> Result<Self, Box<dyn Error>> {
+        let cmd = Cmd::new(command)?;
+        let dir = dir.map(Path::to_path_buf);
+        let group = Arc::new(Mutex::new(Group::default()));
+        let (sender, receiver) = channel();
+
+        let worker_group = group.clone();
+        thread::spawn(move || {
+            let (status, timed_out) = Process::run(
+                &cmd,
+                dir.as_deref(),
+                pre_hooks,
+                limits,
+                &worker_group,
+                &sender,
+            )
+            .unwrap_or_else(|(error, code)| {
+                let _ = sender.send(Event::Line(Stream::Stderr, error));
+                (ExitStatus::from_raw(code << 8), false)
+            });
+            let _ = sender.send(Event::Exit(status, timed_out));
+        });
+
+        Ok(Process {
+            tag: tag.to_string(),
+            lines: Vec::new(),
+            receiver,
+            group,
+            started: Instant::now(),
+            finished: None,
+            timed_out: false,
+            hooks: None,
+        })
+    }
+
+    /// Runs the attempts until one succeeds, the retries are exhausted or the user signals the
+    /// command. Errors come with the exit code reported: 1 for a pre-hook, otherwise 127 as a
+    /// shell not finding a command.
+    fn run(
+        cmd: &Cmd,
+        dir: Option<&Path>,
+        pre_hooks: Option<PreHooks>,
+        limits: Limits,
+        group: &Mutex<Group>,
+        sender: &Sender<Event>,
+    ) -> Result<(ExitStatus, bool), (String, i32)> {
+        if let Some(pre_hooks) = pre_hooks {
+            pre_hooks.run().map_err(|e| (e, 1))?;
+        }
+
+        let started = Instant::now();
+        let mut delay = limits.retry_delay;
+        let mut attempt = 0;
+
+        loop {
+            let (status, timed_out) = Process::attempt(cmd, dir, limits.timeout, group, sender)?;
+            if status.success() || attempt == limits.retries {
+                return Ok((status, timed_out));
+            }
+
+            attempt += 1;
+            let outcome = Outcome {
+                exit_code: status.code(),
+                duration: started.elapsed(),
+                timed_out,
+            };
+            let _ = sender.send(Event::Line(
+                Stream::Stderr,
+                format!(
+                    "==> {}, retry {}/{} in {}s",
+                    outcome.describe(),
+                    attempt,
+                    limits.retries,
+                    delay.as_secs()
+                ),
+            ));
+
+            if !Process::wait_retry(delay, group) {
+                return Ok((status, timed_out));
+            }
+            delay *= 2;
+        }
+    }
+
+    /// Spawns the command and waits for it, the process group is terminated after `timeout`
+    /// then killed like in the terminal. Returns the exit status and whether it timed out.
+    fn attempt(
+        cmd: &Cmd,
+        dir: Option<&Path>,
+        timeout: Option<Duration>,
+        group: &Mutex<Group>,
+        sender: &Sender<Event>,
+    ) -> Result<(ExitStatus, bool), (String, i32)> {
+        let mut child = {
+            let mut group = group.lock().unwrap();
+            if group.cancelled {
+                return Ok((ExitStatus::from_raw(libc::SIGKILL), false));
+            }
+
+            let child = cmd.spawn_piped(dir).map_err(|e| (e.to_string(), 127))?;
+            group.pgid = Some(child.id() as i32);
+            child
+        };
+        let pgid = child.id() as i32;
+
+        if let Some(stdout) = child.stdout.take() {
+            Process::forward(stdout, Stream::Stdout, sender.clone());
+        }
+        if let Some(stderr) = child.stderr.take() {
+            Process::forward(stderr, Stream::Stderr, sender.clone());
+        }
+
+        let started = Instant::now();
+        let mut signal = None;
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break Ok(status),
+                Ok(None) => {}
+                Err(e) => break Err((e.to_string(), 127)),
+            }
+
+            let next = timeout
+                .and_then(|timeout| cmd::get_timeout_signal(started.elapsed(), timeout, signal));
+            if let Some(next) = next {
+                unsafe {
+                    libc::kill(-pgid, next);
+                }
+                signal = Some(next);
+            }
+
+            thread::sleep(POLL_INTERVAL);
+        };
+        group.lock().unwrap().pgid = None;
+
+        status.map(|status| (status, signal.is_some()))
+    }
+
+    /// Waits before a retry, false when the user signaled the command meanwhile.
+    fn wait_retry(delay: Duration, group: &Mutex<Group>) -> bool {
+        let started = Instant::now();
+        while started.elapsed() < delay {
+            if group.lock().unwrap().cancelled {
+                return false;
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+
+        !group.lock().unwrap().cancelled
+    }
+
+    /// A command that was never started, finished with the error as its output.
+    pub fn failed(tag: &str, error: &str, exit_code: i32) -> Self {
+        let (_, receiver) = channel();
+
+        Process {
+            tag: tag.to_string(),
+            lines: vec![(Stream::Stderr, error.to_string())],
+            receiver,
+            group: Arc::new(Mutex::new(Group::default())),
+            started: Instant::now(),
+            finished: Some((ExitStatus::from_raw(exit_code << 8), Duration::ZERO)),
+            timed_out: false,
+            hooks: None,
+        }
+    }
+
+    pub fn with_hooks(mut self, context: HookContext) -> Self {
+        self.hooks = Some(context);
+        self
+    }
+
+    pub fn get_hooks(&self) -> Option<&HookContext> {
+        self.hooks.as_ref()
+    }
+
+    fn forward(output: impl Read + Send + 'static, stream: Stream, sender: Sender<Event>) {
+        thread::spawn(move || {
+            for line in BufReader::new(output).lines().map_while(Result::ok) {
+                if sender.send(Event::Line(stream, line)).is_err() {
+                    break;
+                }
+            }
+        });
+    }
+
+    /// Collects the pending lines and the exit status, to call on every tick.
+    /// Returns true when the process has just finished.
+    pub fn poll(&mut self) -> bool {
+        let mut has_finished = false;
+        for event in self.receiver.try_iter() {
+            match event {
+                Event::Line(stream, line) => self.lines.push((stream, line)),
+                Event::Exit(status, timed_out) => {
+                    self.finished = Some((status, self.started.elapsed()));
+                    self.timed_out = timed_out;
+                    has_finished = true;
+                }
+            }
+        }
+
+        has_finished
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.finished.is_none()
+    }
+
+    pub fn get_exit_code(&self) -> Option<i32> {
+        self.finished.and_then(|(status, _)| status.code())
+    }
+
+    pub fn get_duration(&self) -> Duration {
+        match self.finished {
+            Some((_, duration)) => duration,
+            None => self.started.elapsed(),
+        }
+    }
+
+    pub fn is_timed_out(&self) -> bool {
+        self.timed_out
+    }
+
+    /// 0 before the command is spawned, between retries and once it has exited.
+    pub fn get_pid(&self) -> u32 {
+        self.group.lock().unwrap().pgid.unwrap_or_default() as u32
+    }
+
+    /// Sends a signal to the process group of the command, ignored once it has exited.
+    /// No attempt is started anymore, the command isn't run when its pre-hooks still run.
+    pub fn signal(&self, signal: i32) {
+        if !self.is_running() {
+            return;
+        }
+
+        let mut group = self.group.lock().unwrap();
+        group.cancelled = true;
+        if let Some(pgid) = group.pgid {
+            unsafe {
+                libc::kill(-pgid, signal);
+            }
+        }
+    }
+
+    pub fn kill(&mut self) {
+        self.signal(libc::SIGKILL);
+    }
+}
+
+impl Drop for Process {
+    /// The thread of the command reaps it once killed.
+    fn drop(&mut self) {
+        self.kill();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wait(process: &mut Process) {
+        let started = Instant::now();
+        while !process.poll() {
+            assert!(started.elapsed() < Duration::from_secs(10));
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn test_retries() {
+        let limits = Limits {
+            retries: 2,
+            ..Limits::default()
+        };
+        let mut process = Process::spawn_in("echo run; false", "t", None, None, limits).unwrap();
+
+        wait(&mut process);
+        assert_eq!(process.get_exit_code(), Some(1));
+        let runs = process
+            .lines
+            .iter()
+            .filter(|(_, line)| line == "run")
+            .count();
+        assert_eq!(runs, 3);
+    }
+
+    #[test]
+    fn test_timeout_terminates() {
+        let limits = Limits {
+            timeout: Some(Duration::from_millis(200)),
+            ..Limits::default()
+        };
+        let mut process = Process::spawn_in(
+            "trap 'echo terminated; exit 3' TERM; sleep 5 & wait",
+            "t",
+            None,
+            None,
+            limits,
+        )
+        .unwrap();
+
+        wait(&mut process);
+        assert!(process.is_timed_out());
+        assert_eq!(process.get_exit_code(), Some(3));
+        assert!(process.lines.iter().any(|(_, line)| line == "terminated"));
+    }
+}
@@ -0,0 +1,134 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+
+/// How a command is confirmed before running. Commands without a stored level are detected,
+/// `Safe` is only set explicitly.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, PartialOrd)]
+#[serde(rename_all = "lowercase")]
+pub enum Risk {
+    /// Runs without confirmation.
+    Safe,
+    Confirm,
+    /// The tag has to be typed to confirm.
+    Dangerous,
+}
+
+/// Destructive commands, their words have to appear in this order in a part of the command line.
+/// A word ending with `=` or `.` matches as a prefix.
+const DANGEROUS_PATTERNS: [&[&str]; 18] = [
+    &["docker", "rmi", "-f"],
+    &["docker", "rm", "-f"],
+    &["docker", "system", "prune"],
+    &["docker", "volume", "rm"],
+    &["docker", "volume", "prune"],
+    &["docker", "image", "prune"],
+    &["drop", "table"],
+    &["drop", "database"],
+    &["truncate", "table"],
+    &["git", "push", "--force"],
+    &["git", "push", "-f"],
+    &["git", "reset", "--hard"],
+    &["git", "clean", "-f"],
+    &["kubectl", "delete"],
+    &["chmod", "-r", "777"],
+    &["mkfs"],
+    &["mkfs."],
+    &["dd", "if="],
+];
+
+impl Risk {
+    pub fn from_name(name: &str) -> Result<Self, Box<dyn Error>> {
+        match name {
+            "safe" => Ok(Risk::Safe),
+            "confirm" => Ok(Risk::Confirm),
+            "dangerous" => Ok(Risk::Dangerous),
+            _ => Err(format!(
+                "Unknown risk level: {}, expected safe, confirm or dangerous",
+                name
+            )
+            .into()),
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        match self {
+            Risk::Safe => "safe",
+            Risk::Confirm => "confirm",
+            Risk::Dangerous => "dangerous",
+        }
+    }
+
+    pub fn detect(command: &str) -> Self {
+        let command = command.to_lowercase();
+
+        let dangerous = command.split([';', '&', '|', '(', ')']).any(|part| {
+            let words = part
+                .split_whitespace()
+                .map(|word| word.trim_matches(['\'', '"']))
+                .collect::<Vec<_>>();
+
+            DANGEROUS_PATTERNS
+                .iter()
+                .any(|pattern| Risk::contains_in_order(&words, pattern))
+                || Risk::has_recursive_force_rm(&words)
+        });
+
+        if dangerous {
+            return Risk::Dangerous;
+        }
+
+        Risk::Confirm
+    }
+
+    fn contains_in_order(words: &[&str], pattern: &[&str]) -> bool {
+        let mut words = words.iter();
+
+        pattern.iter().all(|expected| {
+            words.any(|word| {
+                word == expected || (expected.ends_with(['=', '.']) && word.starts_with(expected))
+            })
+        })
+    }
+
+    /// `rm` with both the recursive and force flags, in any order or form.
+    fn has_recursive_force_rm(words: &[&str]) -> bool {
+        let flags = match words.iter().position(|word| *word == "rm") {
+            Some(position) => words[position..]
+                .iter()
+                .filter(|word| word.starts_with('-'))
+                .collect::<Vec<_>>(),
+            None => return false,
+        };
+
+        let has = |short: char, long: &str| {
+            flags
+                .iter()
+                .any(|flag| **flag == long || (!flag.starts_with("--") && flag.contains(short)))
+        };
+
+        has('r', "--recursive") && has('f', "--force")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        for command in [
+            "rm -rf target",
+            "cd /tmp && rm -r -f build",
+            "rm --recursive --force ~/.cache",
+            "docker  RMI -f $(docker images -aq)",
+            "psql -c 'DROP TABLE users'",
+            "git push origin main --force",
+        ] {
+            assert_eq!(Risk::detect(command), Risk::Dangerous, "{}", command);
+        }
+
+        for command in ["rm -f file.txt", "cd ~/ && $SHELL", "git push origin main"] {
+            assert_eq!(Risk::detect(command), Risk::Confirm, "{}", command);
+        }
+    }
+}
@@ -0,0 +1,217 @@
+use crate::core::cmd::Cmd;
+use crate::core::hooks::{self, HookContext};
+use crate::core::settings::{expand_home, NotifySettings, Settings};
+use crate::core::workflow::StepResult;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Limits and working directory of the runs of a command, unset by default.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct RunOptions {
+    /// Seconds before the process group of the command is killed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    /// Runs again after a failure, waiting twice as long before every retry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+    /// Directory the command runs in, the working directory of cm when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dir: Option<String>,
+}
+
+/// Result of a run in the terminal, retries included.
+pub struct Outcome {
+    pub exit_code: Option<i32>,
+    pub duration: Duration,
+    pub timed_out: bool,
+}
+
+impl RunOptions {
+    /// Sets `timeout`, `retries` or `dir` from a `cm set` value, `none` unsets it.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), Box<dyn Error>> {
+        if key == "dir" {
+            self.dir = (value != "none").then(|| value.to_string());
+            return Ok(());
+        }
+
+        let value =
+            match value {
+                "none" => None,
+                _ => Some(value.parse::<u32>().map_err(|_| {
+                    format!("Invalid {}, expected a number or none: {}", key, value)
+                })?),
+            };
+
+        match key {
+            "timeout" => self.timeout = value.map(u64::from),
+            "retries" => self.retries = value,
+            _ => return Err(format!("Unknown run option: {}", key).into()),
+        }
+
+        Ok(())
+    }
+
+    pub fn get_timeout(&self) -> Option<Duration> {
+        self.timeout.map(Duration::from_secs)
+    }
+
+    /// The directory with `~` expanded.
+    pub fn get_dir(&self) -> Option<PathBuf> {
+        self.dir.as_deref().map(expand_home)
+    }
+}
+
+impl Outcome {
+    pub fn is_success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+
+    /// Result recorded in the history.
+    pub fn to_step_result(&self, tag: &str) -> StepResult {
+        StepResult {
+            tag: tag.to_string(),
+            exit_code: self.exit_code,
+            duration: self.duration,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        let state = match self.exit_code {
+            _ if self.timed_out => "timed out".to_string(),
+            Some(code) => format!("exit {}", code),
+            None => "killed".to_string(),
+        };
+
+        format!("{} in {:.1}s", state, self.duration.as_secs_f32())
+    }
+}
+
+/// Runs a command in the terminal between its hooks, again after a failure while retries are left.
+pub fn run(
+    context: &HookContext,
+    options: &RunOptions,
+    settings: &Settings,
+) -> Result<Outcome, Box<dyn Error>> {
+    let cmd = Cmd::new(&context.command)?;
+    let dir = options.get_dir();
+    hooks::run_pre(&settings.hooks, context, true)?;
+
+    let retries = options.retries.unwrap_or(0);
+    let mut delay = Duration::from_secs(settings.run.retry_delay);
+    let started = Instant::now();
+    let mut attempt = 0;
+
+    loop {
+        let (status, timed_out) = match options.get_timeout() {
+            Some(timeout) => cmd.status_with_timeout(timeout, dir.as_deref())?,
+            None => (cmd.status(dir.as_deref())?, false),
+        };
+        let outcome = Outcome {
+            exit_code: status.code(),
+            duration: started.elapsed(),
+            timed_out,
+        };
+
+        if outcome.is_success() || attempt == retries {
+            hooks::run_post(
+                &settings.hooks,
+                context,
+                outcome.exit_code,
+                outcome.duration,
+                true,
+            );
+            return Ok(outcome);
+        }
+
+        attempt += 1;
+        println!(
+            "==> {}: {}, retry {}/{} in {}s",
+            context.tag,
+            outcome.describe(),
+            attempt,
+            retries,
+            delay.as_secs()
+        );
+
+        thread::sleep(delay);
+        delay *= 2;
+    }
+}
+
+/// Rings the bell, shows a desktop notification and runs the configured command
+/// once a command running longer than the threshold finishes.
+pub fn notify(settings: &NotifySettings, tag: &str, exit_code: Option<i32>, duration: Duration) {
+    if duration < Duration::from_secs(settings.after) {
+        return;
+    }
+
+    let state = match exit_code {
+        Some(0) => "succeeded".to_string(),
+        Some(code) => format!("failed with exit {}", code),
+        None => "was killed".to_string(),
+    };
+    let message = format!("{} {} in {:.1}s", tag, state, duration.as_secs_f32());
+
+    if settings.bell {
+        print!("\x07");
+        let _ = io::stdout().flush();
+    }
+
+    // notify-send is optional, a missing program is ignored
+    if settings.desktop {
+        let mut cmd = Command::new("notify-send");
+        cmd.args(["cm", &message]);
+        spawn_detached(cmd);
+    }
+
+    if let Some(command) = &settings.command {
+        let mut cmd = Command::new("sh");
+        cmd.args(["-c", command])
+            .env("CM_TAG", tag)
+            .env(
+                "CM_EXIT_CODE",
+                exit_code.map_or(String::new(), |code| code.to_string()),
+            )
+            .env("CM_DURATION", duration.as_secs().to_string())
+            .env("CM_MESSAGE", &message);
+        spawn_detached(cmd);
+    }
+}
+
+/// Spawns without outputs, the child is reaped in the background.
+fn spawn_detached(mut cmd: Command) {
+    let child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+
+    if let Ok(mut child) = child {
+        thread::spawn(move || child.wait());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set() {
+        let mut options = RunOptions::default();
+
+        options.set("timeout", "30").unwrap();
+        options.set("retries", "3").unwrap();
+        assert_eq!(options.get_timeout(), Some(Duration::from_secs(30)));
+        assert_eq!(options.retries, Some(3));
+
+        options.set("timeout", "none").unwrap();
+        assert_eq!(options.timeout, None);
+        assert!(options.set("retries", "-1").is_err());
+        assert!(options.set("color", "red").is_err());
+    }
+}
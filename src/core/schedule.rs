@@ -0,0 +1,184 @@
+use crate::core::hooks::HookContext;
+use crate::core::params;
+use crate::core::runner;
+use crate::core::settings::Settings;
+use crate::core::workflow::StepResult;
+use crate::db::db::Db;
+use chrono::{DateTime, Local};
+use croner::Cron;
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::thread;
+use std::time::Duration;
+
+/// Longest sleep of the scheduler, schedules edited meanwhile are read again after it.
+const MAX_SLEEP: Duration = Duration::from_secs(30);
+
+/// Displayed format of the run times.
+pub const TIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+/// A stored command run by `cm scheduler` at the times of a cron expression.
+pub struct Schedule {
+    pub expression: String,
+    cron: Cron,
+}
+
+impl Schedule {
+    /// `expression` has five fields: minute, hour, day of month, month and day of week.
+    pub fn new(tag: &str, expression: &str) -> Result<Self, Box<dyn Error>> {
+        let cron = Cron::new(expression)
+            .parse()
+            .map_err(|e| format!("Invalid schedule of {}: {}", tag, e))?;
+
+        Ok(Schedule {
+            expression: expression.to_string(),
+            cron,
+        })
+    }
+
+    /// First run strictly after `time`, `None` when the expression never matches again.
+    pub fn get_next(&self, time: &DateTime<Local>) -> Option<DateTime<Local>> {
+        self.cron.find_next_occurrence(time, false).ok()
+    }
+}
+
+/// A row of the Schedule tab, its next run is computed when the tab is opened.
+pub struct ScheduledCommand {
+    pub tag: String,
+    pub expression: String,
+    pub next_run: String,
+    /// Start time and result of the last recorded run.
+    pub last_run: Option<(String, StepResult)>,
+}
+
+impl ScheduledCommand {
+    /// Commands with a schedule, by tag.
+    pub fn load(db: &Db) -> Result<Vec<Self>, Box<dyn Error>> {
+        let now = Local::now();
+
+        db.get_schedules()?
+            .into_iter()
+            .map(|(tag, expression)| {
+                let next_run = match Schedule::new(&tag, &expression) {
+                    Ok(schedule) => format_time(schedule.get_next(&now)),
+                    Err(_) => "invalid".to_string(),
+                };
+                let last_run = db.get_last_run(&tag)?;
+
+                Ok(ScheduledCommand {
+                    tag,
+                    expression,
+                    next_run,
+                    last_run,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Runs the scheduled commands of the library when they are due, one at a time, until
+/// interrupted. Runs missed while cm wasn't running, or while another command ran, are skipped.
+pub fn run_scheduler(db: &Db, settings: &Settings) -> Result<(), Box<dyn Error>> {
+    let mut planned: HashMap<String, (Schedule, Option<DateTime<Local>>)> = HashMap::new();
+
+    loop {
+        let expressions = db.get_schedules()?;
+        planned.retain(|tag, (schedule, _)| expressions.get(tag) == Some(&schedule.expression));
+
+        let now = Local::now();
+        for (tag, expression) in &expressions {
+            if planned.contains_key(tag) {
+                continue;
+            }
+
+            match Schedule::new(tag, expression) {
+                Ok(schedule) => {
+                    let next = schedule.get_next(&now);
+                    println!(
+                        "==> {}: {}, next run {}",
+                        tag,
+                        expression,
+                        format_time(next)
+                    );
+                    planned.insert(tag.clone(), (schedule, next));
+                }
+                Err(e) => eprintln!("{}", e),
+            }
+        }
+
+        let due = planned
+            .iter()
+            .filter(|(_, (_, next))| next.is_some_and(|next| next <= now))
+            .map(|(tag, _)| tag.clone())
+            .collect::<Vec<_>>();
+
+        for tag in due {
+            if let Err(e) = run_due(db, settings, &tag) {
+                eprintln!("{}", e);
+            }
+
+            if let Some((schedule, next)) = planned.get_mut(&tag) {
+                *next = schedule.get_next(&Local::now());
+                println!("==> {}: next run {}", tag, format_time(*next));
+            }
+        }
+
+        let sleep = planned
+            .values()
+            .filter_map(|(_, next)| *next)
+            .min()
+            .and_then(|next| (next - Local::now()).to_std().ok())
+            .map_or(MAX_SLEEP, |until| until.min(MAX_SLEEP));
+        thread::sleep(sleep);
+    }
+}
+
+fn run_due(db: &Db, settings: &Settings, tag: &str) -> Result<(), Box<dyn Error>> {
+    let command = db
+        .get_command(tag)?
+        .ok_or_else(|| format!("Unknown command: {}", tag))?;
+    let command = params::substitute(&command, &BTreeMap::new())?;
+
+    println!(
+        "==> {}: started at {}",
+        tag,
+        Local::now().format(TIME_FORMAT)
+    );
+
+    let options = db.get_run_options()?.remove(tag).unwrap_or_default();
+    let namespace = db.get_tag_namespace(tag)?;
+    let context = HookContext::new(tag, &command, namespace.as_deref());
+    let outcome = runner::run(&context, &options, settings)?;
+
+    db.add_history(&outcome.to_step_result(tag), &command, None)?;
+    runner::notify(&settings.notify, tag, outcome.exit_code, outcome.duration);
+    println!("==> {}: {}", tag, outcome.describe());
+
+    Ok(())
+}
+
+pub fn format_time(time: Option<DateTime<Local>>) -> String {
+    time.map_or("never".to_string(), |time| {
+        time.format(TIME_FORMAT).to_string()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_get_next() {
+        let schedule = Schedule::new("docker:purge", "30 3 * * 1").unwrap();
+        // A Wednesday
+        let time = Local.with_ymd_and_hms(2024, 5, 15, 12, 0, 0).unwrap();
+
+        assert_eq!(
+            schedule.get_next(&time),
+            Some(Local.with_ymd_and_hms(2024, 5, 20, 3, 30, 0).unwrap())
+        );
+        assert!(Schedule::new("bad", "61 * * * *").is_err());
+        assert!(Schedule::new("bad", "every day").is_err());
+    }
+}
@@ -0,0 +1,182 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+/// User settings read from `CM_CONFIG` or `~/.cm/config.toml`, every section is optional.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Profile used when neither `--profile` nor `CM_DB` is given.
+    pub profile: Option<String>,
+    /// Database path of every named profile.
+    pub profiles: BTreeMap<String, String>,
+    pub sync: Option<SyncSettings>,
+    /// Named lists of directories or globs, used by fan-out runs as `@name`.
+    pub dir_sets: BTreeMap<String, Vec<String>>,
+    pub fanout: FanOutSettings,
+    pub run: RunSettings,
+    pub notify: NotifySettings,
+    pub hooks: HookSettings,
+    pub watch: WatchSettings,
+    pub trash: TrashSettings,
+    pub mouse: MouseSettings,
+}
+
+#[derive(Deserialize)]
+pub struct SyncSettings {
+    pub dir: String,
+    pub remote: Option<String>,
+    #[serde(default = "SyncSettings::default_branch")]
+    pub branch: String,
+}
+
+impl SyncSettings {
+    fn default_branch() -> String {
+        "main".to_string()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct FanOutSettings {
+    /// Directories running at the same time.
+    pub max_jobs: usize,
+}
+
+impl Default for FanOutSettings {
+    fn default() -> Self {
+        FanOutSettings { max_jobs: 4 }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct RunSettings {
+    /// Seconds before the first retry of a failed command, doubled for every next one.
+    pub retry_delay: u64,
+}
+
+impl Default for RunSettings {
+    fn default() -> Self {
+        RunSettings { retry_delay: 1 }
+    }
+}
+
+/// How to tell that a long command has finished.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct NotifySettings {
+    /// Seconds a command has to run before its end is notified.
+    pub after: u64,
+    pub bell: bool,
+    /// Desktop notification through `notify-send`, when installed.
+    pub desktop: bool,
+    /// Run with `sh -c`, with `CM_TAG`, `CM_EXIT_CODE`, `CM_DURATION` and `CM_MESSAGE` set.
+    pub command: Option<String>,
+}
+
+impl Default for NotifySettings {
+    fn default() -> Self {
+        NotifySettings {
+            after: 30,
+            bell: true,
+            desktop: true,
+            command: None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct WatchSettings {
+    /// Milliseconds without change before a burst of changes reruns the command.
+    pub debounce_ms: u64,
+}
+
+impl Default for WatchSettings {
+    fn default() -> Self {
+        WatchSettings { debounce_ms: 300 }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct TrashSettings {
+    /// Days deleted commands and namespaces are kept, forever with 0.
+    pub purge_after_days: u64,
+}
+
+impl Default for TrashSettings {
+    fn default() -> Self {
+        TrashSettings {
+            purge_after_days: 30,
+        }
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct MouseSettings {
+    /// Clicks and the scroll wheel in the interface, off by default to keep the selection of
+    /// the terminal.
+    pub enabled: bool,
+}
+
+/// Commands run with `sh -c` before and after the runs.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct HookSettings {
+    pub pre: Vec<String>,
+    pub post: Vec<String>,
+    /// Hooks of the commands of a namespace, by namespace name.
+    pub namespaces: BTreeMap<String, Hooks>,
+    /// Hooks of a single command, by tag.
+    pub commands: BTreeMap<String, Hooks>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct Hooks {
+    pub pre: Vec<String>,
+    pub post: Vec<String>,
+}
+
+impl Settings {
+    pub fn load() -> Result<Self, Box<dyn Error>> {
+        let path = Settings::get_path()?;
+
+        if !path.is_file() {
+            return Ok(Settings::default());
+        }
+
+        let content = std::fs::read_to_string(&path)?;
+
+        toml::from_str(&content).map_err(|e| format!("Invalid {}: {}", path.display(), e).into())
+    }
+
+    pub fn get_profile_path(&self, name: &str) -> Result<PathBuf, Box<dyn Error>> {
+        self.profiles
+            .get(name)
+            .map(|path| expand_home(path))
+            .ok_or_else(|| format!("Unknown profile: {}", name).into())
+    }
+
+    pub fn get_path() -> Result<PathBuf, Box<dyn Error>> {
+        if let Ok(path) = std::env::var("CM_CONFIG") {
+            return Ok(PathBuf::from(path));
+        }
+
+        let home = dirs::home_dir().ok_or("No home directory found")?;
+
+        Ok(home.join(".cm").join("config.toml"))
+    }
+}
+
+/// Expands a leading `~` to the home directory.
+pub fn expand_home(path: &str) -> PathBuf {
+    match (path.strip_prefix('~'), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest.trim_start_matches('/')),
+        _ => Path::new(path).to_path_buf(),
+    }
+}
@@ -0,0 +1,203 @@
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher as _};
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::mpsc::{channel, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Watches directories or globs for changes, ignoring `.git` and the paths ignored by git.
+pub struct Watcher {
+    /// Stops watching when dropped.
+    _watcher: RecommendedWatcher,
+    receiver: Receiver<notify::Result<Event>>,
+    targets: Vec<Target>,
+    debounce: Duration,
+    changed: BTreeSet<PathBuf>,
+    last_change: Option<Instant>,
+}
+
+/// A command run again in the output pane when files change.
+pub struct WatchedCommand {
+    pub tag: String,
+    pub command: String,
+    pub watcher: Watcher,
+}
+
+enum Target {
+    Path(PathBuf),
+    Glob(glob::Pattern),
+}
+
+impl Watcher {
+    /// `specs` are directories, files or globs relative to `dir`, `dir` itself when empty.
+    /// A burst of changes is reported once no change happened for `debounce`.
+    pub fn new(specs: &[String], dir: &Path, debounce: Duration) -> Result<Self, Box<dyn Error>> {
+        let (sender, receiver) = channel();
+        let mut watcher = notify::recommended_watcher(move |event| {
+            let _ = sender.send(event);
+        })?;
+
+        let specs = if specs.is_empty() {
+            vec![dir.display().to_string()]
+        } else {
+            specs.to_vec()
+        };
+
+        let mut targets = Vec::new();
+        for spec in specs {
+            let path = dir.join(&spec);
+
+            if spec.contains(['*', '?', '[']) {
+                let root = Watcher::get_glob_root(&path);
+                watcher.watch(&root, RecursiveMode::Recursive)?;
+                targets.push(Target::Glob(glob::Pattern::new(&path.to_string_lossy())?));
+            } else {
+                watcher
+                    .watch(&path, RecursiveMode::Recursive)
+                    .map_err(|e| format!("Cannot watch {}: {}", path.display(), e))?;
+                targets.push(Target::Path(path));
+            }
+        }
+
+        Ok(Watcher {
+            _watcher: watcher,
+            receiver,
+            targets,
+            debounce,
+            changed: BTreeSet::new(),
+            last_change: None,
+        })
+    }
+
+    /// Deepest directory of a glob without pattern characters.
+    fn get_glob_root(path: &Path) -> PathBuf {
+        path.components()
+            .take_while(|component| {
+                !component
+                    .as_os_str()
+                    .to_string_lossy()
+                    .contains(['*', '?', '['])
+            })
+            .collect()
+    }
+
+    /// Changed paths, once the changes have settled. To call regularly.
+    pub fn poll(&mut self) -> Option<Vec<PathBuf>> {
+        for event in self.receiver.try_iter().filter_map(Result::ok) {
+            if !matches!(
+                event.kind,
+                EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+            ) {
+                continue;
+            }
+
+            for path in event.paths {
+                if self.is_watched(&path) {
+                    self.changed.insert(path);
+                    self.last_change = Some(Instant::now());
+                }
+            }
+        }
+
+        if self
+            .last_change
+            .is_none_or(|last| last.elapsed() < self.debounce)
+        {
+            return None;
+        }
+
+        self.last_change = None;
+        let changed = Watcher::remove_ignored(std::mem::take(&mut self.changed));
+
+        if changed.is_empty() {
+            return None;
+        }
+
+        Some(changed)
+    }
+
+    /// Blocks until changes have settled.
+    pub fn wait(&mut self) -> Vec<PathBuf> {
+        loop {
+            if let Some(changed) = self.poll() {
+                return changed;
+            }
+
+            thread::sleep(Duration::from_millis(50));
+        }
+    }
+
+    fn is_watched(&self, path: &Path) -> bool {
+        if path
+            .components()
+            .any(|component| component == Component::Normal(".git".as_ref()))
+        {
+            return false;
+        }
+
+        self.targets.iter().any(|target| match target {
+            Target::Path(root) => path.starts_with(root),
+            Target::Glob(pattern) => pattern.matches_path(path),
+        })
+    }
+
+    /// Asks git which paths are ignored, nothing is removed outside of a repository.
+    fn remove_ignored(paths: BTreeSet<PathBuf>) -> Vec<PathBuf> {
+        let dir = match paths.first().and_then(|path| path.parent()) {
+            Some(dir) => dir.to_path_buf(),
+            None => return vec![],
+        };
+
+        let child = Command::new("git")
+            .args(["check-ignore", "--stdin"])
+            .current_dir(&dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn();
+
+        let ignored = child.ok().and_then(|mut child| {
+            let mut stdin = child.stdin.take()?;
+            for path in &paths {
+                writeln!(stdin, "{}", path.display()).ok()?;
+            }
+            drop(stdin);
+
+            child.wait_with_output().ok()
+        });
+
+        let ignored = ignored
+            .map(|output| {
+                String::from_utf8_lossy(&output.stdout)
+                    .lines()
+                    .map(|line| dir.join(line))
+                    .collect::<BTreeSet<_>>()
+            })
+            .unwrap_or_default();
+
+        paths
+            .into_iter()
+            .filter(|path| !ignored.contains(path))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_glob_root() {
+        assert_eq!(
+            Watcher::get_glob_root(Path::new("/repo/src/**/*.rs")),
+            PathBuf::from("/repo/src")
+        );
+        assert_eq!(
+            Watcher::get_glob_root(Path::new("/repo/*.toml")),
+            PathBuf::from("/repo")
+        );
+    }
+}
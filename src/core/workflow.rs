@@ -0,0 +1,194 @@
+use crate::core::hooks::HookContext;
+use crate::core::params;
+use crate::core::preview::Preview;
+use crate::core::risk::Risk;
+use crate::core::runner;
+use crate::core::settings::Settings;
+use crate::db::db::Db;
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+/// Stored commands run one after the other as a single step.
+pub struct Workflow {
+    pub tag: String,
+    pub continue_on_failure: bool,
+    pub steps: Vec<Step>,
+}
+
+pub struct Step {
+    pub tag: String,
+    /// `None` when the referenced command has been deleted.
+    pub command: Option<String>,
+}
+
+pub struct StepResult {
+    pub tag: String,
+    pub exit_code: Option<i32>,
+    pub duration: Duration,
+}
+
+/// Results of the steps that ran, in their order.
+pub struct WorkflowRun {
+    pub started_at: String,
+    pub results: Vec<StepResult>,
+}
+
+/// A run recorded in the history, alone or as a workflow step.
+pub struct HistoryEntry {
+    pub started_at: String,
+    pub command: String,
+    pub result: StepResult,
+}
+
+impl StepResult {
+    pub fn is_success(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+impl Workflow {
+    /// Step tags joined by arrows, shown as the workflow command.
+    pub fn describe(&self) -> String {
+        let steps = self
+            .steps
+            .iter()
+            .map(|step| step.tag.as_str())
+            .collect::<Vec<_>>()
+            .join(" → ");
+
+        if self.continue_on_failure {
+            return format!("{} (continue on failure)", steps);
+        }
+
+        steps
+    }
+
+    /// A workflow is as risky as its riskiest step, `risks` are the levels set by tag.
+    pub fn get_risk(&self, risks: &HashMap<String, Risk>) -> Risk {
+        self.steps
+            .iter()
+            .filter_map(|step| {
+                let command = step.command.as_ref()?;
+                Some(
+                    risks
+                        .get(&step.tag)
+                        .copied()
+                        .unwrap_or_else(|| Risk::detect(command)),
+                )
+            })
+            .fold(Risk::Safe, |max, risk| if risk > max { risk } else { max })
+    }
+
+    /// Previews of every step, in their order.
+    pub fn preview(
+        &self,
+        params: &BTreeMap<String, String>,
+    ) -> Result<Vec<Preview>, Box<dyn Error>> {
+        self.steps
+            .iter()
+            .enumerate()
+            .map(|(i, step)| Preview::new(self.get_step_command(i, step)?, params))
+            .collect()
+    }
+
+    fn get_step_command<'a>(&self, i: usize, step: &'a Step) -> Result<&'a str, Box<dyn Error>> {
+        step.command.as_deref().ok_or_else(|| {
+            format!("Step {} ({}) references a deleted command", i + 1, step.tag).into()
+        })
+    }
+
+    /// Runs the steps in the terminal with the shared parameters and records their results.
+    /// Returns false when a step failed.
+    pub fn run(
+        &self,
+        db: &Db,
+        params: &BTreeMap<String, String>,
+        settings: &Settings,
+    ) -> Result<bool, Box<dyn Error>> {
+        self.run_steps(db, params, settings, true)
+    }
+
+    /// Runs a workflow which isn't stored, like commands marked in the interface. Results
+    /// are recorded in the history as separate runs.
+    pub fn run_unsaved(&self, db: &Db, settings: &Settings) -> Result<bool, Box<dyn Error>> {
+        self.run_steps(db, &BTreeMap::new(), settings, false)
+    }
+
+    fn run_steps(
+        &self,
+        db: &Db,
+        params: &BTreeMap<String, String>,
+        settings: &Settings,
+        stored: bool,
+    ) -> Result<bool, Box<dyn Error>> {
+        // Resolve every step first, a missing parameter must not leave the workflow half done
+        let commands = self
+            .steps
+            .iter()
+            .enumerate()
+            .map(|(i, step)| params::substitute(self.get_step_command(i, step)?, params))
+            .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+
+        let run_id = match stored {
+            true => Some(db.add_workflow_run(&self.tag)?),
+            false => None,
+        };
+        let options = db.get_run_options()?;
+        let count = self.steps.len();
+        let started = Instant::now();
+        let mut failed = 0;
+        // Exit code of the last failing step
+        let mut exit_code = Some(0);
+
+        for (i, (step, command)) in self.steps.iter().zip(commands).enumerate() {
+            println!("==> [{}/{}] {}", i + 1, count, step.tag);
+
+            let step_options = options.get(&step.tag).cloned().unwrap_or_default();
+            let namespace = db.get_tag_namespace(&step.tag)?;
+            let context = HookContext::new(&step.tag, &command, namespace.as_deref());
+            let outcome = runner::run(&context, &step_options, settings)?;
+            let result = StepResult {
+                tag: step.tag.clone(),
+                exit_code: outcome.exit_code,
+                duration: outcome.duration,
+            };
+
+            db.add_history(&result, &command, run_id)?;
+            println!(
+                "==> [{}/{}] {} {}",
+                i + 1,
+                count,
+                step.tag,
+                outcome.describe()
+            );
+
+            if !result.is_success() {
+                failed += 1;
+                exit_code = result.exit_code;
+
+                if !self.continue_on_failure {
+                    println!(
+                        "==> {}: stopped, {} step(s) skipped",
+                        self.tag,
+                        count - i - 1
+                    );
+                    break;
+                }
+            }
+        }
+
+        if failed == 0 || self.continue_on_failure {
+            println!(
+                "==> {}: {}/{} steps succeeded",
+                self.tag,
+                count - failed,
+                count
+            );
+        }
+
+        runner::notify(&settings.notify, &self.tag, exit_code, started.elapsed());
+
+        Ok(failed == 0)
+    }
+}
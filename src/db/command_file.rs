@@ -0,0 +1,65 @@
+use crate::core::risk::Risk;
+use crate::core::runner::RunOptions;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::path::Path;
+
+/// A namespace stored as TOML, shared by project files and the sync tree.
+#[derive(Default, Deserialize, Serialize)]
+pub struct CommandFile {
+    pub name: Option<String>,
+    #[serde(default)]
+    pub commands: Vec<FileCommand>,
+}
+
+#[derive(Clone, Default, Deserialize, Serialize)]
+pub struct FileCommand {
+    pub tag: String,
+    pub command: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Detected from the command when not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub risk: Option<Risk>,
+    #[serde(flatten)]
+    pub options: RunOptions,
+    /// Cron expression of `cm scheduler`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<String>,
+    /// Starred in the interface.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub favorite: bool,
+}
+
+impl CommandFile {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let content = std::fs::read_to_string(path)?;
+
+        toml::from_str(&content).map_err(|e| format!("Invalid {}: {}", path.display(), e).into())
+    }
+
+    pub fn to_toml(&self) -> Result<String, Box<dyn Error>> {
+        Ok(toml::to_string(self)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_options_round_trip() {
+        let file: CommandFile = toml::from_str(
+            "[[commands]]\ntag = \"fetch\"\ncommand = \"curl example.com\"\ntimeout = 30\nretries = 2\n",
+        )
+        .unwrap();
+        let options = &file.commands[0].options;
+
+        assert_eq!(options.timeout, Some(30));
+        assert_eq!(options.retries, Some(2));
+
+        let content = file.to_toml().unwrap();
+        assert!(content.contains("timeout = 30"));
+        assert!(!content.contains("risk"));
+    }
+}
@@ -1,51 +1,127 @@
 #![allow(unused)]
 
+use crate::core::risk::Risk;
+use crate::core::runner::RunOptions;
+use crate::core::settings::Settings;
+use crate::core::workflow::{HistoryEntry, Step, StepResult, Workflow, WorkflowRun};
+use crate::db::command_file::{CommandFile, FileCommand};
 use crate::db::fixtures;
-use rusqlite::Connection;
+use crate::db::namespace::{Favorite, TrashEntry};
+use rusqlite::{params, Connection, OpenFlags};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::error::Error;
 use std::io::ErrorKind;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const DB_FILE: &str = "command_manager.db";
+
+/// Ids of deleted commands are never given again, workflow steps keep pointing at them.
+const COMMANDS_COLUMNS: &str = r"(
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        value TEXT NOT NULL,
+        namespace_id INTEGER NOT NULL,
+        risk TEXT,
+        timeout INTEGER,
+        retries INTEGER,
+        schedule TEXT,
+        favorite INTEGER NOT NULL DEFAULT 0,
+        dir TEXT,
+        description TEXT,
+        FOREIGN KEY (namespace_id) REFERENCES namespaces(id)
+        ON DELETE CASCADE
+    )";
 
 pub struct Db {
     conn: Connection,
+    team: Option<Connection>,
 }
 
 impl Db {
-    pub fn new() -> Result<Db, Box<dyn Error>> {
-        let path = Db::get_db_path()?;
+    pub fn new(settings: &Settings, profile: Option<&str>) -> Result<Db, Box<dyn Error>> {
+        let path = Db::get_db_path(settings, profile)?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
         let conn = Connection::open(path)?;
 
-        Ok(Db { conn })
+        let team = match Db::get_team_db_path()? {
+            Some(path) => Some(Connection::open_with_flags(
+                path,
+                OpenFlags::SQLITE_OPEN_READ_ONLY,
+            )?),
+            None => None,
+        };
+
+        Ok(Db { conn, team })
     }
 
-    pub fn get_db_path() -> Result<String, Box<dyn Error>> {
-        let db_file = std::env::var("CM_DB");
+    #[cfg(test)]
+    pub fn in_memory() -> Result<Db, Box<dyn Error>> {
+        let db = Db {
+            conn: Connection::open_in_memory()?,
+            team: None,
+        };
+        db.create_db_structure()?;
 
-        let db_file = match db_file {
-            Ok(f) => {
-                let db_path = Path::new(&f);
-                if !db_path.is_file() {
-                    return Err(Box::new(std::io::Error::new(
-                        ErrorKind::NotFound,
-                        "CM_DB env var is not a file",
-                    )));
-                }
-                Ok(f)
-            }
-            Err(_) => {
-                let home = dirs::home_dir().ok_or("No home directory found")?;
-                let db_namespace = home.join(".cm");
-                std::fs::create_dir_all(&db_namespace)?;
-                let db_path = db_namespace.join("command_manager.db");
-                let db = db_path
-                    .to_str()
-                    .ok_or("Could not convert db path to string")?;
-
-                Ok(db.to_string())
+        Ok(db)
+    }
+
+    /// An explicit profile wins over `CM_DB`, which wins over the configured default profile.
+    /// The database file is created when it does not exist yet.
+    pub fn get_db_path(
+        settings: &Settings,
+        profile: Option<&str>,
+    ) -> Result<PathBuf, Box<dyn Error>> {
+        if let Some(name) = profile {
+            return settings.get_profile_path(name);
+        }
+
+        if let Ok(f) = std::env::var("CM_DB") {
+            let db_path = PathBuf::from(f);
+            if db_path.is_dir() {
+                return Err(Box::new(std::io::Error::new(
+                    ErrorKind::InvalidInput,
+                    "CM_DB env var is a directory",
+                )));
             }
+
+            return Ok(db_path);
+        }
+
+        if let Some(name) = &settings.profile {
+            return settings.get_profile_path(name);
+        }
+
+        let home = dirs::home_dir().ok_or("No home directory found")?;
+
+        Ok(home.join(".cm").join(DB_FILE))
+    }
+
+    /// The shared team library is a database file, or a directory holding one.
+    pub fn get_team_db_path() -> Result<Option<String>, Box<dyn Error>> {
+        let team_db = match std::env::var("CM_TEAM_DB") {
+            Ok(f) => f,
+            Err(_) => return Ok(None),
         };
 
-        db_file
+        let mut db_path = Path::new(&team_db).to_path_buf();
+        if db_path.is_dir() {
+            db_path = db_path.join(DB_FILE);
+        }
+
+        if !db_path.is_file() {
+            return Err(Box::new(std::io::Error::new(
+                ErrorKind::NotFound,
+                "CM_TEAM_DB env var is not a file or a directory containing a database",
+            )));
+        }
+
+        let db = db_path
+            .to_str()
+            .ok_or("Could not convert team db path to string")?;
+
+        Ok(Some(db.to_string()))
     }
 
     pub fn init_db(&self) -> Result<(), Box<dyn Error>> {
@@ -56,19 +132,13 @@ impl Db {
     }
 
     pub fn create_db_structure(&self) -> Result<(), Box<dyn Error>> {
-        self.conn.execute_batch(
+        self.conn.execute_batch(&format!(
             r"
     CREATE TABLE IF NOT EXISTS namespaces (
         id INTEGER PRIMARY KEY,
         name VARCHAR(255) UNIQUE NOT NULL
     );
-    CREATE TABLE IF NOT EXISTS commands (
-        id INTEGER PRIMARY KEY,
-        value TEXT NOT NULL,
-        namespace_id INTEGER NOT NULL,
-        FOREIGN KEY (namespace_id) REFERENCES namespaces(id)
-        ON DELETE CASCADE
-    );
+    CREATE TABLE IF NOT EXISTS commands {};
     CREATE TABLE IF NOT EXISTS tags (
         id INTEGER PRIMARY KEY,
         name VARCHAR(255) UNIQUE NOT NULL,
@@ -76,8 +146,133 @@ impl Db {
         FOREIGN KEY (command_id) REFERENCES commands(id)
         ON DELETE CASCADE
     );
+    CREATE TABLE IF NOT EXISTS workflows (
+        id INTEGER PRIMARY KEY,
+        tag VARCHAR(255) UNIQUE NOT NULL,
+        continue_on_failure INTEGER NOT NULL DEFAULT 0
+    );
+    CREATE TABLE IF NOT EXISTS workflow_steps (
+        id INTEGER PRIMARY KEY,
+        workflow_id INTEGER NOT NULL,
+        position INTEGER NOT NULL,
+        command_id INTEGER NOT NULL,
+        FOREIGN KEY (workflow_id) REFERENCES workflows(id)
+        ON DELETE CASCADE
+    );
+    CREATE TABLE IF NOT EXISTS workflow_runs (
+        id INTEGER PRIMARY KEY,
+        workflow_id INTEGER NOT NULL,
+        started_at TEXT NOT NULL DEFAULT (datetime('now', 'localtime')),
+        FOREIGN KEY (workflow_id) REFERENCES workflows(id)
+        ON DELETE CASCADE
+    );
+    CREATE TABLE IF NOT EXISTS history (
+        id INTEGER PRIMARY KEY,
+        tag VARCHAR(255) NOT NULL,
+        command TEXT NOT NULL,
+        exit_code INTEGER,
+        duration_ms INTEGER NOT NULL,
+        started_at TEXT NOT NULL DEFAULT (datetime('now', 'localtime')),
+        workflow_run_id INTEGER,
+        FOREIGN KEY (workflow_run_id) REFERENCES workflow_runs(id)
+        ON DELETE CASCADE
+    );
+    CREATE TABLE IF NOT EXISTS trash (
+        id INTEGER PRIMARY KEY,
+        parent_id INTEGER,
+        namespace VARCHAR(255) NOT NULL,
+        tag VARCHAR(255),
+        command_id INTEGER,
+        value TEXT,
+        risk TEXT,
+        timeout INTEGER,
+        retries INTEGER,
+        schedule TEXT,
+        favorite INTEGER NOT NULL DEFAULT 0,
+        dir TEXT,
+        description TEXT,
+        deleted_at TEXT NOT NULL DEFAULT (datetime('now', 'localtime'))
+    );
     ",
+            COMMANDS_COLUMNS
+        ))?;
+
+        // Databases created before risk levels, run options, schedules, favorites and descriptions
+        self.add_missing_column("commands", "risk", "TEXT")?;
+        self.add_missing_column("commands", "timeout", "INTEGER")?;
+        self.add_missing_column("commands", "retries", "INTEGER")?;
+        self.add_missing_column("commands", "schedule", "TEXT")?;
+        self.add_missing_column("commands", "favorite", "INTEGER NOT NULL DEFAULT 0")?;
+        self.add_missing_column("commands", "dir", "TEXT")?;
+        self.add_missing_column("commands", "description", "TEXT")?;
+        self.add_missing_column("trash", "dir", "TEXT")?;
+        self.add_missing_column("trash", "description", "TEXT")?;
+        self.add_autoincrement()?;
+
+        Ok(())
+    }
+
+    /// Databases created before the trash reused the ids of deleted commands, so workflow steps
+    /// ran whatever command was added next. The table is copied into one that never reuses them,
+    /// starting after the ids still kept by the trash and the workflow steps.
+    fn add_autoincrement(&self) -> Result<(), Box<dyn Error>> {
+        let sql: String = self.conn.query_row(
+            "SELECT sql FROM sqlite_master WHERE type = 'table' AND name = 'commands'",
+            [],
+            |row| row.get(0),
         )?;
+        if sql.contains("AUTOINCREMENT") {
+            return Ok(());
+        }
+
+        // Dropping the table would otherwise delete the tags with it
+        self.conn.execute_batch("PRAGMA foreign_keys = OFF")?;
+        let result = self.copy_commands();
+        self.conn.execute_batch("PRAGMA foreign_keys = ON")?;
+
+        result
+    }
+
+    fn copy_commands(&self) -> Result<(), Box<dyn Error>> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute_batch(&format!(
+            r"
+        CREATE TABLE commands_autoincrement {};
+        INSERT INTO commands_autoincrement
+            (id, value, namespace_id, risk, timeout, retries, schedule, favorite, dir, description)
+        SELECT id, value, namespace_id, risk, timeout, retries, schedule, favorite, dir, description
+        FROM commands;
+        DROP TABLE commands;
+        ALTER TABLE commands_autoincrement RENAME TO commands;
+        DELETE FROM sqlite_sequence WHERE name = 'commands';
+        INSERT INTO sqlite_sequence (name, seq) SELECT 'commands', MAX(
+            (SELECT IFNULL(MAX(id), 0) FROM commands),
+            (SELECT IFNULL(MAX(command_id), 0) FROM trash),
+            (SELECT IFNULL(MAX(command_id), 0) FROM workflow_steps)
+        );",
+            COMMANDS_COLUMNS
+        ))?;
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    fn add_missing_column(
+        &self,
+        table: &str,
+        column: &str,
+        kind: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        if self
+            .conn
+            .prepare(&format!("SELECT {} FROM {} LIMIT 0", column, table))
+            .is_err()
+        {
+            self.conn.execute(
+                &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, kind),
+                [],
+            )?;
+        }
 
         Ok(())
     }
@@ -86,21 +281,20 @@ impl Db {
         let mut stmt = self
             .conn
             .prepare("INSERT INTO namespaces (name) VALUES (?)")?;
-        stmt.execute([s]);
+        stmt.execute([s])?;
 
         Ok(())
     }
 
     pub fn get_namespaces(&self) -> Result<Vec<String>, Box<dyn Error>> {
-        let mut namespaces: Vec<String> = Vec::new();
+        query_namespaces(&self.conn)
+    }
 
-        let mut stmt = self.conn.prepare("SELECT name FROM namespaces")?;
-        for row in stmt.query_map([], |row| row.get(0))? {
-            let namespace = row?;
-            namespaces.push(namespace);
+    pub fn get_team_namespaces(&self) -> Result<Vec<String>, Box<dyn Error>> {
+        match &self.team {
+            Some(conn) => query_namespaces(conn),
+            None => Ok(vec![]),
         }
-
-        Ok(namespaces)
     }
 
     pub fn get_namespace(&self, s: &String) -> Result<Option<String>, Box<dyn Error>> {
@@ -125,55 +319,382 @@ impl Db {
         Ok(namespace)
     }
 
+    pub fn has_tag(&self, tag: &String) -> Result<bool, Box<dyn Error>> {
+        let count: u32 =
+            self.conn
+                .query_row("SELECT count(*) FROM tags WHERE name = ?", [tag], |row| {
+                    row.get(0)
+                })?;
+
+        Ok(count > 0)
+    }
+
+    pub fn get_command(&self, tag: &str) -> Result<Option<String>, Box<dyn Error>> {
+        let command = self.conn.query_row(
+            "SELECT value FROM commands WHERE id = (SELECT command_id FROM tags WHERE name = ?)",
+            [tag],
+            |row| row.get(0),
+        );
+
+        match command {
+            Ok(command) => Ok(Some(command)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn get_tag_namespace(&self, tag: &str) -> Result<Option<String>, Box<dyn Error>> {
+        let namespace = self.conn.query_row(
+            r"
+        SELECT namespaces.name FROM namespaces
+        JOIN commands ON commands.namespace_id = namespaces.id
+        WHERE commands.id = (SELECT command_id FROM tags WHERE name = ?);",
+            [tag],
+            |row| row.get(0),
+        );
+
+        match namespace {
+            Ok(namespace) => Ok(Some(namespace)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// `None` removes the stored level, the risk is then detected from the command.
+    pub fn set_risk(&self, tag: &str, risk: Option<Risk>) -> Result<(), Box<dyn Error>> {
+        let updated = self.conn.execute(
+            "UPDATE commands SET risk = ? WHERE id = (SELECT command_id FROM tags WHERE name = ?)",
+            params![risk.map(|risk| risk.get_name().to_string()), tag],
+        )?;
+
+        if updated == 0 {
+            return Err(format!("Unknown command tag: {}", tag).into());
+        }
+
+        Ok(())
+    }
+
+    /// Stored risk levels of the personal commands, by tag.
+    pub fn get_risks(&self) -> Result<HashMap<String, Risk>, Box<dyn Error>> {
+        let mut stmt = self.conn.prepare(
+            r"
+        SELECT tags.name, commands.risk FROM commands
+        JOIN tags ON tags.command_id = commands.id
+        WHERE commands.risk IS NOT NULL;",
+        )?;
+
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<(String, String)>, _>>()?;
+
+        rows.into_iter()
+            .map(|(tag, risk)| Ok((tag, Risk::from_name(&risk)?)))
+            .collect()
+    }
+
+    pub fn set_run_options(&self, tag: &str, options: &RunOptions) -> Result<(), Box<dyn Error>> {
+        let updated = self.conn.execute(
+            "UPDATE commands SET timeout = ?, retries = ?, dir = ? WHERE id = (SELECT command_id FROM tags WHERE name = ?)",
+            params![options.timeout, options.retries, options.dir, tag],
+        )?;
+
+        if updated == 0 {
+            return Err(format!("Unknown command tag: {}", tag).into());
+        }
+
+        Ok(())
+    }
+
+    /// Run options of the personal commands having one set, by tag.
+    pub fn get_run_options(&self) -> Result<HashMap<String, RunOptions>, Box<dyn Error>> {
+        let mut stmt = self.conn.prepare(
+            r"
+        SELECT tags.name, commands.timeout, commands.retries, commands.dir FROM commands
+        JOIN tags ON tags.command_id = commands.id
+        WHERE commands.timeout IS NOT NULL OR commands.retries IS NOT NULL
+            OR commands.dir IS NOT NULL;",
+        )?;
+
+        let options = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get(0)?,
+                    RunOptions {
+                        timeout: row.get(1)?,
+                        retries: row.get(2)?,
+                        dir: row.get(3)?,
+                    },
+                ))
+            })?
+            .collect::<Result<HashMap<_, _>, _>>()?;
+
+        Ok(options)
+    }
+
+    /// `None` removes the command from the scheduler.
+    pub fn set_schedule(&self, tag: &str, schedule: Option<&str>) -> Result<(), Box<dyn Error>> {
+        let updated = self.conn.execute(
+            "UPDATE commands SET schedule = ? WHERE id = (SELECT command_id FROM tags WHERE name = ?)",
+            params![schedule, tag],
+        )?;
+
+        if updated == 0 {
+            return Err(format!("Unknown command tag: {}", tag).into());
+        }
+
+        Ok(())
+    }
+
+    /// Cron expressions of the scheduled commands, by tag.
+    pub fn get_schedules(&self) -> Result<BTreeMap<String, String>, Box<dyn Error>> {
+        let mut stmt = self.conn.prepare(
+            r"
+        SELECT tags.name, commands.schedule FROM commands
+        JOIN tags ON tags.command_id = commands.id
+        WHERE commands.schedule IS NOT NULL;",
+        )?;
+
+        let schedules = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<BTreeMap<_, _>, _>>()?;
+
+        Ok(schedules)
+    }
+
+    /// `None` removes the description.
+    pub fn set_description(
+        &self,
+        tag: &str,
+        description: Option<&str>,
+    ) -> Result<(), Box<dyn Error>> {
+        let updated = self.conn.execute(
+            "UPDATE commands SET description = ? WHERE id = (SELECT command_id FROM tags WHERE name = ?)",
+            params![description, tag],
+        )?;
+
+        if updated == 0 {
+            return Err(format!("Unknown command tag: {}", tag).into());
+        }
+
+        Ok(())
+    }
+
+    /// Descriptions of the personal commands having one, by tag.
+    pub fn get_descriptions(&self) -> Result<HashMap<String, String>, Box<dyn Error>> {
+        let mut stmt = self.conn.prepare(
+            r"
+        SELECT tags.name, commands.description FROM commands
+        JOIN tags ON tags.command_id = commands.id
+        WHERE commands.description IS NOT NULL;",
+        )?;
+
+        let descriptions = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<HashMap<_, _>, _>>()?;
+
+        Ok(descriptions)
+    }
+
+    pub fn set_favorite(&self, tag: &str, favorite: bool) -> Result<(), Box<dyn Error>> {
+        let updated = self.conn.execute(
+            "UPDATE commands SET favorite = ? WHERE id = (SELECT command_id FROM tags WHERE name = ?)",
+            params![favorite, tag],
+        )?;
+
+        if updated == 0 {
+            return Err(format!("Unknown command tag: {}", tag).into());
+        }
+
+        Ok(())
+    }
+
+    /// Starred commands, by namespace then tag.
+    pub fn get_favorites(&self) -> Result<Vec<Favorite>, Box<dyn Error>> {
+        let mut stmt = self.conn.prepare(
+            r"
+        SELECT namespaces.name, tags.name, commands.value FROM commands
+        JOIN tags ON tags.command_id = commands.id
+        JOIN namespaces ON namespaces.id = commands.namespace_id
+        WHERE commands.favorite = 1
+        ORDER BY namespaces.name, tags.name;",
+        )?;
+
+        let favorites = stmt
+            .query_map([], |row| {
+                Ok(Favorite {
+                    namespace: row.get(0)?,
+                    tag: row.get(1)?,
+                    command: row.get(2)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(favorites)
+    }
+
     pub fn get_commands_and_tags(
         &self,
         namespace: Option<String>,
     ) -> Result<(Vec<String>, Vec<String>), Box<dyn Error>> {
+        if let Some(namespace) = namespace {
+            return query_commands_and_tags(&self.conn, &namespace);
+        }
+
         let mut commands: Vec<String> = Vec::new();
         let mut tags: Vec<String> = Vec::new();
 
-        if namespace.is_some() {
-            let mut stmt = self.conn.prepare(
-                r"
-            SELECT commands.value, tags.name FROM commands
-            JOIN tags ON tags.command_id = commands.id
-            WHERE namespace_id = (SELECT id FROM namespaces WHERE name = :namespace);",
-            )?;
+        let mut stmt = self.conn.prepare(
+            r"
+        SELECT commands.value, tags.name FROM commands
+        JOIN tags ON tags.command_id = commands.id
+        WHERE namespace_id = (SELECT id FROM namespaces LIMIT 1);",
+        )?;
+        for row in stmt.query_map([], |row| {
+            let command = row.get(0)?;
+            let tag = row.get(1)?;
+            Ok((command, tag))
+        })? {
+            let (command, tag) = row?;
+            commands.push(command);
+            tags.push(tag);
+        }
 
-            stmt.query_map([namespace.unwrap()], |row| {
-                let command = row.get(0)?;
-                let tag = row.get(1)?;
-                Ok((command, tag))
-            })?
-            .for_each(|row| {
-                let (command, tag) = row.expect("Unable to get row");
-                commands.push(command);
-                tags.push(tag);
+        Ok((commands, tags))
+    }
+
+    pub fn get_team_commands_and_tags(
+        &self,
+        namespace: &str,
+    ) -> Result<(Vec<String>, Vec<String>), Box<dyn Error>> {
+        match &self.team {
+            Some(conn) => query_commands_and_tags(conn, namespace),
+            None => Ok((vec![], vec![])),
+        }
+    }
+
+    /// Personal namespaces sorted by name, with their commands sorted by tag.
+    pub fn export_files(&self) -> Result<Vec<CommandFile>, Box<dyn Error>> {
+        let mut namespaces = self.get_namespaces()?;
+        namespaces.sort();
+        let risks = self.get_risks()?;
+        let options = self.get_run_options()?;
+        let schedules = self.get_schedules()?;
+        let descriptions = self.get_descriptions()?;
+        let favorites = self
+            .get_favorites()?
+            .into_iter()
+            .map(|favorite| favorite.tag)
+            .collect::<HashSet<_>>();
+
+        let mut files = Vec::new();
+        for namespace in namespaces {
+            let (commands, tags) = self.get_commands_and_tags(Some(namespace.clone()))?;
+
+            let mut commands = tags
+                .into_iter()
+                .zip(commands)
+                .map(|(tag, command)| FileCommand {
+                    risk: risks.get(&tag).copied(),
+                    options: options.get(&tag).cloned().unwrap_or_default(),
+                    schedule: schedules.get(&tag).cloned(),
+                    description: descriptions.get(&tag).cloned(),
+                    favorite: favorites.contains(&tag),
+                    tag,
+                    command,
+                })
+                .collect::<Vec<_>>();
+            commands.sort_by(|a, b| a.tag.cmp(&b.tag));
+
+            files.push(CommandFile {
+                name: Some(namespace),
+                commands,
             });
-        } else {
-            let mut stmt = self.conn.prepare(
-                r"
-            SELECT commands.value, tags.name FROM commands
-            JOIN tags ON tags.command_id = commands.id
-            WHERE namespace_id = (SELECT id FROM namespaces LIMIT 1);",
+        }
+
+        Ok(files)
+    }
+
+    /// Makes the personal library match the files, commands are matched by tag to keep their ids.
+    /// Commands missing from the files are moved into the trash.
+    pub fn import_files(&self, files: &[CommandFile]) -> Result<(), Box<dyn Error>> {
+        let tx = self.conn.unchecked_transaction()?;
+
+        let mut namespaces = Vec::new();
+        let mut tags = Vec::new();
+
+        for file in files {
+            let namespace = file.name.clone().ok_or("Namespace file without name")?;
+            tx.execute(
+                "INSERT OR IGNORE INTO namespaces (name) VALUES (?)",
+                [&namespace],
             )?;
-            stmt.query_map([], |row| {
-                let command = row.get(0)?;
-                let tag = row.get(1)?;
-                Ok((command, tag))
-            })?
-            .for_each(|row| {
-                let (command, tag) = row.expect("Unable to get row");
-                commands.push(command);
-                tags.push(tag);
-            });
-        };
 
-        if commands.len() != tags.len() {
-            panic!("Commands and tags are not the same length");
+            for command in &file.commands {
+                let updated = tx.execute(
+                    r"
+                UPDATE commands
+                SET value = :command, namespace_id = (SELECT id FROM namespaces WHERE name = :namespace)
+                WHERE id = (SELECT command_id FROM tags WHERE name = :tag);",
+                    [&command.command, &namespace, &command.tag],
+                )?;
+
+                if updated == 0 {
+                    tx.execute(
+                        r"
+                    INSERT INTO commands (value, namespace_id)
+                    VALUES (:command, (SELECT id FROM namespaces WHERE name = :namespace));",
+                        [&command.command, &namespace],
+                    )?;
+                    tx.execute(
+                        "INSERT INTO tags (name, command_id) VALUES (?, ?)",
+                        params![command.tag, tx.last_insert_rowid()],
+                    )?;
+                }
+
+                tx.execute(
+                    "UPDATE commands SET risk = ? WHERE id = (SELECT command_id FROM tags WHERE name = ?)",
+                    params![command.risk.map(|risk| risk.get_name().to_string()), command.tag],
+                )?;
+                tx.execute(
+                    "UPDATE commands SET timeout = ?, retries = ?, dir = ? WHERE id = (SELECT command_id FROM tags WHERE name = ?)",
+                    params![command.options.timeout, command.options.retries, command.options.dir, command.tag],
+                )?;
+                tx.execute(
+                    "UPDATE commands SET schedule = ?, description = ?, favorite = ? WHERE id = (SELECT command_id FROM tags WHERE name = ?)",
+                    params![command.schedule, command.description, command.favorite, command.tag],
+                )?;
+
+                tags.push(command.tag.clone());
+            }
+
+            namespaces.push(namespace);
         }
 
-        Ok((commands, tags))
+        // Commands gone from the files go to the trash, their namespaces are empty by then
+        for tag in self.get_all_tags()? {
+            if !tags.contains(&tag) {
+                trash_command(&tx, self.get_command_id(&tag)?, None)?;
+            }
+        }
+
+        for namespace in self.get_namespaces()? {
+            if !namespaces.contains(&namespace) {
+                tx.execute("DELETE FROM namespaces WHERE name = ?", [&namespace])?;
+            }
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    fn get_all_tags(&self) -> Result<Vec<String>, Box<dyn Error>> {
+        let mut stmt = self.conn.prepare("SELECT name FROM tags")?;
+        let tags = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+
+        Ok(tags)
     }
 
     pub fn add_command_and_tag(
@@ -182,76 +703,875 @@ impl Db {
         tag: Option<&String>,
         namespace: &String,
     ) -> Result<(), Box<dyn Error>> {
-        let mut stmt = self.conn.prepare(
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
             r"
         INSERT INTO commands (value, namespace_id)
         VALUES (:command, (SELECT id FROM namespaces WHERE name = :namespace));",
+            [command, Some(namespace)],
+        )?;
+        tx.execute(
+            "INSERT INTO tags (name, command_id) VALUES (?, ?)",
+            params![tag, tx.last_insert_rowid()],
         )?;
+        tx.commit()?;
 
-        stmt.execute([command, Some(namespace)])?;
+        Ok(())
+    }
+
+    /// Moves a command with its settings into the trash, returns the id of the trash entry.
+    pub fn delete_command(&self, tag: &str) -> Result<i64, Box<dyn Error>> {
+        let command_id = self.get_command_id(tag)?;
+
+        let tx = self.conn.unchecked_transaction()?;
+        let id = trash_command(&tx, command_id, None)?;
+        tx.commit()?;
 
+        Ok(id)
+    }
+
+    /// Steps reference commands of the personal library by tag, the workflow tag must be unused.
+    pub fn add_workflow(
+        &self,
+        tag: &str,
+        steps: &[String],
+        continue_on_failure: bool,
+    ) -> Result<(), Box<dyn Error>> {
+        if self.has_tag(&tag.to_string())? || self.get_workflow(tag)?.is_some() {
+            return Err(format!("Tag already used: {}", tag).into());
+        }
+        if steps.is_empty() {
+            return Err("A workflow needs at least one step".into());
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT INTO workflows (tag, continue_on_failure) VALUES (?, ?)",
+            params![tag, continue_on_failure],
+        )?;
+        let workflow_id = tx.last_insert_rowid();
+
+        for (position, step) in steps.iter().enumerate() {
+            let command_id: i64 = tx
+                .query_row(
+                    "SELECT command_id FROM tags WHERE name = ?",
+                    [step],
+                    |row| row.get(0),
+                )
+                .map_err(|_| format!("Unknown command tag: {}", step))?;
+
+            tx.execute(
+                "INSERT INTO workflow_steps (workflow_id, position, command_id) VALUES (?, ?, ?)",
+                params![workflow_id, position, command_id],
+            )?;
+        }
+
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    /// Returns false when no workflow has this tag.
+    pub fn delete_workflow(&self, tag: &str) -> Result<bool, Box<dyn Error>> {
+        let tx = self.conn.unchecked_transaction()?;
+        let id = "(SELECT id FROM workflows WHERE tag = ?)";
+
+        tx.execute(
+            &format!(
+                "DELETE FROM history WHERE workflow_run_id IN (SELECT id FROM workflow_runs WHERE workflow_id = {})",
+                id
+            ),
+            [tag],
+        )?;
+        tx.execute(
+            &format!("DELETE FROM workflow_runs WHERE workflow_id = {}", id),
+            [tag],
+        )?;
+        tx.execute(
+            &format!("DELETE FROM workflow_steps WHERE workflow_id = {}", id),
+            [tag],
+        )?;
+        let deleted = tx.execute("DELETE FROM workflows WHERE tag = ?", [tag])?;
+
+        tx.commit()?;
+
+        Ok(deleted > 0)
+    }
+
+    pub fn get_workflows(&self) -> Result<Vec<Workflow>, Box<dyn Error>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, tag, continue_on_failure FROM workflows ORDER BY tag")?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+            .collect::<Result<Vec<(i64, String, bool)>, _>>()?;
+
+        rows.into_iter()
+            .map(|(id, tag, continue_on_failure)| {
+                Ok(Workflow {
+                    tag,
+                    continue_on_failure,
+                    steps: self.get_workflow_steps(id)?,
+                })
+            })
+            .collect()
+    }
+
+    pub fn get_workflow(&self, tag: &str) -> Result<Option<Workflow>, Box<dyn Error>> {
+        Ok(self
+            .get_workflows()?
+            .into_iter()
+            .find(|workflow| workflow.tag == tag))
+    }
+
+    fn get_workflow_steps(&self, workflow_id: i64) -> Result<Vec<Step>, Box<dyn Error>> {
         let mut stmt = self.conn.prepare(
             r"
-        INSERT INTO tags (name, command_id)
-        VALUES (:tag, (SELECT id FROM commands WHERE value = :command));",
+        SELECT tags.name, commands.value, workflow_steps.command_id FROM workflow_steps
+        LEFT JOIN commands ON commands.id = workflow_steps.command_id
+        LEFT JOIN tags ON tags.command_id = workflow_steps.command_id
+        WHERE workflow_id = ?
+        ORDER BY position;",
         )?;
 
-        stmt.execute([tag, command])?;
-        Ok(())
+        let steps = stmt
+            .query_map([workflow_id], |row| {
+                let tag: Option<String> = row.get(0)?;
+                let command_id: i64 = row.get(2)?;
+
+                Ok(Step {
+                    tag: tag.unwrap_or_else(|| format!("#{}", command_id)),
+                    command: row.get(1)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(steps)
+    }
+
+    pub fn add_workflow_run(&self, tag: &str) -> Result<i64, Box<dyn Error>> {
+        self.conn.execute(
+            "INSERT INTO workflow_runs (workflow_id) VALUES ((SELECT id FROM workflows WHERE tag = ?))",
+            [tag],
+        )?;
+
+        Ok(self.conn.last_insert_rowid())
     }
 
-    pub fn delete_command(
+    pub fn add_history(
         &self,
-        command: &String,
-        namespace: &String,
+        result: &StepResult,
+        command: &str,
+        workflow_run_id: Option<i64>,
     ) -> Result<(), Box<dyn Error>> {
-        self.conn.execute(r"SET FOREIGN_KEY_CHECKS=0", []);
+        self.conn.execute(
+            r"
+        INSERT INTO history (tag, command, exit_code, duration_ms, workflow_run_id)
+        VALUES (?, ?, ?, ?, ?);",
+            params![
+                result.tag,
+                command,
+                result.exit_code,
+                result.duration.as_millis() as i64,
+                workflow_run_id
+            ],
+        )?;
 
-        let mut stmt = self.conn.prepare(
+        Ok(())
+    }
+
+    pub fn get_last_workflow_run(&self, tag: &str) -> Result<Option<WorkflowRun>, Box<dyn Error>> {
+        let run = self.conn.query_row(
             r"
-        DELETE FROM commands
-        WHERE value = :command AND namespace_id = (SELECT id FROM namespaces WHERE name = :namespace);",
+        SELECT id, started_at FROM workflow_runs
+        WHERE workflow_id = (SELECT id FROM workflows WHERE tag = ?)
+        ORDER BY id DESC LIMIT 1;",
+            [tag],
+            |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?)),
+        );
+
+        let (run_id, started_at) = match run {
+            Ok(run) => run,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut stmt = self.conn.prepare(
+            "SELECT tag, exit_code, duration_ms FROM history WHERE workflow_run_id = ? ORDER BY id",
         )?;
+        let results = stmt
+            .query_map([run_id], |row| {
+                Ok(StepResult {
+                    tag: row.get(0)?,
+                    exit_code: row.get(1)?,
+                    duration: Duration::from_millis(row.get::<_, i64>(2)? as u64),
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
 
-        stmt.execute([command, namespace])?;
+        Ok(Some(WorkflowRun {
+            started_at,
+            results,
+        }))
+    }
 
+    /// Latest runs first.
+    pub fn get_history(&self, limit: usize) -> Result<Vec<HistoryEntry>, Box<dyn Error>> {
         let mut stmt = self.conn.prepare(
             r"
-        DELETE FROM tags
-        WHERE command_id = (SELECT id FROM commands WHERE value = :command);",
+        SELECT started_at, tag, command, exit_code, duration_ms FROM history
+        ORDER BY id DESC LIMIT ?;",
         )?;
 
-        stmt.execute([command])?;
+        let history = stmt
+            .query_map([limit as i64], |row| {
+                Ok(HistoryEntry {
+                    started_at: row.get(0)?,
+                    command: row.get(2)?,
+                    result: StepResult {
+                        tag: row.get(1)?,
+                        exit_code: row.get(3)?,
+                        duration: Duration::from_millis(row.get::<_, i64>(4)? as u64),
+                    },
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
 
-        self.conn.execute(r"SET FOREIGN_KEY_CHECKS=1", []);
+        Ok(history)
+    }
 
-        Ok(())
+    /// Start time and result of the last recorded run of a tag.
+    pub fn get_last_run(&self, tag: &str) -> Result<Option<(String, StepResult)>, Box<dyn Error>> {
+        let run = self.conn.query_row(
+            r"
+        SELECT started_at, exit_code, duration_ms FROM history
+        WHERE tag = ? ORDER BY id DESC LIMIT 1;",
+            [tag],
+            |row| {
+                Ok((
+                    row.get(0)?,
+                    StepResult {
+                        tag: tag.to_string(),
+                        exit_code: row.get(1)?,
+                        duration: Duration::from_millis(row.get::<_, i64>(2)? as u64),
+                    },
+                ))
+            },
+        );
+
+        match run {
+            Ok(run) => Ok(Some(run)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Moves a namespace and its commands into the trash, returns the id of the trash entry.
+    pub fn delete_namespace(&self, namespace: &str) -> Result<i64, Box<dyn Error>> {
+        let namespace_id = self.get_namespace_id(namespace)?;
+        let command_ids = self
+            .conn
+            .prepare("SELECT id FROM commands WHERE namespace_id = ?")?
+            .query_map([namespace_id], |row| row.get(0))?
+            .collect::<Result<Vec<i64>, _>>()?;
+
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute("INSERT INTO trash (namespace) VALUES (?)", [namespace])?;
+        let id = tx.last_insert_rowid();
+        for command_id in command_ids {
+            trash_command(&tx, command_id, Some(id))?;
+        }
+        tx.execute("DELETE FROM namespaces WHERE id = ?", [namespace_id])?;
+        tx.commit()?;
+
+        Ok(id)
     }
 
-    pub fn delete_namespace(&self, namespace: &String) -> Result<(), Box<dyn Error>> {
+    /// Deleted commands and namespaces, latest first.
+    pub fn get_trash(&self) -> Result<Vec<TrashEntry>, Box<dyn Error>> {
         let mut stmt = self.conn.prepare(
             r"
-        DELETE FROM commands
-        WHERE namespace_id = (SELECT id FROM namespaces WHERE name = :namespace);",
+        SELECT id, namespace, tag, value, deleted_at,
+            (SELECT COUNT(*) FROM trash AS child WHERE child.parent_id = trash.id)
+        FROM trash
+        WHERE parent_id IS NULL
+        ORDER BY id DESC;",
         )?;
 
-        stmt.execute([namespace])?;
+        let entries = stmt
+            .query_map([], |row| {
+                Ok(TrashEntry {
+                    id: row.get(0)?,
+                    namespace: row.get(1)?,
+                    tag: row.get(2)?,
+                    command: row.get(3)?,
+                    deleted_at: row.get(4)?,
+                    commands: row.get(5)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
 
-        let mut stmt = self.conn.prepare(
-            r"
-        DELETE FROM tags
-        WHERE command_id IN (SELECT id FROM commands WHERE namespace_id = (SELECT id FROM namespaces WHERE name = :namespace));",
+        Ok(entries)
+    }
+
+    /// Puts a trash entry back in the library, a namespace with its commands. Missing namespaces
+    /// are created again. Commands get their former id back when it is still free, so the
+    /// workflow steps using them work again.
+    pub fn restore_trash(&self, id: i64) -> Result<TrashEntry, Box<dyn Error>> {
+        let entry = self
+            .get_trash()?
+            .into_iter()
+            .find(|entry| entry.id == id)
+            .ok_or_else(|| format!("No entry {} in the trash", id))?;
+
+        let rows = self
+            .conn
+            .prepare(
+                "SELECT id, tag FROM trash WHERE (id = ? OR parent_id = ?) AND tag IS NOT NULL",
+            )?
+            .query_map([id, id], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<(i64, String)>, _>>()?;
+
+        for (_, tag) in &rows {
+            if self.has_tag(tag)? {
+                return Err(
+                    format!("Tag {} is used again, rename it before restoring", tag).into(),
+                );
+            }
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "INSERT OR IGNORE INTO namespaces (name) VALUES (?)",
+            [&entry.namespace],
         )?;
+        for (row_id, tag) in rows {
+            tx.execute(
+                r"
+            INSERT INTO commands
+                (id, value, namespace_id, risk, timeout, retries, schedule, favorite, dir, description)
+            SELECT CASE WHEN EXISTS (SELECT 1 FROM commands WHERE id = trash.command_id)
+                THEN NULL ELSE trash.command_id END,
+                value, (SELECT id FROM namespaces WHERE name = trash.namespace),
+                risk, timeout, retries, schedule, favorite, dir, description
+            FROM trash WHERE id = ?;",
+                [row_id],
+            )?;
+            tx.execute(
+                "INSERT INTO tags (name, command_id) VALUES (?, ?)",
+                params![tag, tx.last_insert_rowid()],
+            )?;
+        }
+        tx.execute("DELETE FROM trash WHERE id = ? OR parent_id = ?", [id, id])?;
+        tx.commit()?;
 
-        stmt.execute([namespace])?;
+        Ok(entry)
+    }
 
-        let mut stmt = self.conn.prepare(
+    /// Deletes the trash entries older than the days for good, or every entry without days.
+    /// Returns the count of entries deleted.
+    pub fn purge_trash(&self, days: Option<u64>) -> Result<usize, Box<dyn Error>> {
+        let condition = match days {
+            Some(days) => format!(
+                "deleted_at < datetime('now', 'localtime', '-{} days')",
+                days
+            ),
+            None => "1".to_string(),
+        };
+
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            &format!(
+                "DELETE FROM trash WHERE parent_id IN (SELECT id FROM trash WHERE parent_id IS NULL AND {})",
+                condition
+            ),
+            [],
+        )?;
+        let purged = tx.execute(
+            &format!(
+                "DELETE FROM trash WHERE parent_id IS NULL AND {}",
+                condition
+            ),
+            [],
+        )?;
+        tx.commit()?;
+
+        Ok(purged)
+    }
+
+    /// Purges the entries kept longer than configured, none when they are kept forever.
+    pub fn purge_expired_trash(&self, settings: &Settings) -> Result<usize, Box<dyn Error>> {
+        match settings.trash.purge_after_days {
+            0 => Ok(0),
+            days => self.purge_trash(Some(days)),
+        }
+    }
+
+    /// Deletes a trash entry for good, with the commands of a namespace.
+    pub fn purge_trash_entry(&self, id: i64) -> Result<(), Box<dyn Error>> {
+        let purged = self
+            .conn
+            .execute("DELETE FROM trash WHERE id = ? OR parent_id = ?", [id, id])?;
+
+        if purged == 0 {
+            return Err(format!("No entry {} in the trash", id).into());
+        }
+
+        Ok(())
+    }
+
+    pub fn move_command(&self, tag: &str, namespace: &str) -> Result<(), Box<dyn Error>> {
+        let namespace_id = self.get_namespace_id(namespace)?;
+        let updated = self.conn.execute(
+            "UPDATE commands SET namespace_id = ? WHERE id = (SELECT command_id FROM tags WHERE name = ?)",
+            params![namespace_id, tag],
+        )?;
+
+        if updated == 0 {
+            return Err(format!("Unknown command tag: {}", tag).into());
+        }
+
+        Ok(())
+    }
+
+    /// Copies a command with its risk level and run options under a free tag derived from
+    /// its own, e.g. `docker:purge:copy`, returns the new tag. Schedules and stars aren't copied.
+    pub fn duplicate_command(&self, tag: &str, namespace: &str) -> Result<String, Box<dyn Error>> {
+        let namespace_id = self.get_namespace_id(namespace)?;
+        if !self.has_tag(&tag.to_string())? {
+            return Err(format!("Unknown command tag: {}", tag).into());
+        }
+
+        let mut new_tag = format!("{}:copy", tag);
+        let mut copy = 1;
+        while self.has_tag(&new_tag)? {
+            copy += 1;
+            new_tag = format!("{}:copy{}", tag, copy);
+        }
+
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
             r"
-        DELETE FROM namespaces
-        WHERE name = :namespace;",
+        INSERT INTO commands (value, namespace_id, risk, timeout, retries, dir, description)
+        SELECT value, ?, risk, timeout, retries, dir, description FROM commands
+        WHERE id = (SELECT command_id FROM tags WHERE name = ?);",
+            params![namespace_id, tag],
+        )?;
+        tx.execute(
+            "INSERT INTO tags (name, command_id) VALUES (?, ?)",
+            params![new_tag, tx.last_insert_rowid()],
+        )?;
+        tx.commit()?;
+
+        Ok(new_tag)
+    }
+
+    pub fn rename_tag(&self, tag: &str, name: &str) -> Result<(), Box<dyn Error>> {
+        if self.has_tag(&name.to_string())? {
+            return Err(format!("Tag {} already exists", name).into());
+        }
+
+        let updated = self.conn.execute(
+            "UPDATE tags SET name = ? WHERE name = ?",
+            params![name, tag],
         )?;
 
-        stmt.execute([namespace])?;
+        if updated == 0 {
+            return Err(format!("Unknown command tag: {}", tag).into());
+        }
+
         Ok(())
     }
+
+    pub fn rename_namespace(&self, namespace: &str, name: &str) -> Result<(), Box<dyn Error>> {
+        if self.get_namespace(&name.to_string())?.is_some() {
+            return Err(format!("Namespace {} already exists", name).into());
+        }
+
+        let updated = self.conn.execute(
+            "UPDATE namespaces SET name = ? WHERE name = ?",
+            params![name, namespace],
+        )?;
+
+        if updated == 0 {
+            return Err(format!("Unknown namespace: {}", namespace).into());
+        }
+
+        Ok(())
+    }
+
+    /// Moves every command of `namespace` into `target`, then removes `namespace`.
+    pub fn merge_namespace(&self, namespace: &str, target: &str) -> Result<(), Box<dyn Error>> {
+        if namespace == target {
+            return Err(format!("Cannot merge {} into itself", namespace).into());
+        }
+
+        let source_id = self.get_namespace_id(namespace)?;
+        let target_id = self.get_namespace_id(target)?;
+
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "UPDATE commands SET namespace_id = ? WHERE namespace_id = ?",
+            params![target_id, source_id],
+        )?;
+        tx.execute("DELETE FROM namespaces WHERE id = ?", [source_id])?;
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    fn get_namespace_id(&self, namespace: &str) -> Result<i64, Box<dyn Error>> {
+        let id = self.conn.query_row(
+            "SELECT id FROM namespaces WHERE name = ?",
+            [namespace],
+            |row| row.get(0),
+        );
+
+        match id {
+            Ok(id) => Ok(id),
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                Err(format!("Unknown namespace: {}", namespace).into())
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn get_command_id(&self, tag: &str) -> Result<i64, Box<dyn Error>> {
+        let id = self
+            .conn
+            .query_row("SELECT command_id FROM tags WHERE name = ?", [tag], |row| {
+                row.get(0)
+            });
+
+        match id {
+            Ok(id) => Ok(id),
+            Err(rusqlite::Error::QueryReturnedNoRows) => {
+                Err(format!("Unknown command tag: {}", tag).into())
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Copies a command with its tag, namespace and settings into the trash, then deletes it.
+fn trash_command(
+    conn: &Connection,
+    command_id: i64,
+    parent_id: Option<i64>,
+) -> Result<i64, Box<dyn Error>> {
+    conn.execute(
+        r"
+        INSERT INTO trash (parent_id, namespace, tag, command_id, value, risk, timeout, retries,
+            schedule, favorite, dir, description)
+        SELECT ?, namespaces.name, tags.name, commands.id, commands.value,
+            commands.risk, commands.timeout, commands.retries, commands.schedule, commands.favorite,
+            commands.dir, commands.description
+        FROM commands
+        JOIN tags ON tags.command_id = commands.id
+        JOIN namespaces ON namespaces.id = commands.namespace_id
+        WHERE commands.id = ?;",
+        params![parent_id, command_id],
+    )?;
+    let id = conn.last_insert_rowid();
+
+    conn.execute("DELETE FROM tags WHERE command_id = ?", [command_id])?;
+    conn.execute("DELETE FROM commands WHERE id = ?", [command_id])?;
+
+    Ok(id)
+}
+
+fn query_namespaces(conn: &Connection) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut namespaces: Vec<String> = Vec::new();
+
+    let mut stmt = conn.prepare("SELECT name FROM namespaces")?;
+    for row in stmt.query_map([], |row| row.get(0))? {
+        let namespace = row?;
+        namespaces.push(namespace);
+    }
+
+    Ok(namespaces)
+}
+
+fn query_commands_and_tags(
+    conn: &Connection,
+    namespace: &str,
+) -> Result<(Vec<String>, Vec<String>), Box<dyn Error>> {
+    let mut commands: Vec<String> = Vec::new();
+    let mut tags: Vec<String> = Vec::new();
+
+    let mut stmt = conn.prepare(
+        r"
+        SELECT commands.value, tags.name FROM commands
+        JOIN tags ON tags.command_id = commands.id
+        WHERE namespace_id = (SELECT id FROM namespaces WHERE name = :namespace);",
+    )?;
+
+    for row in stmt.query_map([namespace], |row| Ok((row.get(0)?, row.get(1)?)))? {
+        let (command, tag) = row?;
+        commands.push(command);
+        tags.push(tag);
+    }
+
+    Ok((commands, tags))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn add(db: &Db, namespace: &str, command: &str, tag: &str) {
+        let namespace = namespace.to_string();
+        if db.get_namespace(&namespace).unwrap().is_none() {
+            db.add_namespace(&namespace).unwrap();
+        }
+        db.add_command_and_tag(
+            Some(&command.to_string()),
+            Some(&tag.to_string()),
+            &namespace,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_delete_and_restore_command() {
+        let db = Db::in_memory().unwrap();
+        add(&db, "docker", "docker ps", "ps");
+        let command_id = db.get_command_id("ps").unwrap();
+
+        let id = db.delete_command("ps").unwrap();
+        assert!(!db.has_tag(&"ps".to_string()).unwrap());
+        assert_eq!(db.get_trash().unwrap()[0].tag.as_deref(), Some("ps"));
+
+        db.restore_trash(id).unwrap();
+        assert_eq!(db.get_command_id("ps").unwrap(), command_id);
+        assert!(db.get_trash().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_restore_keeps_description_and_dir() {
+        let db = Db::in_memory().unwrap();
+        add(&db, "docker", "docker compose up", "up");
+        db.set_description("up", Some("Starts the stack")).unwrap();
+        let mut options = RunOptions::default();
+        options.set("dir", "~/stack").unwrap();
+        db.set_run_options("up", &options).unwrap();
+
+        let id = db.delete_command("up").unwrap();
+        db.restore_trash(id).unwrap();
+        let command = db.export_files().unwrap().remove(0).commands.remove(0);
+        assert_eq!(command.description.as_deref(), Some("Starts the stack"));
+        assert_eq!(command.options.dir.as_deref(), Some("~/stack"));
+    }
+
+    #[test]
+    fn test_restore_namespace() {
+        let db = Db::in_memory().unwrap();
+        add(&db, "git", "git status", "st");
+        add(&db, "git", "git log", "lg");
+
+        let id = db.delete_namespace("git").unwrap();
+        assert!(db.get_namespaces().unwrap().is_empty());
+        assert_eq!(db.get_trash().unwrap()[0].commands, 2);
+
+        db.restore_trash(id).unwrap();
+        let (_, mut tags) = db.get_commands_and_tags(Some("git".to_string())).unwrap();
+        tags.sort();
+        assert_eq!(tags, vec!["lg", "st"]);
+    }
+
+    #[test]
+    fn test_restore_refuses_a_used_tag() {
+        let db = Db::in_memory().unwrap();
+        add(&db, "docker", "docker ps", "ps");
+        let id = db.delete_command("ps").unwrap();
+        add(&db, "docker", "docker ps -a", "ps");
+
+        assert!(db.restore_trash(id).is_err());
+        assert_eq!(db.get_trash().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_restore_with_a_reused_id() {
+        let db = Db::in_memory().unwrap();
+        add(&db, "docker", "docker ps", "ps");
+        let id = db.delete_command("ps").unwrap();
+        add(&db, "docker", "docker images", "images");
+
+        db.restore_trash(id).unwrap();
+        let images = db.get_command_id("images").unwrap();
+        assert_ne!(db.get_command_id("ps").unwrap(), images);
+        let (commands, _) = db
+            .get_commands_and_tags(Some("docker".to_string()))
+            .unwrap();
+        assert_eq!(commands.len(), 2);
+    }
+
+    #[test]
+    fn test_purge_trash_by_age() {
+        let db = Db::in_memory().unwrap();
+        add(&db, "docker", "docker ps", "ps");
+        add(&db, "git", "git status", "st");
+        let old = db.delete_command("ps").unwrap();
+        db.delete_namespace("git").unwrap();
+        db.conn
+            .execute(
+                "UPDATE trash SET deleted_at = datetime('now', 'localtime', '-40 days') WHERE id = ?",
+                [old],
+            )
+            .unwrap();
+
+        assert_eq!(db.purge_trash(Some(30)).unwrap(), 1);
+        assert_eq!(db.get_trash().unwrap()[0].namespace, "git");
+        assert_eq!(db.purge_trash(None).unwrap(), 1);
+        assert!(db
+            .conn
+            .query_row("SELECT count(*) FROM trash", [], |row| row.get::<_, i64>(0))
+            .map(|count| count == 0)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_import_trashes_missing_commands() {
+        let db = Db::in_memory().unwrap();
+        add(&db, "docker", "docker ps", "ps");
+        add(&db, "git", "git status", "st");
+        db.set_favorite("st", true).unwrap();
+
+        // The git namespace is gone from the files
+        let files = db
+            .export_files()
+            .unwrap()
+            .into_iter()
+            .filter(|file| file.name.as_deref() == Some("docker"))
+            .collect::<Vec<_>>();
+        db.import_files(&files).unwrap();
+
+        assert_eq!(db.get_namespaces().unwrap(), vec!["docker"]);
+        assert!(db.get_favorites().unwrap().is_empty());
+        let id = db.get_trash().unwrap()[0].id;
+        db.restore_trash(id).unwrap();
+        assert!(db.has_tag(&"st".to_string()).unwrap());
+        assert_eq!(db.get_favorites().unwrap()[0].tag, "st");
+    }
+
+    #[test]
+    fn test_import_keeps_favorites() {
+        let db = Db::in_memory().unwrap();
+        add(&db, "docker", "docker ps", "ps");
+        add(&db, "docker", "docker images", "images");
+        db.set_favorite("ps", true).unwrap();
+        let files = db.export_files().unwrap();
+        assert!(files[0].to_toml().unwrap().contains("favorite = true"));
+
+        let other = Db::in_memory().unwrap();
+        other.import_files(&files).unwrap();
+        let favorites = other.get_favorites().unwrap();
+        assert_eq!(favorites.len(), 1);
+        assert_eq!(favorites[0].tag, "ps");
+    }
+
+    #[test]
+    fn test_ids_are_not_reused() {
+        // A database created before the ids were kept
+        let db = Db {
+            conn: Connection::open_in_memory().unwrap(),
+            team: None,
+        };
+        db.conn
+            .execute_batch(
+                r"
+            CREATE TABLE namespaces (id INTEGER PRIMARY KEY, name VARCHAR(255) UNIQUE NOT NULL);
+            CREATE TABLE commands (id INTEGER PRIMARY KEY, value TEXT NOT NULL, namespace_id INTEGER NOT NULL);
+            CREATE TABLE tags (id INTEGER PRIMARY KEY, name VARCHAR(255) UNIQUE NOT NULL, command_id INTEGER NOT NULL,
+                FOREIGN KEY (command_id) REFERENCES commands(id) ON DELETE CASCADE);
+            CREATE TABLE trash (id INTEGER PRIMARY KEY, parent_id INTEGER, namespace VARCHAR(255) NOT NULL,
+                tag VARCHAR(255), command_id INTEGER, value TEXT, risk TEXT, timeout INTEGER, retries INTEGER,
+                schedule TEXT, favorite INTEGER NOT NULL DEFAULT 0,
+                deleted_at TEXT NOT NULL DEFAULT (datetime('now', 'localtime')));
+            INSERT INTO namespaces (id, name) VALUES (1, 'docker');
+            INSERT INTO commands (id, value, namespace_id) VALUES (1, 'docker ps', 1);
+            INSERT INTO tags (name, command_id) VALUES ('ps', 1);
+            INSERT INTO trash (namespace, command_id) VALUES ('docker', 2);",
+            )
+            .unwrap();
+        db.create_db_structure().unwrap();
+        db.create_db_structure().unwrap();
+        assert_eq!(db.get_command_id("ps").unwrap(), 1);
+
+        add(&db, "docker", "docker images", "images");
+        assert_eq!(db.get_command_id("images").unwrap(), 3);
+        db.delete_command("images").unwrap();
+        add(&db, "docker", "docker volume ls", "volumes");
+        assert_eq!(db.get_command_id("volumes").unwrap(), 4);
+    }
+
+    #[test]
+    fn test_add_duplicate_values() {
+        let db = Db::in_memory().unwrap();
+        add(&db, "docker", "docker ps", "ps");
+        add(&db, "docker", "docker ps", "ps2");
+
+        assert_ne!(
+            db.get_command_id("ps").unwrap(),
+            db.get_command_id("ps2").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_move_command() {
+        let db = Db::in_memory().unwrap();
+        add(&db, "docker", "docker ps", "ps");
+        add(&db, "k8s", "kubectl get pods", "pods");
+
+        db.move_command("ps", "k8s").unwrap();
+        assert_eq!(db.get_tag_namespace("ps").unwrap().as_deref(), Some("k8s"));
+        assert!(db.move_command("unknown", "k8s").is_err());
+        assert!(db.move_command("ps", "unknown").is_err());
+    }
+
+    #[test]
+    fn test_duplicate_command() {
+        let db = Db::in_memory().unwrap();
+        add(&db, "docker", "docker system prune", "prune");
+        add(&db, "k8s", "kubectl get pods", "pods");
+        db.set_risk("prune", Some(Risk::Dangerous)).unwrap();
+
+        assert_eq!(db.duplicate_command("prune", "k8s").unwrap(), "prune:copy");
+        assert_eq!(db.duplicate_command("prune", "k8s").unwrap(), "prune:copy2");
+        assert_eq!(
+            db.get_tag_namespace("prune:copy").unwrap().as_deref(),
+            Some("k8s")
+        );
+        assert_eq!(db.get_risks().unwrap()["prune:copy"], Risk::Dangerous);
+        assert!(db.duplicate_command("unknown", "k8s").is_err());
+    }
+
+    #[test]
+    fn test_rename_namespace() {
+        let db = Db::in_memory().unwrap();
+        add(&db, "docker", "docker ps", "ps");
+        add(&db, "k8s", "kubectl get pods", "pods");
+
+        assert!(db.rename_namespace("docker", "k8s").is_err());
+        assert!(db.rename_namespace("unknown", "podman").is_err());
+        db.rename_namespace("docker", "podman").unwrap();
+        assert_eq!(
+            db.get_tag_namespace("ps").unwrap().as_deref(),
+            Some("podman")
+        );
+    }
+
+    #[test]
+    fn test_merge_namespace() {
+        let db = Db::in_memory().unwrap();
+        add(&db, "docker", "docker ps", "ps");
+        add(&db, "docker", "docker images", "images");
+        add(&db, "k8s", "kubectl get pods", "pods");
+
+        assert!(db.merge_namespace("k8s", "k8s").is_err());
+        db.merge_namespace("docker", "k8s").unwrap();
+        assert_eq!(db.get_namespaces().unwrap(), vec!["k8s"]);
+        let (_, tags) = db.get_commands_and_tags(Some("k8s".to_string())).unwrap();
+        assert_eq!(tags.len(), 3);
+    }
 }
@@ -1,2 +1,7 @@
+pub mod command_file;
+#[allow(clippy::module_inception)]
 pub mod db;
 pub mod fixtures;
+pub mod namespace;
+pub mod project;
+pub mod sync;
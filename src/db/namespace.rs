@@ -0,0 +1,59 @@
+#[derive(Clone, Debug, PartialEq)]
+pub enum Origin {
+    Personal,
+    Project,
+    Team,
+    Workflow,
+}
+
+/// A starred command of the personal library.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Favorite {
+    pub namespace: String,
+    pub tag: String,
+    pub command: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Namespace {
+    pub name: String,
+    pub origin: Origin,
+}
+
+impl Namespace {
+    pub fn new(name: String, origin: Origin) -> Self {
+        Namespace { name, origin }
+    }
+
+    pub fn personal(name: String) -> Self {
+        Namespace::new(name, Origin::Personal)
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.origin != Origin::Personal
+    }
+
+    /// Workflows are listed as a namespace, their items are not shell commands.
+    pub fn is_workflow(&self) -> bool {
+        self.origin == Origin::Workflow
+    }
+}
+
+/// A deleted command, or a deleted namespace with the count of its commands.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TrashEntry {
+    pub id: i64,
+    pub namespace: String,
+    /// `None` for a namespace.
+    pub tag: Option<String>,
+    pub command: Option<String>,
+    /// Commands deleted with a namespace.
+    pub commands: usize,
+    pub deleted_at: String,
+}
+
+impl TrashEntry {
+    pub fn get_name(&self) -> &str {
+        self.tag.as_deref().unwrap_or(&self.namespace)
+    }
+}
@@ -0,0 +1,99 @@
+use crate::db::command_file::{CommandFile, FileCommand};
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+pub const PROJECT_FILE: &str = ".cm.toml";
+
+/// Commands of a `.cm.toml` found from the working directory, shown as a read-only namespace.
+pub struct Project {
+    pub name: String,
+    pub commands: Vec<FileCommand>,
+}
+
+impl Project {
+    pub fn discover(start: &Path) -> Result<Option<Self>, Box<dyn Error>> {
+        match Project::find_file(start) {
+            Some(path) => Ok(Some(Project::load(path)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn load(path: PathBuf) -> Result<Self, Box<dyn Error>> {
+        let file = CommandFile::load(&path)?;
+
+        let name = match file.name {
+            Some(name) => name,
+            None => path
+                .parent()
+                .and_then(|dir| dir.file_name())
+                .map(|dir| dir.to_string_lossy().to_string())
+                .unwrap_or_else(|| "project".to_string()),
+        };
+
+        Ok(Project {
+            name,
+            commands: file.commands,
+        })
+    }
+
+    /// Looks in `start` and its parents up to the git root, or only in `start` outside a repository.
+    fn find_file(start: &Path) -> Option<PathBuf> {
+        let in_repository = start.ancestors().any(|dir| dir.join(".git").exists());
+
+        for dir in start.ancestors() {
+            let file = dir.join(PROJECT_FILE);
+            if file.is_file() {
+                return Some(file);
+            }
+
+            if !in_repository || dir.join(".git").exists() {
+                return None;
+            }
+        }
+
+        None
+    }
+
+    pub fn get_commands_and_tags(&self) -> (Vec<String>, Vec<String>) {
+        self.commands
+            .iter()
+            .map(|c| (c.command.clone(), c.tag.clone()))
+            .unzip()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_discover_stops_at_git_root() {
+        let root = std::env::temp_dir().join(format!("cm-project-{}", std::process::id()));
+        let nested = root.join("repo/crates/app");
+        fs::create_dir_all(&nested).unwrap();
+        fs::create_dir_all(root.join("repo/.git")).unwrap();
+
+        fs::write(
+            root.join(PROJECT_FILE),
+            "[[commands]]\ntag = \"outside\"\ncommand = \"true\"\n",
+        )
+        .unwrap();
+        assert!(Project::discover(&nested).unwrap().is_none());
+
+        fs::write(
+            root.join("repo").join(PROJECT_FILE),
+            "[[commands]]\ntag = \"build\"\ncommand = \"cargo build\"\n",
+        )
+        .unwrap();
+        let project = Project::discover(&nested).unwrap().unwrap();
+
+        assert_eq!(project.name, "repo");
+        assert_eq!(
+            project.get_commands_and_tags(),
+            (vec!["cargo build".to_string()], vec!["build".to_string()])
+        );
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,407 @@
+use crate::core::settings::{expand_home, SyncSettings};
+use crate::db::command_file::CommandFile;
+use crate::db::db::Db;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+const NAMESPACES_DIR: &str = "namespaces";
+const REMOTE: &str = "origin";
+const CONFLICT_MARKER: &str = "<<<<<<< ";
+
+/// Mirrors the personal database into a git working directory, one TOML file per namespace.
+///
+/// The last synced commit is the merge base: local changes are committed, remote ones merged by
+/// git, and the merged tree is imported back into the database before pushing.
+pub struct Syncer<'a> {
+    db: &'a Db,
+    settings: &'a SyncSettings,
+    dir: PathBuf,
+}
+
+impl<'a> Syncer<'a> {
+    pub fn new(db: &'a Db, settings: &'a SyncSettings) -> Self {
+        Syncer {
+            db,
+            settings,
+            dir: expand_home(&settings.dir),
+        }
+    }
+
+    /// Returns a line for every step that changed something.
+    pub fn run(&self) -> Result<Vec<String>, Box<dyn Error>> {
+        let mut report = Vec::new();
+
+        self.init_repository()?;
+
+        if self.is_merging() {
+            let conflicts = get_conflicts(&self.dir)?;
+            if !conflicts.is_empty() {
+                return Err(conflicts_message(&conflicts).into());
+            }
+
+            self.git(&["add", "-A"])?;
+            self.git(&["commit", "--no-edit"])?;
+            report.push("Concluded the merge of remote changes".to_string());
+        } else {
+            self.write_tree()?;
+
+            if self.commit("Update command library")? {
+                report.push("Committed local changes".to_string());
+            }
+
+            if self.has_remote_branch()? && self.pull(&mut report)? {
+                let unmerged = self.git(&["diff", "--name-only", "--diff-filter=U"])?;
+                let conflicts = unmerged
+                    .lines()
+                    .map(|path| self.dir.join(path).display().to_string())
+                    .collect::<Vec<_>>();
+
+                return Err(conflicts_message(&conflicts).into());
+            }
+        }
+
+        let files = self.read_tree()?;
+        self.db.import_files(&files)?;
+
+        if self.settings.remote.is_some() && self.is_ahead() {
+            let refspec = format!("HEAD:{}", self.settings.branch);
+            self.git(&["push", REMOTE, &refspec])?;
+            report.push(format!("Pushed to {}/{}", REMOTE, self.settings.branch));
+        }
+
+        Ok(report)
+    }
+
+    fn init_repository(&self) -> Result<(), Box<dyn Error>> {
+        fs::create_dir_all(self.dir.join(NAMESPACES_DIR))?;
+
+        if !self.dir.join(".git").exists() {
+            self.git(&["init", "--quiet"])?;
+
+            let head = format!("refs/heads/{}", self.settings.branch);
+            self.git(&["symbolic-ref", "HEAD", &head])?;
+        }
+
+        if let Some(remote) = &self.settings.remote {
+            match self.git(&["remote", "get-url", REMOTE]) {
+                Ok(url) if url.trim() == remote => {}
+                Ok(_) => {
+                    self.git(&["remote", "set-url", REMOTE, remote])?;
+                }
+                Err(_) => {
+                    self.git(&["remote", "add", REMOTE, remote])?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn is_merging(&self) -> bool {
+        self.dir.join(".git").join("MERGE_HEAD").exists()
+    }
+
+    fn has_remote_branch(&self) -> Result<bool, Box<dyn Error>> {
+        if self.settings.remote.is_none() {
+            return Ok(false);
+        }
+
+        let output = self.git_output(&[
+            "ls-remote",
+            "--exit-code",
+            "--heads",
+            REMOTE,
+            &self.settings.branch,
+        ])?;
+
+        Ok(output.status.success())
+    }
+
+    fn is_ahead(&self) -> bool {
+        let range = format!("{}/{}..HEAD", REMOTE, self.settings.branch);
+
+        match self.git(&["rev-list", "--count", &range]) {
+            Ok(count) => count.trim() != "0",
+            // The remote branch has never been fetched
+            Err(_) => true,
+        }
+    }
+
+    /// Merges the remote branch, returns true when conflicts are left to resolve.
+    fn pull(&self, report: &mut Vec<String>) -> Result<bool, Box<dyn Error>> {
+        let head = self.git(&["rev-parse", "--verify", "--quiet", "HEAD"]).ok();
+
+        let output = self.git_output(&[
+            "pull",
+            "--no-rebase",
+            "--no-edit",
+            "--allow-unrelated-histories",
+            REMOTE,
+            &self.settings.branch,
+        ])?;
+
+        if !output.status.success() {
+            if self.is_merging() {
+                return Ok(true);
+            }
+
+            return Err(get_error("pull", &output).into());
+        }
+
+        if self.git(&["rev-parse", "HEAD"]).ok() != head {
+            report.push(format!("Merged {}/{}", REMOTE, self.settings.branch));
+        }
+
+        Ok(false)
+    }
+
+    fn commit(&self, message: &str) -> Result<bool, Box<dyn Error>> {
+        self.git(&["add", "-A"])?;
+
+        if self
+            .git_output(&["diff", "--cached", "--quiet"])?
+            .status
+            .success()
+        {
+            return Ok(false);
+        }
+
+        self.git(&["commit", "--quiet", "-m", message])?;
+
+        Ok(true)
+    }
+
+    fn write_tree(&self) -> Result<(), Box<dyn Error>> {
+        let dir = self.dir.join(NAMESPACES_DIR);
+
+        for path in get_namespace_files(&dir)? {
+            fs::remove_file(path)?;
+        }
+
+        for file in self.db.export_files()? {
+            let name = file.name.clone().unwrap_or_default();
+            fs::write(dir.join(get_file_name(&name)), file.to_toml()?)?;
+        }
+
+        Ok(())
+    }
+
+    fn read_tree(&self) -> Result<Vec<CommandFile>, Box<dyn Error>> {
+        let mut files = Vec::new();
+
+        for path in get_namespace_files(&self.dir.join(NAMESPACES_DIR))? {
+            let mut file = CommandFile::load(&path)?;
+
+            if file.name.is_none() {
+                file.name = path.file_stem().map(|s| s.to_string_lossy().to_string());
+            }
+
+            files.push(file);
+        }
+
+        Ok(files)
+    }
+
+    fn git(&self, args: &[&str]) -> Result<String, Box<dyn Error>> {
+        let output = self.git_output(args)?;
+
+        if !output.status.success() {
+            return Err(get_error(args[0], &output).into());
+        }
+
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    fn git_output(&self, args: &[&str]) -> Result<Output, Box<dyn Error>> {
+        Ok(Command::new("git")
+            .arg("-C")
+            .arg(&self.dir)
+            .args(args)
+            .output()?)
+    }
+}
+
+/// Namespace files of an interrupted merge that still contain conflict markers.
+pub fn get_conflicts(dir: &Path) -> Result<Vec<String>, Box<dyn Error>> {
+    if !dir.join(".git").join("MERGE_HEAD").exists() {
+        return Ok(vec![]);
+    }
+
+    let mut conflicts = Vec::new();
+    for path in get_namespace_files(&dir.join(NAMESPACES_DIR))? {
+        let content = fs::read_to_string(&path)?;
+
+        if content
+            .lines()
+            .any(|line| line.starts_with(CONFLICT_MARKER))
+        {
+            conflicts.push(path.display().to_string());
+        }
+    }
+
+    Ok(conflicts)
+}
+
+fn conflicts_message(conflicts: &[String]) -> String {
+    format!(
+        "Sync conflicts to resolve, then run cm sync again:\n  {}",
+        conflicts.join("\n  ")
+    )
+}
+
+fn get_error(command: &str, output: &Output) -> String {
+    format!(
+        "git {} failed: {}",
+        command,
+        String::from_utf8_lossy(&output.stderr).trim()
+    )
+}
+
+fn get_namespace_files(dir: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    let mut files = Vec::new();
+
+    if !dir.is_dir() {
+        return Ok(files);
+    }
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.extension().is_some_and(|ext| ext == "toml") {
+            files.push(path);
+        }
+    }
+
+    files.sort();
+
+    Ok(files)
+}
+
+/// Keeps namespace file names portable, the real name is stored inside the file. Other bytes
+/// are percent-encoded so that two namespaces never share a file.
+fn get_file_name(namespace: &str) -> String {
+    let name = namespace
+        .bytes()
+        .map(|byte| match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' | b'.' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect::<String>();
+
+    format!("{}.toml", name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run_git(dir: &Path, args: &[&str]) {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(dir)
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "{}", get_error(args[0], &output));
+    }
+
+    /// A clone of the remote with its own database.
+    fn clone(root: &Path, remote: &str, name: &str) -> (Db, SyncSettings) {
+        run_git(root, &["clone", "--quiet", remote, name]);
+        let dir = root.join(name);
+        run_git(&dir, &["symbolic-ref", "HEAD", "refs/heads/main"]);
+        run_git(&dir, &["config", "user.name", name]);
+        run_git(&dir, &["config", "user.email", "cm@localhost"]);
+
+        let settings = SyncSettings {
+            dir: dir.display().to_string(),
+            remote: Some(remote.to_string()),
+            branch: "main".to_string(),
+        };
+        (Db::in_memory().unwrap(), settings)
+    }
+
+    fn add_command(db: &Db, namespace: &str, command: &str, tag: &str) {
+        let namespace = namespace.to_string();
+        if db.get_namespace(&namespace).unwrap().is_none() {
+            db.add_namespace(&namespace).unwrap();
+        }
+        db.add_command_and_tag(
+            Some(&command.to_string()),
+            Some(&tag.to_string()),
+            &namespace,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_sync_two_clones() {
+        let root = std::env::temp_dir().join(format!("cm-sync-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let remote = root.join("remote.git").display().to_string();
+        run_git(&root, &["init", "--quiet", "--bare", &remote]);
+
+        let (db_a, settings_a) = clone(&root, &remote, "a");
+        let (db_b, settings_b) = clone(&root, &remote, "b");
+        let sync_a = Syncer::new(&db_a, &settings_a);
+        let sync_b = Syncer::new(&db_b, &settings_b);
+
+        add_command(&db_a, "shell", "ls", "list");
+        sync_a.run().unwrap();
+        sync_b.run().unwrap();
+        assert_eq!(db_b.get_command("list").unwrap(), Some("ls".to_string()));
+
+        // Edits of different namespaces are merged on both sides
+        add_command(&db_a, "git", "git status", "st");
+        add_command(&db_b, "docker", "docker ps", "ps");
+        sync_a.run().unwrap();
+        let report = sync_b.run().unwrap();
+        assert!(report.contains(&"Merged origin/main".to_string()));
+        sync_a.run().unwrap();
+        for db in [&db_a, &db_b] {
+            assert_eq!(
+                db.get_command("st").unwrap(),
+                Some("git status".to_string())
+            );
+            assert_eq!(db.get_command("ps").unwrap(), Some("docker ps".to_string()));
+        }
+
+        // Edits of the same command conflict until the file is resolved
+        db_a.set_description("list", Some("from a")).unwrap();
+        db_b.set_description("list", Some("from b")).unwrap();
+        sync_a.run().unwrap();
+        let error = sync_b.run().unwrap_err().to_string();
+        let dir_b = root.join("b");
+        let conflicts = get_conflicts(&dir_b).unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert!(conflicts[0].ends_with("shell.toml"));
+        assert!(error.contains(&conflicts[0]));
+        assert!(dir_b.join(".git/MERGE_HEAD").exists());
+
+        run_git(&dir_b, &["checkout", "--theirs", "namespaces/shell.toml"]);
+        let report = sync_b.run().unwrap();
+        assert!(report.contains(&"Concluded the merge of remote changes".to_string()));
+        assert!(!dir_b.join(".git/MERGE_HEAD").exists());
+        assert_eq!(
+            db_b.get_descriptions().unwrap().get("list"),
+            Some(&"from a".to_string())
+        );
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_get_file_name() {
+        assert_eq!(get_file_name("docker"), "docker.toml");
+        assert_eq!(get_file_name("a_b"), "a_b.toml");
+        assert_eq!(get_file_name("a b"), "a%20b.toml");
+        assert_eq!(get_file_name("a/b"), "a%2Fb.toml");
+        assert_eq!(get_file_name("a%b"), "a%25b.toml");
+        assert_eq!(get_file_name("café"), "caf%C3%A9.toml");
+    }
+}
@@ -1,13 +1,26 @@
 mod app;
+mod cli;
 mod core;
 mod db;
 mod ui;
 
 use crate::app::app::App;
+use crate::cli::Cli;
 use crate::core::Engine;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let app = App::new()?;
+    let cli = Cli::parse(std::env::args().skip(1))?;
+
+    if let Some(command) = cli.command {
+        if let Err(e) = command.run(cli.profile.as_deref()) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+
+        return Ok(());
+    }
+
+    let app = App::new(cli.profile)?;
     Engine::run(app)?;
 
     Ok(())
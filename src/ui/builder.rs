@@ -1,13 +1,24 @@
+use crate::app::picker::{NamespacePicker, PickAction};
 use crate::app::state::{StatefulList, TabsState};
+use crate::app::status::{Level, Status};
 use crate::core::config::Config;
+use crate::core::fanout::FanOut;
+use crate::core::parser::Binding;
+use crate::core::process::{Process, Stream};
+use crate::core::risk::Risk;
+use crate::core::schedule::ScheduledCommand;
+use crate::core::workflow::{HistoryEntry, StepResult};
+use crate::db::namespace::{Favorite, Namespace, Origin, TrashEntry};
+use crate::ui::scrollbar::Scrollbar;
 
 use std::cell::{RefCell, RefMut};
+use std::collections::BTreeSet;
 use std::rc::Rc;
 use tui::backend::Backend;
 use tui::layout::{Alignment, Constraint, Direction, Layout, Rect};
 use tui::style::{Color, Style};
 use tui::text::{Span, Spans};
-use tui::widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Tabs, Wrap};
+use tui::widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Row, Table, Tabs, Wrap};
 use tui::Frame;
 
 pub struct UiBuilder {
@@ -23,22 +34,133 @@ impl UiBuilder {
         }
     }
 
-    pub fn create_list(&self, title: String, items: &RefMut<StatefulList<String>>) -> List {
-        let list_item = items
+    /// Dangerous items are shown in red, with a marker when `marked` like the favorites.
+    /// Items marked for a bulk action are shown in the marked color on both lists. Only the
+    /// visible items are turned into rows, `risks` and `favorites` are those of these items.
+    /// Every item takes a single row, to stay beside its tag.
+    pub fn create_risk_list(
+        &self,
+        title: String,
+        items: &RefMut<StatefulList<String>>,
+        risks: &[Risk],
+        favorites: &[bool],
+        marks: &BTreeSet<usize>,
+        marked: bool,
+    ) -> List<'_> {
+        let visible = items.get_visible();
+        let list_item = items.items[visible.clone()]
+            .iter()
+            .zip(risks)
+            .zip(favorites)
+            .zip(visible)
+            .map(|(item, i)| (i, item))
+            .filter(|(_, ((item, _), _))| !item.trim().is_empty())
+            .map(|(i, ((item, risk), favorite))| {
+                // One row per item, the details show multi-line commands in full
+                let mut text = item
+                    .lines()
+                    .map(str::trim)
+                    .collect::<Vec<_>>()
+                    .join(&format!(" {} ", self.config.name_config.line_break_marker));
+                if marked && *risk == Risk::Dangerous {
+                    text = format!("{} {}", self.config.name_config.dangerous_marker, text);
+                }
+                if marked && *favorite {
+                    text = format!("{} {}", self.config.name_config.favorite_marker, text);
+                }
+                if marked && marks.contains(&i) {
+                    text = format!("{} {}", self.config.name_config.mark_marker, text);
+                }
+
+                let color = match risk {
+                    _ if marks.contains(&i) => self.config.font_config.marked_fg,
+                    Risk::Dangerous => self.config.font_config.dangerous_fg,
+                    _ => Color::White,
+                };
+                ListItem::new(text).style(Style::default().fg(color))
+            })
+            .collect::<Vec<ListItem>>();
+
+        self.get_list(title, list_item, items.is_selected)
+    }
+
+    pub fn create_namespace_list(
+        &self,
+        title: String,
+        items: &RefMut<StatefulList<Namespace>>,
+    ) -> List<'_> {
+        let visible = items.get_visible();
+        let list_item = items.items[visible.clone()]
+            .iter()
+            .zip(visible)
+            .map(|(namespace, i)| match namespace.origin {
+                Origin::Personal if items.marked.contains(&i) => ListItem::new(format!(
+                    "{} {}",
+                    self.config.name_config.mark_marker, namespace.name
+                ))
+                .style(Style::default().fg(self.config.font_config.marked_fg)),
+                Origin::Personal => ListItem::new(namespace.name.clone())
+                    .style(Style::default().fg(self.config.font_config.text_fg)),
+                Origin::Project => ListItem::new(format!(
+                    "{} {}",
+                    self.config.name_config.project_marker, namespace.name
+                ))
+                .style(Style::default().fg(self.config.font_config.project_fg)),
+                Origin::Team => ListItem::new(format!(
+                    "{} {}",
+                    self.config.name_config.team_marker, namespace.name
+                ))
+                .style(Style::default().fg(self.config.font_config.team_fg)),
+                Origin::Workflow => ListItem::new(format!(
+                    "{} {}",
+                    self.config.name_config.workflow_marker, namespace.name
+                ))
+                .style(Style::default().fg(self.config.font_config.workflow_fg)),
+            })
+            .collect::<Vec<ListItem>>();
+
+        self.get_list(title, list_item, items.is_selected)
+    }
+
+    pub fn create_picker_list(&self, picker: &NamespacePicker) -> List<'_> {
+        let title = match picker.action {
+            PickAction::Move => &self.config.name_config.move_title,
+            PickAction::Duplicate => &self.config.name_config.duplicate_title,
+            PickAction::Merge => &self.config.name_config.merge_title,
+        };
+
+        let list_item = picker
+            .namespaces
             .items
             .iter()
-            .filter(|item| !item.trim().is_empty())
-            .map(|item| ListItem::new(item.clone()).style(Style::default().fg(Color::White)))
+            .map(|namespace| {
+                ListItem::new(namespace.clone())
+                    .style(Style::default().fg(self.config.font_config.text_fg))
+            })
             .collect::<Vec<ListItem>>();
 
-        List::new(list_item)
+        self.get_list(title.clone(), list_item, true)
+    }
+
+    /// Position of the visible items on the right border of the list or table.
+    pub fn create_scrollbar<T>(&self, list: &StatefulList<T>, selected: bool) -> Scrollbar<'_> {
+        Scrollbar::new(
+            list.get_visible(),
+            list.items.len(),
+            &self.config.name_config.scrollbar_thumb,
+            self.get_border_style(selected),
+        )
+    }
+
+    fn get_list<'a>(&'a self, title: String, items: Vec<ListItem<'a>>, selected: bool) -> List<'a> {
+        List::new(items)
             .block(self.get_block(title))
-            .style(self.get_border_style(items.is_selected))
+            .style(self.get_border_style(selected))
             .highlight_style(self.get_highlight_style())
-            .highlight_symbol(&*self.config.name_config.highlight_symbol)
+            .highlight_symbol(&self.config.name_config.highlight_symbol)
     }
 
-    pub fn create_tabs(&self, items: &Rc<RefCell<TabsState>>) -> Tabs {
+    pub fn create_tabs(&self, items: &Rc<RefCell<TabsState>>) -> Tabs<'_> {
         let tabs_ref = items.as_ref().borrow();
 
         let titles = tabs_ref
@@ -66,6 +188,386 @@ impl UiBuilder {
             .select(tabs_ref.index)
     }
 
+    /// Profile names with the active one highlighted, `None` when the database comes from `CM_DB`.
+    pub fn create_profile_tabs(&self, profiles: Vec<String>, active: Option<usize>) -> Tabs<'_> {
+        let titles = profiles
+            .into_iter()
+            .map(|name| Spans::from(Span::raw(name)))
+            .collect::<Vec<Spans>>();
+        let count = titles.len();
+
+        Tabs::new(titles)
+            .block(self.get_block(self.config.name_config.profile_title.clone()))
+            .style(self.get_border_style(false))
+            .highlight_style(self.get_highlight_style())
+            .select(active.unwrap_or(count))
+    }
+
+    /// Running or finished jobs with their pid, state and duration.
+    pub fn create_jobs_list(&self, items: &RefMut<StatefulList<Process>>) -> List<'_> {
+        let list_item = items
+            .items
+            .iter()
+            .map(|job| {
+                let (state, color) = self.get_process_state(job);
+
+                ListItem::new(format!(
+                    "{}  pid {}  {} in {:.1}s",
+                    job.tag,
+                    job.get_pid(),
+                    state,
+                    job.get_duration().as_secs_f32()
+                ))
+                .style(Style::default().fg(color))
+            })
+            .collect::<Vec<ListItem>>();
+
+        self.get_list(
+            self.config.name_config.jobs_title.clone(),
+            list_item,
+            items.is_selected,
+        )
+    }
+
+    /// One row per directory with its state, exit code and duration.
+    pub fn create_fanout_table(&self, fanout: &FanOut) -> Table<'_> {
+        let rows = fanout
+            .runs
+            .iter()
+            .map(|run| {
+                let (state, color, duration) = match &run.process {
+                    Some(process) => {
+                        let (state, color) = self.get_process_state(process);
+                        let duration = format!("{:.1}s", process.get_duration().as_secs_f32());
+
+                        (state, color, duration)
+                    }
+                    None if fanout.is_cancelled() => {
+                        ("cancelled".to_string(), Color::DarkGray, String::new())
+                    }
+                    None => ("pending".to_string(), Color::DarkGray, String::new()),
+                };
+
+                Row::new(vec![run.dir.display().to_string(), state, duration])
+                    .style(Style::default().fg(color))
+            })
+            .collect::<Vec<Row>>();
+
+        let done = fanout
+            .runs
+            .iter()
+            .filter(|run| run.process.as_ref().is_some_and(|p| !p.is_running()))
+            .count();
+        let title = format!(
+            "{} · {} · {}/{}",
+            self.config.name_config.fanout_title,
+            fanout.tag,
+            done,
+            fanout.runs.len()
+        );
+
+        Table::new(rows)
+            .header(Row::new(vec!["Directory", "Status", "Time"]).bottom_margin(1))
+            .widths(&[
+                Constraint::Percentage(60),
+                Constraint::Percentage(25),
+                Constraint::Percentage(15),
+            ])
+            .block(self.get_block(title))
+            .style(self.get_border_style(true))
+            .highlight_style(self.get_highlight_style())
+            .highlight_symbol(&self.config.name_config.highlight_symbol)
+    }
+
+    /// Current message of the status bar, the key hints without one.
+    pub fn create_status_bar(&self, status: &Status, hints: String) -> Paragraph<'_> {
+        let message = match status.get() {
+            Some(message) => message,
+            None => {
+                return Paragraph::new(Span::styled(
+                    format!(" {}", hints),
+                    Style::default().fg(self.config.font_config.hint_fg),
+                ))
+            }
+        };
+
+        let color = match message.level {
+            Level::Info => self.config.font_config.info_fg,
+            Level::Warning => self.config.font_config.warning_fg,
+            Level::Error => self.config.font_config.error_fg,
+        };
+
+        Paragraph::new(Span::styled(
+            format!(" {}", message.text),
+            Style::default().fg(color),
+        ))
+    }
+
+    /// Keys and what they do, one per line.
+    pub fn create_help(&self, bindings: &[&Binding]) -> Paragraph<'_> {
+        let width = bindings
+            .iter()
+            .map(|binding| binding.get_label().chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let lines = bindings
+            .iter()
+            .map(|binding| {
+                Spans::from(vec![
+                    Span::styled(
+                        format!("{:<width$}  ", binding.get_label(), width = width),
+                        Style::default().fg(self.config.font_config.input_fg),
+                    ),
+                    Span::raw(binding.description),
+                ])
+            })
+            .collect::<Vec<Spans>>();
+
+        Paragraph::new(lines).style(Style::default().fg(self.config.font_config.text_fg))
+    }
+
+    /// Latest runs first, with their result.
+    pub fn create_history_table(&self, history: &StatefulList<HistoryEntry>) -> Table<'_> {
+        let rows = history.items[history.get_visible()]
+            .iter()
+            .map(|entry| {
+                let (state, color) = self.get_result_state(&entry.result);
+
+                Row::new(vec![
+                    entry.started_at.clone(),
+                    entry.result.tag.clone(),
+                    state,
+                    entry.command.clone(),
+                ])
+                .style(Style::default().fg(color))
+            })
+            .collect::<Vec<Row>>();
+
+        Table::new(rows)
+            .header(Row::new(vec!["Started", "Tag", "Result", "Command"]).bottom_margin(1))
+            .widths(&[
+                Constraint::Length(19),
+                Constraint::Percentage(20),
+                Constraint::Length(20),
+                Constraint::Percentage(60),
+            ])
+            .column_spacing(2)
+            .block(self.get_block(self.config.name_config.history_title.clone()))
+            .style(self.get_border_style(true))
+            .highlight_style(self.get_highlight_style())
+            .highlight_symbol(&self.config.name_config.highlight_symbol)
+    }
+
+    /// The first nine favorites are numbered with their quick-run key.
+    pub fn create_favorites_table(&self, favorites: &StatefulList<Favorite>) -> Table<'_> {
+        let visible = favorites.get_visible();
+        let rows = favorites.items[visible.clone()]
+            .iter()
+            .zip(visible)
+            .map(|(favorite, i)| {
+                let key = match i {
+                    0..=8 => (i + 1).to_string(),
+                    _ => String::new(),
+                };
+
+                Row::new(vec![
+                    key,
+                    favorite.tag.clone(),
+                    favorite.namespace.clone(),
+                    favorite.command.clone(),
+                ])
+            })
+            .collect::<Vec<Row>>();
+
+        Table::new(rows)
+            .header(Row::new(vec!["Key", "Tag", "Namespace", "Command"]).bottom_margin(1))
+            .widths(&[
+                Constraint::Length(3),
+                Constraint::Percentage(20),
+                Constraint::Percentage(15),
+                Constraint::Percentage(65),
+            ])
+            .block(self.get_block(self.config.name_config.favorites_title.clone()))
+            .style(self.get_border_style(true))
+            .highlight_style(self.get_highlight_style())
+            .highlight_symbol(&self.config.name_config.highlight_symbol)
+    }
+
+    /// Latest deletions first, a namespace with the count of the commands deleted with it.
+    pub fn create_trash_table(&self, trash: &StatefulList<TrashEntry>) -> Table<'_> {
+        let rows = trash.items[trash.get_visible()]
+            .iter()
+            .map(|entry| {
+                let command = match &entry.command {
+                    Some(command) => command.clone(),
+                    None => format!("namespace with {} command(s)", entry.commands),
+                };
+
+                Row::new(vec![
+                    entry.deleted_at.clone(),
+                    entry.tag.clone().unwrap_or_default(),
+                    entry.namespace.clone(),
+                    command,
+                ])
+            })
+            .collect::<Vec<Row>>();
+
+        Table::new(rows)
+            .header(Row::new(vec!["Deleted", "Tag", "Namespace", "Command"]).bottom_margin(1))
+            .widths(&[
+                Constraint::Length(19),
+                Constraint::Percentage(20),
+                Constraint::Percentage(15),
+                Constraint::Percentage(65),
+            ])
+            .column_spacing(2)
+            .block(self.get_block(self.config.name_config.trash_title.clone()))
+            .style(self.get_border_style(true))
+            .highlight_style(self.get_highlight_style())
+            .highlight_symbol(&self.config.name_config.highlight_symbol)
+    }
+
+    /// Names and values, one per row.
+    pub fn create_settings_table(&self, rows: &[(String, String)]) -> Table<'_> {
+        let rows = rows
+            .iter()
+            .map(|(name, value)| Row::new(vec![name.clone(), value.clone()]))
+            .collect::<Vec<Row>>();
+
+        Table::new(rows)
+            .widths(&[Constraint::Length(20), Constraint::Percentage(80)])
+            .block(self.get_block(self.config.name_config.settings_title.clone()))
+            .style(Style::default().fg(self.config.font_config.text_fg))
+    }
+
+    fn get_result_state(&self, result: &StepResult) -> (String, Color) {
+        let (state, color) = match result.exit_code {
+            Some(0) => ("exit 0".to_string(), Color::Green),
+            Some(code) => (format!("exit {}", code), Color::Red),
+            None => ("killed".to_string(), Color::Red),
+        };
+
+        (
+            format!("{} in {:.1}s", state, result.duration.as_secs_f32()),
+            color,
+        )
+    }
+
+    /// Scheduled commands with their next run and the result of their last one.
+    pub fn create_schedule_table(&self, schedules: &[ScheduledCommand]) -> Table<'_> {
+        let rows = schedules
+            .iter()
+            .map(|scheduled| {
+                let (started_at, result, color) = match &scheduled.last_run {
+                    Some((started_at, result)) => {
+                        let (state, color) = self.get_result_state(result);
+
+                        (started_at.clone(), state, color)
+                    }
+                    None => (
+                        String::new(),
+                        String::new(),
+                        self.config.font_config.text_fg,
+                    ),
+                };
+
+                Row::new(vec![
+                    scheduled.tag.clone(),
+                    scheduled.expression.clone(),
+                    scheduled.next_run.clone(),
+                    started_at,
+                    result,
+                ])
+                .style(Style::default().fg(color))
+            })
+            .collect::<Vec<Row>>();
+
+        Table::new(rows)
+            .header(
+                Row::new(vec![
+                    "Tag",
+                    "Schedule",
+                    "Next run",
+                    "Last run",
+                    "Last result",
+                ])
+                .bottom_margin(1),
+            )
+            .widths(&[
+                Constraint::Percentage(20),
+                Constraint::Percentage(20),
+                Constraint::Percentage(20),
+                Constraint::Percentage(20),
+                Constraint::Percentage(20),
+            ])
+            .block(self.get_block(self.config.name_config.schedule_title.clone()))
+            .style(self.get_border_style(true))
+    }
+
+    /// Output lines of a process, its state and duration in the title.
+    pub fn create_output(
+        &self,
+        process: &Process,
+        scroll_back: Option<u16>,
+        height: u16,
+        watching: bool,
+    ) -> Paragraph<'_> {
+        let (state, color) = self.get_process_state(process);
+
+        let mut title = format!(
+            "{} · {} · {} in {:.1}s",
+            self.config.name_config.output_title,
+            process.tag,
+            state,
+            process.get_duration().as_secs_f32()
+        );
+        if watching {
+            title = format!("{} · {}", title, self.config.name_config.watching);
+        }
+
+        let lines = process
+            .lines
+            .iter()
+            .map(|(stream, line)| {
+                let fg = match stream {
+                    Stream::Stdout => self.config.font_config.text_fg,
+                    Stream::Stderr => self.config.font_config.stderr_fg,
+                };
+
+                Spans::from(Span::styled(line.clone(), Style::default().fg(fg)))
+            })
+            .collect::<Vec<Spans>>();
+
+        let visible = height.saturating_sub(2) as usize;
+        let first = lines
+            .len()
+            .saturating_sub(visible + scroll_back.unwrap_or(0) as usize);
+
+        Paragraph::new(lines).scroll((first as u16, 0)).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(Span::styled(title, Style::default().fg(color)))
+                .style(Style::default().fg(self.config.font_config.text_fg)),
+        )
+    }
+
+    fn get_process_state(&self, process: &Process) -> (String, Color) {
+        if process.is_running() {
+            let spinner = &self.config.name_config.spinner;
+            let frame = (process.get_duration().as_millis() / 100) as usize % spinner.len();
+
+            return (format!("{} running", spinner[frame]), Color::Yellow);
+        }
+
+        match process.get_exit_code() {
+            _ if process.is_timed_out() => ("timed out".to_string(), Color::Red),
+            Some(0) => ("exit 0".to_string(), Color::Green),
+            Some(code) => (format!("exit {}", code), Color::Red),
+            None => ("killed".to_string(), Color::Red),
+        }
+    }
+
     pub fn get_border_style(&self, selected: bool) -> Style {
         if selected {
             return Style::default().fg(self.config.font_config.selected_border);
@@ -74,7 +576,7 @@ impl UiBuilder {
         Style::default().fg(self.config.font_config.border)
     }
 
-    pub fn get_block(&self, title: String) -> Block {
+    pub fn get_block(&self, title: String) -> Block<'_> {
         Block::default().title(title).borders(Borders::ALL)
     }
 
@@ -85,20 +587,39 @@ impl UiBuilder {
             .bg(self.config.font_config.highlight_bg)
     }
 
-    pub fn get_confirm_command(&self, alignment: Alignment) -> Paragraph {
-        let text = vec![
+    /// The question, followed by the details of what is confirmed when there are some.
+    pub fn get_confirm_command(
+        &self,
+        question: String,
+        details: Vec<String>,
+        alignment: Alignment,
+    ) -> Paragraph<'_> {
+        let mut text = vec![
             Spans::from(Span::styled(
-                self.config.text_config.confirm_command.clone(),
+                question,
                 Style::default().fg(self.config.font_config.text_fg),
             )),
             Spans::from(Span::raw("")),
-            Spans::from(Span::styled(
-                self.config.text_config.confirm_command_answer.clone(),
-                self.get_highlight_style(),
-            )),
         ];
 
-        Paragraph::new(text).alignment(alignment)
+        if !details.is_empty() {
+            text.extend(details.into_iter().map(|line| {
+                Spans::from(Span::styled(
+                    line,
+                    Style::default().fg(self.config.font_config.input_fg),
+                ))
+            }));
+            text.push(Spans::from(Span::raw("")));
+        }
+
+        text.push(Spans::from(Span::styled(
+            self.config.text_config.confirm_command_answer.clone(),
+            self.get_highlight_style(),
+        )));
+
+        Paragraph::new(text)
+            .alignment(alignment)
+            .wrap(Wrap { trim: false })
     }
 
     pub fn create_highlighted_paragraph(
@@ -106,7 +627,7 @@ impl UiBuilder {
         title: String,
         input: String,
         alignment: Alignment,
-    ) -> Paragraph {
+    ) -> Paragraph<'_> {
         Paragraph::new(Span::styled(
             input,
             Style::default().fg(self.config.font_config.input_fg),
@@ -157,8 +678,8 @@ impl LayoutBuilder {
 
         let layout = self.create(vec![Constraint::Percentage(100)], Direction::Horizontal);
 
-        if margin_ratio.is_some() {
-            let layout = layout.margin(area.height / margin_ratio.unwrap() as u16);
+        if let Some(margin_ratio) = margin_ratio {
+            let layout = layout.margin(area.height / margin_ratio as u16);
 
             return layout.split(area);
         }
@@ -1,4 +1,6 @@
 mod builder;
+mod scrollbar;
+#[allow(clippy::module_inception)]
 pub mod ui;
 
 use crate::App;
@@ -0,0 +1,74 @@
+use std::ops::Range;
+use tui::buffer::Buffer;
+use tui::layout::Rect;
+use tui::style::Style;
+use tui::widgets::Widget;
+
+/// Thumb drawn over the right border of a list with more items than rows, its length and
+/// position follow the items shown.
+pub struct Scrollbar<'a> {
+    visible: Range<usize>,
+    count: usize,
+    symbol: &'a str,
+    style: Style,
+}
+
+impl<'a> Scrollbar<'a> {
+    pub fn new(visible: Range<usize>, count: usize, symbol: &'a str, style: Style) -> Self {
+        Scrollbar {
+            visible,
+            count,
+            symbol,
+            style,
+        }
+    }
+}
+
+impl Widget for Scrollbar<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        // The track is the right border, between the corners
+        if area.width == 0 || area.height < 3 {
+            return;
+        }
+
+        let track = area.height as usize - 2;
+        if let Some((start, length)) = get_thumb(&self.visible, self.count, track) {
+            for y in start..start + length {
+                buf.get_mut(area.right() - 1, area.y + 1 + y as u16)
+                    .set_symbol(self.symbol)
+                    .set_style(self.style);
+            }
+        }
+    }
+}
+
+/// Start and length of the thumb on a track of `track` cells, `None` when every item is shown.
+/// The thumb only reaches the end of the track with the last item.
+fn get_thumb(visible: &Range<usize>, count: usize, track: usize) -> Option<(usize, usize)> {
+    if visible.len() >= count || track == 0 {
+        return None;
+    }
+
+    let length = (track * visible.len() / count).clamp(1, track);
+    let start = match visible.end >= count {
+        true => track - length,
+        false => (track * visible.start / count).min(track - length),
+    };
+
+    Some((start, length))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_thumb() {
+        assert_eq!(get_thumb(&(0..5), 5, 10), None);
+        assert_eq!(get_thumb(&(0..10), 100, 10), Some((0, 1)));
+        assert_eq!(get_thumb(&(45..55), 100, 10), Some((4, 1)));
+        assert_eq!(get_thumb(&(89..99), 100, 10), Some((8, 1)));
+        assert_eq!(get_thumb(&(90..100), 100, 10), Some((9, 1)));
+        assert_eq!(get_thumb(&(0..5), 10, 4), Some((0, 2)));
+    }
+}
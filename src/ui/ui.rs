@@ -1,18 +1,24 @@
 use crate::app::app::App;
 
-use crate::app::event_state::{Confirm, EventType, SubMode, Tab};
+use crate::app::event_state::{Confirm, EventType, Mode, SubMode, Tab};
 use crate::app::input::CursorPosition;
+use crate::app::state::{Areas, StatefulList};
+use crate::core::parser::KeyParser;
+use crate::db::namespace::Origin;
 use crate::ui::builder::{LayoutBuilder, UiBuilder};
+use crate::ui::scrollbar::Scrollbar;
 
 use tui::backend::Backend;
-use tui::layout::{Alignment, Direction, Rect};
+use tui::layout::{Alignment, Constraint, Direction, Margin, Rect};
 use tui::style::{Color, Style};
-use tui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+use tui::text::{Span, Spans};
+use tui::widgets::{Block, Borders, Clear, Paragraph, Table, TableState, Wrap};
 use tui::Frame;
 
 pub fn ui<B: Backend>(f: &mut Frame<B>, app: &mut App) {
     let ui_builder = UiBuilder::new();
     let layout_builder = LayoutBuilder::new();
+    app.areas = Areas::default();
 
     let chunks = layout_builder
         .create(
@@ -21,16 +27,305 @@ pub fn ui<B: Backend>(f: &mut Frame<B>, app: &mut App) {
         )
         .split(f.size());
 
-    // Display tabs
+    // Display tabs, with the profile switcher on the right when profiles are configured
+    let profiles = app
+        .settings
+        .profiles
+        .keys()
+        .cloned()
+        .collect::<Vec<String>>();
+    let tabs_block = if profiles.is_empty() {
+        vec![chunks[0]]
+    } else {
+        let width = profiles.iter().map(|name| name.len() + 3).sum::<usize>() + 1;
+
+        layout_builder
+            .create(
+                vec![Constraint::Min(0), Constraint::Length(width as u16)],
+                Direction::Horizontal,
+            )
+            .split(chunks[0])
+    };
+
     let tabs = ui_builder.create_tabs(&app.tabs);
-    f.render_widget(tabs, chunks[0]);
+    f.render_widget(tabs, tabs_block[0]);
+    app.areas.tabs = get_inner(tabs_block[0]);
+
+    if let Some(rect) = tabs_block.get(1) {
+        let active = app
+            .profile
+            .as_ref()
+            .and_then(|profile| profiles.iter().position(|name| name == profile));
+
+        f.render_widget(ui_builder.create_profile_tabs(profiles, active), *rect);
+    }
 
     match app.event_state.get_tab() {
-        Tab::Tab1 => draw_first_tab(f, chunks[1], app),
+        Tab::Library => draw_library_tab(f, chunks[1], app),
+        Tab::History => draw_history_tab(f, chunks[1], app),
+        Tab::Favorites => draw_favorites_tab(f, chunks[1], app),
+        Tab::Settings => draw_settings_tab(f, chunks[1], app),
+        Tab::Trash => draw_trash_tab(f, chunks[1], app),
+        Tab::Jobs => draw_jobs_tab(f, chunks[1], app),
+        Tab::FanOut => draw_fanout_tab(f, chunks[1], app),
+        Tab::Schedule => draw_schedule_tab(f, chunks[1], app),
     };
+
+    if !app.sync_conflicts.is_empty() {
+        draw_sync_conflicts(f, chunks[1], app);
+    }
+
+    if app.help {
+        draw_help(f, chunks[1], app);
+    }
+
+    let hints = KeyParser::get_hints(app);
+    f.render_widget(ui_builder.create_status_bar(&app.status, hints), chunks[2]);
+}
+
+fn draw_help<B>(f: &mut Frame<B>, rect: Rect, app: &App)
+where
+    B: Backend,
+{
+    let ui_builder = UiBuilder::new();
+    let layout_builder = LayoutBuilder::new();
+
+    let bindings = KeyParser::get_bindings(app);
+    let height = (bindings.len() as u16 + 2) * 100 / rect.height.max(1) + 1;
+
+    let popup_rects = layout_builder.get_popup_rects(
+        app.config.name_config.help_title.clone(),
+        f,
+        rect,
+        None,
+        Some((70, height.min(100))),
+    );
+
+    let help = ui_builder.create_help(&bindings);
+    f.render_widget(
+        help,
+        popup_rects[0].inner(&Margin {
+            vertical: 1,
+            horizontal: 2,
+        }),
+    );
+}
+
+fn draw_sync_conflicts<B>(f: &mut Frame<B>, rect: Rect, app: &App)
+where
+    B: Backend,
+{
+    let layout_builder = LayoutBuilder::new();
+
+    let popup_rects = layout_builder.get_popup_rects(
+        app.config.name_config.sync_conflicts_title.clone(),
+        f,
+        rect,
+        Some(5),
+        Some((70, 40)),
+    );
+
+    let mut text = app
+        .sync_conflicts
+        .iter()
+        .map(|path| Spans::from(Span::raw(path.clone())))
+        .collect::<Vec<Spans>>();
+    text.push(Spans::from(Span::raw("")));
+    text.push(Spans::from(Span::raw(
+        app.config.text_config.sync_conflicts.clone(),
+    )));
+
+    let p = Paragraph::new(text)
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
+        .style(Style::default().fg(Color::Yellow));
+
+    f.render_widget(p, popup_rects[0]);
+}
+
+fn draw_jobs_tab<B>(f: &mut Frame<B>, rect: Rect, app: &mut App)
+where
+    B: Backend,
+{
+    let ui_builder = UiBuilder::new();
+    let layout_builder = LayoutBuilder::new();
+
+    let jobs_block = layout_builder
+        .create(
+            app.config.layout_config.jobs_block.clone(),
+            Direction::Horizontal,
+        )
+        .split(rect);
+
+    let mut jobs = app.jobs.as_ref().borrow_mut();
+
+    if jobs.items.is_empty() {
+        let p = Paragraph::new(app.config.text_config.no_jobs.clone())
+            .alignment(Alignment::Center)
+            .block(ui_builder.get_block(app.config.name_config.jobs_title.clone()))
+            .style(Style::default().fg(app.config.font_config.text_fg));
+
+        f.render_widget(p, rect);
+        return;
+    }
+
+    app.areas.rows = get_inner(jobs_block[0]);
+    app.areas.output = get_inner(jobs_block[1]);
+    jobs.update_offset(app.areas.rows.height as usize);
+
+    let list = ui_builder.create_jobs_list(&jobs);
+    f.render_stateful_widget(list, jobs_block[0], &mut jobs.state);
+
+    let output = ui_builder.create_output(
+        jobs.current_item(),
+        app.job_scroll,
+        jobs_block[1].height,
+        false,
+    );
+    f.render_widget(output, jobs_block[1]);
 }
 
-fn draw_first_tab<B>(f: &mut Frame<B>, rect: Rect, app: &mut App)
+fn draw_fanout_tab<B>(f: &mut Frame<B>, rect: Rect, app: &mut App)
+where
+    B: Backend,
+{
+    let ui_builder = UiBuilder::new();
+    let layout_builder = LayoutBuilder::new();
+
+    let fanout = match app.fanout.as_mut() {
+        Some(fanout) => fanout,
+        None => {
+            let p = Paragraph::new(app.config.text_config.no_fanout.clone())
+                .alignment(Alignment::Center)
+                .block(ui_builder.get_block(app.config.name_config.fanout_title.clone()))
+                .style(Style::default().fg(app.config.font_config.text_fg));
+
+            f.render_widget(p, rect);
+            return;
+        }
+    };
+
+    let fanout_block = if fanout.expanded {
+        layout_builder
+            .create(
+                app.config.layout_config.fanout_block.clone(),
+                Direction::Vertical,
+            )
+            .split(rect)
+    } else {
+        vec![rect]
+    };
+
+    let table = ui_builder.create_fanout_table(fanout);
+    f.render_stateful_widget(table, fanout_block[0], &mut fanout.state.clone());
+
+    if let (Some(rect), Some(process)) = (
+        fanout_block.get(1),
+        fanout.runs[fanout.current()].process.as_ref(),
+    ) {
+        let output = ui_builder.create_output(process, fanout.scroll, rect.height, false);
+        f.render_widget(output, *rect);
+        app.areas.output = get_inner(*rect);
+    }
+}
+
+fn draw_history_tab<B>(f: &mut Frame<B>, rect: Rect, app: &mut App)
+where
+    B: Backend,
+{
+    let ui_builder = UiBuilder::new();
+    let mut history = app.history.as_ref().borrow_mut();
+
+    if history.items.is_empty() {
+        let p = Paragraph::new(app.config.text_config.no_history.clone())
+            .alignment(Alignment::Center)
+            .block(ui_builder.get_block(app.config.name_config.history_title.clone()))
+            .style(Style::default().fg(app.config.font_config.text_fg));
+
+        f.render_widget(p, rect);
+        return;
+    }
+
+    app.areas.rows = draw_table(f, rect, &ui_builder, &mut history, |history| {
+        ui_builder.create_history_table(history)
+    });
+}
+
+fn draw_favorites_tab<B>(f: &mut Frame<B>, rect: Rect, app: &mut App)
+where
+    B: Backend,
+{
+    let ui_builder = UiBuilder::new();
+    let mut favorites = app.favorites.as_ref().borrow_mut();
+
+    if favorites.items.is_empty() {
+        let p = Paragraph::new(app.config.text_config.no_favorites.clone())
+            .alignment(Alignment::Center)
+            .block(ui_builder.get_block(app.config.name_config.favorites_title.clone()))
+            .style(Style::default().fg(app.config.font_config.text_fg));
+
+        f.render_widget(p, rect);
+        return;
+    }
+
+    app.areas.rows = draw_table(f, rect, &ui_builder, &mut favorites, |favorites| {
+        ui_builder.create_favorites_table(favorites)
+    });
+}
+
+fn draw_trash_tab<B>(f: &mut Frame<B>, rect: Rect, app: &mut App)
+where
+    B: Backend,
+{
+    let ui_builder = UiBuilder::new();
+    let mut trash = app.trash.as_ref().borrow_mut();
+
+    if trash.items.is_empty() {
+        let p = Paragraph::new(app.config.text_config.no_trash.clone())
+            .alignment(Alignment::Center)
+            .block(ui_builder.get_block(app.config.name_config.trash_title.clone()))
+            .style(Style::default().fg(app.config.font_config.text_fg));
+
+        f.render_widget(p, rect);
+        return;
+    }
+
+    app.areas.rows = draw_table(f, rect, &ui_builder, &mut trash, |trash| {
+        ui_builder.create_trash_table(trash)
+    });
+}
+
+fn draw_settings_tab<B>(f: &mut Frame<B>, rect: Rect, app: &App)
+where
+    B: Backend,
+{
+    let ui_builder = UiBuilder::new();
+
+    let table = ui_builder.create_settings_table(&app.settings_summary);
+    f.render_widget(table, rect);
+}
+
+fn draw_schedule_tab<B>(f: &mut Frame<B>, rect: Rect, app: &mut App)
+where
+    B: Backend,
+{
+    let ui_builder = UiBuilder::new();
+
+    if app.schedules.is_empty() {
+        let p = Paragraph::new(app.config.text_config.no_schedules.clone())
+            .alignment(Alignment::Center)
+            .block(ui_builder.get_block(app.config.name_config.schedule_title.clone()))
+            .style(Style::default().fg(app.config.font_config.text_fg));
+
+        f.render_widget(p, rect);
+        return;
+    }
+
+    let table = ui_builder.create_schedule_table(&app.schedules);
+    f.render_widget(table, rect);
+}
+
+fn draw_library_tab<B>(f: &mut Frame<B>, rect: Rect, app: &mut App)
 where
     B: Backend,
 {
@@ -73,6 +368,63 @@ where
             f.render_widget(Clear, lists_block[1]);
             f.render_widget(p, lists_block[1]);
         }
+        SubMode::Rename => {
+            let input = String::from_iter(
+                app.inputs
+                    .entry(app.config.name_config.namespace.to_string())
+                    .or_default()
+                    .clone(),
+            );
+
+            let p = ui_builder.create_highlighted_paragraph(
+                app.config.name_config.rename_namespace_title.clone(),
+                input.clone(),
+                Alignment::Left,
+            );
+
+            CursorPosition::set_cursor_position(app, f, lists_block[1], input);
+
+            f.render_widget(Clear, lists_block[1]);
+            f.render_widget(p, lists_block[1]);
+        }
+        SubMode::Prefix => {
+            let input = String::from_iter(
+                app.inputs
+                    .entry(app.config.name_config.tag.to_string())
+                    .or_default()
+                    .clone(),
+            );
+
+            let p = ui_builder.create_highlighted_paragraph(
+                app.config.name_config.prefix_tags_title.clone(),
+                input.clone(),
+                Alignment::Left,
+            );
+
+            CursorPosition::set_cursor_position(app, f, lists_block[1], input);
+
+            f.render_widget(Clear, lists_block[1]);
+            f.render_widget(p, lists_block[1]);
+        }
+        SubMode::Directories => {
+            let input = String::from_iter(
+                app.inputs
+                    .entry(app.config.name_config.directories.to_string())
+                    .or_default()
+                    .clone(),
+            );
+
+            let p = ui_builder.create_highlighted_paragraph(
+                app.config.name_config.add_directories_title.clone(),
+                input.clone(),
+                Alignment::Left,
+            );
+
+            CursorPosition::set_cursor_position(app, f, lists_block[1], input);
+
+            f.render_widget(Clear, lists_block[1]);
+            f.render_widget(p, lists_block[1]);
+        }
         SubMode::Command => match app.event_state.get_event_type() {
             EventType::Command => {
                 let input = String::from_iter(
@@ -142,7 +494,61 @@ where
                 f.render_widget(Clear, lists_block[1]);
                 f.render_widget(p, lists_block[1]);
             }
-            _ => {
+            _ if app.event_state.get_mode() == &Mode::TypeTag => {
+                let popup_rects = layout_builder.get_popup_rects(
+                    app.config.name_config.confirm_title.clone(),
+                    f,
+                    main_block[0],
+                    Some(6),
+                    Some((80, 80)),
+                );
+
+                let confirm_block = layout_builder
+                    .create(
+                        vec![Constraint::Min(0), Constraint::Length(3)],
+                        Direction::Vertical,
+                    )
+                    .split(popup_rects[0]);
+
+                let p = ui_builder
+                    .get_confirm_command(
+                        app.config.text_config.confirm_dangerous.clone(),
+                        app.get_preview_lines(),
+                        Alignment::Left,
+                    )
+                    .style(Style::default().fg(app.config.font_config.dangerous_fg));
+                f.render_widget(p, confirm_block[0]);
+
+                let input = String::from_iter(
+                    app.inputs
+                        .entry(app.config.name_config.tag.to_string())
+                        .or_default()
+                        .clone(),
+                );
+                let p = ui_builder.create_highlighted_paragraph(
+                    app.config.name_config.confirm_tag_title.clone(),
+                    input.clone(),
+                    Alignment::Left,
+                );
+
+                CursorPosition::set_cursor_position(app, f, confirm_block[1], input);
+                f.render_widget(p, confirm_block[1]);
+            }
+            _ if matches!(app.event_state.get_mode(), Mode::Delete | Mode::Sequence) => {
+                let popup_rects = layout_builder.get_popup_rects(
+                    app.config.name_config.confirm_title.clone(),
+                    f,
+                    main_block[0],
+                    Some(6),
+                    Some((80, 80)),
+                );
+
+                let (question, details) = app.get_bulk_summary();
+                let p = ui_builder.get_confirm_command(question, details, Alignment::Left);
+
+                f.render_widget(p, popup_rects[0]);
+            }
+            _ if app.event_state.get_mode() == &Mode::Fork => {
                 let popup_rects = layout_builder.get_popup_rects(
                     app.config.name_config.confirm_title.clone(),
                     f,
@@ -151,57 +557,261 @@ where
                     None,
                 );
 
-                let p = ui_builder.get_confirm_command(Alignment::Center);
+                let p = ui_builder.get_confirm_command(
+                    app.config.text_config.confirm_fork.clone(),
+                    vec![],
+                    Alignment::Center,
+                );
+
+                f.render_widget(p, popup_rects[0]);
+            }
+            _ => {
+                // Larger popup to preview what the command executes
+                let popup_rects = layout_builder.get_popup_rects(
+                    app.config.name_config.confirm_title.clone(),
+                    f,
+                    main_block[0],
+                    Some(6),
+                    Some((80, 80)),
+                );
+
+                let p = ui_builder.get_confirm_command(
+                    app.config.text_config.confirm_command.clone(),
+                    app.get_preview_lines(),
+                    Alignment::Left,
+                );
 
                 f.render_widget(p, popup_rects[0]);
             }
         }
     }
 
+    if let Some(picker) = &mut app.picker {
+        let area = layout_builder.get_centered_rect(40, 50, main_block[0]);
+        let list = ui_builder.create_picker_list(picker);
+
+        f.render_widget(Clear, area);
+        f.render_stateful_widget(list, area, &mut picker.namespaces.state);
+    }
+
     //Command details
     let commands = app.commands.as_ref().borrow_mut();
+    let tags = app.tags.as_ref().borrow();
     let mut command_text = "\n".to_string();
-    if commands.state.selected().is_some() && commands.items.len() > 0 {
-        command_text.push_str(&*commands.items[commands.state.selected().unwrap()].clone());
+    let mut scroll = 0;
+    if commands.state.selected().is_some() && !commands.items.is_empty() {
+        command_text.push_str(&commands.items[commands.state.selected().unwrap()].clone());
+
+        // The scroll is kept while the same command stays selected
+        if let Some((tag, lines)) = &app.details_scroll {
+            if tag == tags.current_item() {
+                scroll = *lines;
+            }
+        }
     }
     drop(commands);
+    drop(tags);
 
+    // Indentation of multi-line commands is kept
     let detail_command_paragraph = Paragraph::new(command_text)
         .alignment(Alignment::Left)
-        .wrap(Wrap { trim: true })
+        .wrap(Wrap { trim: false })
         .style(Style::default().fg(Color::Yellow))
         .block(
             Block::default()
                 .borders(Borders::ALL)
                 .title("Command details")
                 .style(Style::default().fg(Color::White)),
-        );
+        )
+        .scroll((scroll, 0));
 
     f.render_widget(detail_command_paragraph, main_block[1]);
+    app.areas.details = get_inner(main_block[1]);
+
+    if let Some(process) = &app.output {
+        let output = ui_builder.create_output(
+            process,
+            app.output_scroll,
+            main_block[2].height,
+            app.watch.is_some(),
+        );
+        f.render_widget(output, main_block[2]);
+        app.areas.output = get_inner(main_block[2]);
+    }
+}
+
+/// Area inside the borders of a block.
+fn get_inner(rect: Rect) -> Rect {
+    rect.inner(&Margin {
+        vertical: 1,
+        horizontal: 1,
+    })
+}
+
+/// Draws the visible rows of a table and its scrollbar, returns the area of the rows. The
+/// selection of the widget is relative to the visible rows, the list keeps its own.
+fn draw_table<'a, T>(
+    f: &mut Frame<impl Backend>,
+    rect: Rect,
+    ui_builder: &'a UiBuilder,
+    list: &mut StatefulList<T>,
+    create_table: impl FnOnce(&StatefulList<T>) -> Table<'a>,
+) -> Rect {
+    let rows = get_table_rows(rect);
+    list.update_offset(rows.height as usize);
+
+    let mut state = TableState::default();
+    state.select(list.get_visible_state().selected());
+
+    f.render_stateful_widget(create_table(list), rect, &mut state);
+    draw_table_scrollbar(f, rect, ui_builder.create_scrollbar(list, true));
+
+    rows
+}
+
+/// Scrollbar beside the rows of a table, below its header.
+fn draw_table_scrollbar(f: &mut Frame<impl Backend>, rect: Rect, scrollbar: Scrollbar) {
+    let rows = get_table_rows(rect);
+
+    f.render_widget(
+        scrollbar,
+        Rect {
+            y: rows.y - 1,
+            height: rows.height + 2,
+            ..rect
+        },
+    );
+}
+
+/// Rows of a table drawn in the area, below its header and the margin after it.
+fn get_table_rows(rect: Rect) -> Rect {
+    let inner = get_inner(rect);
+
+    Rect {
+        y: inner.y + 2,
+        height: inner.height.saturating_sub(2),
+        ..inner
+    }
 }
 
-fn display_lists(app: &mut App, f: &mut Frame<impl Backend>, lists_block: &Vec<Rect>) {
+fn display_lists(app: &mut App, f: &mut Frame<impl Backend>, lists_block: &[Rect]) {
     let ui_builder = UiBuilder::new();
 
+    app.areas.namespaces = get_inner(lists_block[0]);
+    app.areas.commands = get_inner(lists_block[1]);
+    app.areas.tags = get_inner(lists_block[2]);
+
+    let mut namespaces = app.namespaces.as_ref().borrow_mut();
+    namespaces.update_offset(app.areas.namespaces.height as usize);
+    let namespace_list = ui_builder.create_namespace_list(
+        app.config.name_config.namespaces_title.to_string(),
+        &namespaces,
+    );
+    f.render_stateful_widget(
+        namespace_list,
+        lists_block[0],
+        &mut namespaces.get_visible_state(),
+    );
+    f.render_widget(
+        ui_builder.create_scrollbar(&namespaces, namespaces.is_selected),
+        lists_block[0],
+    );
+
+    let mut commands = app.commands.as_ref().borrow_mut();
+    let mut tags = app.tags.as_ref().borrow_mut();
+    commands.update_offset(app.areas.commands.height as usize);
+    tags.update_offset(app.areas.tags.height as usize);
+
+    // Only the visible commands are drawn, the risk of the others is not needed
+    let visible = commands.get_visible();
+    let risks = match namespaces.items.get(namespaces.current()) {
+        Some(namespace) => commands.items[visible.clone()]
+            .iter()
+            .zip(tags.items[visible.clone()].iter())
+            .map(|(command, tag)| app.get_risk(namespace, command, tag))
+            .collect::<Vec<_>>(),
+        None => vec![],
+    };
+    // Only commands of the personal library are starred
+    let personal = namespaces
+        .items
+        .get(namespaces.current())
+        .is_some_and(|namespace| namespace.origin == Origin::Personal);
+    let favorites = tags.items[visible]
+        .iter()
+        .map(|tag| personal && app.is_favorite(tag))
+        .collect::<Vec<_>>();
+
+    // Commands are marked in the tags list
+    let marks = tags.marked.clone();
+
     let list = vec![
         (
-            app.namespaces.as_ref().borrow_mut(),
-            app.config.name_config.namespaces_title.to_string(),
-        ),
-        (
-            app.commands.as_ref().borrow_mut(),
+            &mut commands,
             app.config.name_config.commands_title.to_string(),
+            false,
         ),
         (
-            app.tags.as_ref().borrow_mut(),
+            &mut tags,
             app.config.name_config.tags_title.to_string(),
+            true,
         ),
     ];
 
     list.into_iter()
         .enumerate()
-        .for_each(|(i, (mut list, title))| {
-            let item_list = ui_builder.create_list(title.clone(), &list);
-            f.render_stateful_widget(item_list, lists_block[i], &mut list.state);
+        .for_each(|(i, (list, title, marked))| {
+            let item_list = ui_builder.create_risk_list(
+                title.clone(),
+                list,
+                &risks,
+                &favorites,
+                &marks,
+                marked,
+            );
+            f.render_stateful_widget(item_list, lists_block[i + 1], &mut list.get_visible_state());
+            f.render_widget(
+                ui_builder.create_scrollbar(list, list.is_selected),
+                lists_block[i + 1],
+            );
         });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::namespace::TrashEntry;
+    use tui::backend::TestBackend;
+    use tui::Terminal;
+
+    #[test]
+    fn test_draw_table_keeps_selection() {
+        let ui_builder = UiBuilder::new();
+        let entries = (0..50)
+            .map(|id| TrashEntry {
+                id,
+                namespace: "ops".to_string(),
+                tag: Some(format!("ops:{}", id)),
+                command: Some("true".to_string()),
+                commands: 0,
+                deleted_at: "2026-10-19 10:00:00".to_string(),
+            })
+            .collect();
+        let mut trash = StatefulList::with_items(entries);
+        trash.state.select(Some(40));
+
+        let mut terminal = Terminal::new(TestBackend::new(80, 12)).unwrap();
+        for _ in 0..3 {
+            terminal
+                .draw(|f| {
+                    draw_table(f, f.size(), &ui_builder, &mut trash, |trash| {
+                        ui_builder.create_trash_table(trash)
+                    });
+                })
+                .unwrap();
+        }
+
+        assert_eq!(trash.state.selected(), Some(40));
+        assert_eq!(trash.get_visible(), 33..41);
+    }
+}